@@ -82,6 +82,9 @@ def partition_sql(
     partition_on: str,
     partition_num: int,
     partition_range: Optional[Tuple[int, int]] = None,
+    partition_order: bool = False,
+    partition_range_in_query: bool = False,
+    partition_quote_column: bool = False,
 ):
     """
     Partition the sql query
@@ -98,6 +101,12 @@ def partition_sql(
       how many partitions to generate.
     partition_range
       the value range of the partition column.
+    partition_order
+      whether to sort each partition on the partition column; see `read_sql`.
+    partition_range_in_query
+      whether to compute the value range in each partition query; see `read_sql`.
+    partition_quote_column
+      whether to quote the partition column in the generated queries; see `read_sql`.
     """
     partition_query = {
         "query": query,
@@ -105,6 +114,9 @@ def partition_sql(
         "min": partition_range[0] if partition_range else None,
         "max": partition_range[1] if partition_range else None,
         "num": partition_num,
+        "order": partition_order,
+        "range_in_query": partition_range_in_query,
+        "quote_column": partition_quote_column,
     }
     return _partition_sql(conn, partition_query)
 
@@ -118,6 +130,9 @@ def read_sql(
     partition_on: Optional[str] = None,
     partition_range: Optional[Tuple[int, int]] = None,
     partition_num: Optional[int] = None,
+    partition_order: bool = False,
+    partition_range_in_query: bool = False,
+    partition_quote_column: bool = False,
     index_col: Optional[str] = None,
 ):
     """
@@ -140,6 +155,21 @@ def read_sql(
       the value range of the partition column.
     partition_num
       how many partitions to generate.
+    partition_order
+      whether to sort each partition on the partition column. By default the rows come in no
+      particular order, usually the physical order of the table. Sorting helps destinations that
+      write faster in key order, at the cost of a sort on the database for every partition, which
+      is cheap only if the partition column is indexed.
+    partition_range_in_query
+      whether to compute the value range of the partition column in each partition query (postgres
+      only), instead of with a query of its own beforehand. The range is then read from the same
+      snapshot as the rows of the partition, so a row written in between is not missed for being
+      beyond the precomputed range, at the cost of a scan for the range per partition. Partitions
+      still take a snapshot each, so concurrent writes can shift the boundaries between them.
+    partition_quote_column
+      whether to quote the partition column in the generated queries, so that a mixed case or
+      reserved name is matched exactly as given. For postgres the column is also checked to be in
+      the result of the query first. Without quoting, the column must be a plain identifier.
     index_col
       the index column to set; only applicable for return type "pandas", "modin", "dask".
 
@@ -202,6 +232,9 @@ def read_sql(
                 "min": partition_range[0] if partition_range else None,
                 "max": partition_range[1] if partition_range else None,
                 "num": partition_num,
+                "order": partition_order,
+                "range_in_query": partition_range_in_query,
+                "quote_column": partition_quote_column,
             }
             queries = None
     elif isinstance(query, list):
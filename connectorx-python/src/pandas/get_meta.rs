@@ -14,8 +14,8 @@ use connectorx::{
         mssql::MsSQLSource,
         mysql::{BinaryProtocol as MySQLBinaryProtocol, MySQLSource, TextProtocol},
         postgres::{
-            rewrite_tls_args, BinaryProtocol as PgBinaryProtocol, CSVProtocol, CursorProtocol,
-            PostgresSource,
+            rewrite_tls_args, AutoProtocol, BinaryProtocol as PgBinaryProtocol, CSVProtocol,
+            CursorProtocol, PostgresSource,
         },
         sqlite::SQLiteSource,
     },
@@ -107,6 +107,29 @@ pub fn get_meta<'a>(py: Python<'a>, conn: &str, protocol: &str, query: String) -
                     debug!("Running dispatcher");
                     dispatcher.get_meta()?;
                 }
+                ("auto", Some(tls_conn)) => {
+                    let sb =
+                        PostgresSource::<AutoProtocol, MakeTlsConnector>::new(config, tls_conn, 1)?;
+                    let dispatcher = Dispatcher::<
+                        _,
+                        _,
+                        PostgresPandasTransport<AutoProtocol, MakeTlsConnector>,
+                    >::new(sb, &mut destination, queries, None);
+                    debug!("Running dispatcher");
+                    dispatcher.get_meta()?;
+                }
+                ("auto", None) => {
+                    let sb = PostgresSource::<AutoProtocol, NoTls>::new(config, NoTls, 1)?;
+                    let dispatcher =
+                        Dispatcher::<_, _, PostgresPandasTransport<AutoProtocol, NoTls>>::new(
+                            sb,
+                            &mut destination,
+                            queries,
+                            None,
+                        );
+                    debug!("Running dispatcher");
+                    dispatcher.get_meta()?;
+                }
                 _ => unimplemented!("{} protocol not supported", protocol),
             }
         }
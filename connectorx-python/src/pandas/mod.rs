@@ -19,7 +19,8 @@ use connectorx::{
     sources::{
         mysql::{BinaryProtocol as MySQLBinaryProtocol, TextProtocol},
         postgres::{
-            rewrite_tls_args, BinaryProtocol as PgBinaryProtocol, CSVProtocol, CursorProtocol,
+            rewrite_tls_args, AutoProtocol, BinaryProtocol as PgBinaryProtocol, CSVProtocol,
+            CursorProtocol,
         },
     },
     sql::CXQuery,
@@ -130,6 +131,33 @@ pub fn write_pandas<'a>(
                     );
                     dispatcher.run()?;
                 }
+                ("auto", Some(tls_conn)) => {
+                    let sb = PostgresSource::<AutoProtocol, MakeTlsConnector>::new(
+                        config,
+                        tls_conn,
+                        queries.len(),
+                    )?;
+                    let dispatcher = Dispatcher::<
+                        _,
+                        _,
+                        PostgresPandasTransport<AutoProtocol, MakeTlsConnector>,
+                    >::new(
+                        sb, &mut destination, queries, origin_query
+                    );
+                    dispatcher.run()?;
+                }
+                ("auto", None) => {
+                    let sb =
+                        PostgresSource::<AutoProtocol, NoTls>::new(config, NoTls, queries.len())?;
+                    let dispatcher =
+                        Dispatcher::<_, _, PostgresPandasTransport<AutoProtocol, NoTls>>::new(
+                            sb,
+                            &mut destination,
+                            queries,
+                            origin_query,
+                        );
+                    dispatcher.run()?;
+                }
                 _ => unimplemented!("{} protocol not supported", protocol),
             }
         }
@@ -4,7 +4,8 @@ use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use connectorx::{
     impl_transport,
     sources::postgres::{
-        BinaryProtocol, CSVProtocol, CursorProtocol, PostgresSource, PostgresTypeSystem,
+        AutoProtocol, BinaryProtocol, CSVProtocol, CursorProtocol, PostgresSource,
+        PostgresTypeSystem,
     },
     typesystem::TypeConversion,
 };
@@ -29,30 +30,43 @@ macro_rules! impl_postgres_transport {
                 { Float4[f32]                                   => F64[f64]                 | conversion auto }
                 { Float8[f64]                                   => F64[f64]                 | conversion auto }
                 { Numeric[Decimal]                              => F64[f64]                 | conversion option }
+                { Money[Decimal]                                => F64[f64]                 | conversion none }
                 { Int2[i16]                                     => I64[i64]                 | conversion auto }
                 { Int4[i32]                                     => I64[i64]                 | conversion auto }
                 { Int8[i64]                                     => I64[i64]                 | conversion auto }
+                { Oid[u32]                                      => I64[i64]                 | conversion auto }
                 { Int2Array[Vec<i16>]                           => I64Array[Vec<i64>]       | conversion auto_vec }
                 { Int4Array[Vec<i32>]                           => I64Array[Vec<i64>]       | conversion auto_vec }
                 { Int8Array[Vec<i64>]                           => I64Array[Vec<i64>]       | conversion auto }
+                { Int2Vector[Vec<i16>]                          => I64Array[Vec<i64>]       | conversion none }
+                { OidArray[Vec<u32>]                            => I64Array[Vec<i64>]       | conversion auto_vec }
+                { OidVector[Vec<u32>]                           => I64Array[Vec<i64>]       | conversion none }
                 { Float4Array[Vec<f32>]                         => F64Array[Vec<f64>]       | conversion auto_vec }
                 { Float8Array[Vec<f64>]                         => F64Array[Vec<f64>]       | conversion auto }
                 { NumericArray[Vec<Decimal>]                    => F64Array[Vec<f64>]       | conversion option }
+                { MoneyArray[Vec<Decimal>]                      => F64Array[Vec<f64>]       | conversion none }
                 { Bool[bool]                                    => Bool[bool]               | conversion auto }
+                { Bit[Vec<bool>]                                => String[String]           | conversion option }
                 { Char[i8]                                      => Char[char]               | conversion option }
                 { Text[&'r str]                                 => Str[&'r str]             | conversion auto }
                 { BpChar[&'r str]                               => Str[&'r str]             | conversion none }
                 { VarChar[&'r str]                              => Str[&'r str]             | conversion none }
+                { Name[&'r str]                                 => Str[&'r str]             | conversion none }
+                { Unsupported[&'r str]                          => Str[&'r str]             | conversion none }
                 { Timestamp[NaiveDateTime]                      => DateTime[DateTime<Utc>]  | conversion option }
                 { TimestampTz[DateTime<Utc>]                    => DateTime[DateTime<Utc>]  | conversion auto }
                 { Date[NaiveDate]                               => DateTime[DateTime<Utc>]  | conversion option }
                 { UUID[Uuid]                                    => String[String]           | conversion option }
                 { JSON[Value]                                   => String[String]           | conversion option }
                 { JSONB[Value]                                  => String[String]           | conversion none }
+                { RecordArray[Vec<Value>]                       => String[String]           | conversion option }
                 { Time[NaiveTime]                               => String[String]           | conversion option }
                 { ByteA[Vec<u8>]                                => Bytes[Vec<u8>]           | conversion auto }
                 { Enum[&'r str]                                 => Str[&'r str]             | conversion none }
+                { NodeTree[&'r str]                             => Str[&'r str]             | conversion none }
+                { Xml[&'r str]                                  => Str[&'r str]             | conversion none }
                 { HSTORE[HashMap<String, Option<String>>]       => String[String]           | conversion option }
+                { Geometry[String]                              => String[String]           | conversion owned }
             }
         );
     }
@@ -64,6 +78,8 @@ impl_postgres_transport!(CSVProtocol, NoTls);
 impl_postgres_transport!(CSVProtocol, MakeTlsConnector);
 impl_postgres_transport!(CursorProtocol, NoTls);
 impl_postgres_transport!(CursorProtocol, MakeTlsConnector);
+impl_postgres_transport!(AutoProtocol, NoTls);
+impl_postgres_transport!(AutoProtocol, MakeTlsConnector);
 
 impl<'py, P, C> TypeConversion<HashMap<String, Option<String>>, String>
     for PostgresPandasTransport<'py, P, C>
@@ -84,6 +100,12 @@ impl<'py, P, C> TypeConversion<Vec<Decimal>, Vec<f64>> for PostgresPandasTranspo
     }
 }
 
+impl<'py, P, C> TypeConversion<Vec<bool>, String> for PostgresPandasTransport<'py, P, C> {
+    fn convert(val: Vec<bool>) -> String {
+        val.iter().map(|&b| if b { '1' } else { '0' }).collect()
+    }
+}
+
 impl<'py, P, C> TypeConversion<Decimal, f64> for PostgresPandasTransport<'py, P, C> {
     fn convert(val: Decimal) -> f64 {
         val.to_f64()
@@ -128,3 +150,9 @@ impl<'py, P, C> TypeConversion<Value, String> for PostgresPandasTransport<'py, P
         to_string(&val).unwrap()
     }
 }
+
+impl<'py, P, C> TypeConversion<Vec<Value>, String> for PostgresPandasTransport<'py, P, C> {
+    fn convert(val: Vec<Value>) -> String {
+        to_string(&val).unwrap()
+    }
+}
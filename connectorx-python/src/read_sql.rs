@@ -1,4 +1,6 @@
-use crate::source_router::{get_col_range, get_part_query, parse_source};
+use crate::source_router::{
+    get_col_range, get_part_query, get_part_query_in_range, parse_source, quote_part_col,
+};
 use connectorx::{source_router::SourceConn, sql::CXQuery};
 use dict_derive::FromPyObject;
 use fehler::throw;
@@ -12,6 +14,9 @@ pub struct PartitionQuery {
     min: Option<i64>,
     max: Option<i64>,
     num: usize,
+    order: bool,
+    range_in_query: bool,
+    quote_column: bool,
 }
 
 impl PartitionQuery {
@@ -22,15 +27,60 @@ impl PartitionQuery {
             min,
             max,
             num,
+            order: false,
+            range_in_query: false,
+            quote_column: false,
         }
     }
+
+    /// Sort each partition on the partition column.
+    pub fn order(mut self, order: bool) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Compute the value range of the partition column in each partition query.
+    pub fn range_in_query(mut self, range_in_query: bool) -> Self {
+        self.range_in_query = range_in_query;
+        self
+    }
+
+    /// Quote the partition column in the generated queries. Off by default, as an unquoted
+    /// column is folded like the rest of the query (e.g. `L_ORDERKEY` matches `l_orderkey` in
+    /// postgres) and may be an expression, which quoting would break.
+    pub fn quote_column(mut self, quote_column: bool) -> Self {
+        self.quote_column = quote_column;
+        self
+    }
 }
 
 pub fn partition(part: &PartitionQuery, source_conn: &SourceConn) -> PyResult<Vec<CXQuery>> {
     let mut queries = vec![];
+    let column = match part.quote_column {
+        true => quote_part_col(source_conn, &part.query, &part.column)?,
+        false => part.column.clone(),
+    };
+    if part.range_in_query {
+        if part.min.is_some() || part.max.is_some() {
+            throw!(PyValueError::new_err(
+                "partition_range cannot be specified with the range computed in the query",
+            ));
+        }
+        for i in 0..part.num {
+            queries.push(get_part_query_in_range(
+                source_conn,
+                &part.query,
+                &column,
+                i,
+                part.num,
+                part.order,
+            )?);
+        }
+        return Ok(queries);
+    }
     let num = part.num as i64;
     let (min, max) = match (part.min, part.max) {
-        (None, None) => get_col_range(source_conn, &part.query, &part.column)?,
+        (None, None) => get_col_range(source_conn, &part.query, &column)?,
         (Some(min), Some(max)) => (min, max),
         _ => throw!(PyValueError::new_err(
             "partition_query range can not be partially specified",
@@ -45,7 +95,8 @@ pub fn partition(part: &PartitionQuery, source_conn: &SourceConn) -> PyResult<Ve
             true => max + 1,
             false => min + (i + 1) * partition_size,
         };
-        let partition_query = get_part_query(source_conn, &part.query, &part.column, lower, upper)?;
+        let partition_query =
+            get_part_query(source_conn, &part.query, &column, lower, upper, part.order)?;
         queries.push(partition_query);
     }
     Ok(queries)
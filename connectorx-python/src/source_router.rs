@@ -2,20 +2,23 @@ use crate::errors::{ConnectorXPythonError, Result};
 use anyhow::anyhow;
 use connectorx::source_router::{SourceConn, SourceType};
 use connectorx::{
+    prelude::Source,
     sources::{
         bigquery::BigQueryDialect,
         mssql::{mssql_config, FloatN, IntN, MsSQLTypeSystem},
         mysql::{MySQLSourceError, MySQLTypeSystem},
         oracle::{connect_oracle, OracleDialect},
-        postgres::{rewrite_tls_args, PostgresTypeSystem},
+        postgres::{rewrite_tls_args, CursorProtocol, PostgresSource, PostgresTypeSystem},
     },
     sql::{
-        get_partition_range_query, get_partition_range_query_sep, single_col_partition_query,
-        CXQuery,
+        get_partition_range_query, get_partition_range_query_sep, quote_ident,
+        single_col_partition_query, single_col_partition_query_in_range, CXQuery,
     },
 };
 use fehler::{throw, throws};
 use gcp_bigquery_client;
+use postgres::NoTls;
+use postgres_openssl::MakeTlsConnector;
 use r2d2_mysql::mysql::{prelude::Queryable, Opts, Pool, Row};
 use rusqlite::{types::Type, Connection};
 use rust_decimal::{prelude::ToPrimitive, Decimal};
@@ -56,30 +59,97 @@ pub fn get_part_query(
     col: &str,
     lower: i64,
     upper: i64,
+    order: bool,
 ) -> CXQuery<String> {
     let query = match source_conn.ty {
         SourceType::Postgres => {
-            single_col_partition_query(query, col, lower, upper, &PostgreSqlDialect {})?
+            single_col_partition_query(query, col, lower, upper, order, &PostgreSqlDialect {})?
         }
         SourceType::SQLite => {
-            single_col_partition_query(query, col, lower, upper, &SQLiteDialect {})?
+            single_col_partition_query(query, col, lower, upper, order, &SQLiteDialect {})?
         }
         SourceType::MySQL => {
-            single_col_partition_query(query, col, lower, upper, &MySqlDialect {})?
+            single_col_partition_query(query, col, lower, upper, order, &MySqlDialect {})?
         }
         SourceType::MsSQL => {
-            single_col_partition_query(query, col, lower, upper, &MsSqlDialect {})?
+            single_col_partition_query(query, col, lower, upper, order, &MsSqlDialect {})?
         }
         SourceType::Oracle => {
-            single_col_partition_query(query, col, lower, upper, &OracleDialect {})?
+            single_col_partition_query(query, col, lower, upper, order, &OracleDialect {})?
         }
         SourceType::BigQuery => {
-            single_col_partition_query(query, col, lower, upper, &BigQueryDialect {})?
+            single_col_partition_query(query, col, lower, upper, order, &BigQueryDialect {})?
         }
     };
     CXQuery::Wrapped(query)
 }
 
+// partition `part` of `num` with the range computed in the query, see
+// `single_col_partition_query_in_range`
+#[throws(ConnectorXPythonError)]
+pub fn get_part_query_in_range(
+    source_conn: &SourceConn,
+    query: &str,
+    col: &str,
+    part: usize,
+    num: usize,
+    order: bool,
+) -> CXQuery<String> {
+    let query = match source_conn.ty {
+        SourceType::Postgres => single_col_partition_query_in_range(query, col, part, num, order)?,
+        _ => throw!(anyhow!(
+            "computing the partition range in the query is only supported for postgres"
+        )),
+    };
+    CXQuery::Wrapped(query)
+}
+
+// the partition column quoted for the dialect of the source, see `quote_ident`. For postgres it
+// is checked first to be a column of the result of `query`.
+#[throws(ConnectorXPythonError)]
+pub fn quote_part_col(source_conn: &SourceConn, query: &str, col: &str) -> String {
+    match source_conn.ty {
+        SourceType::Postgres => {
+            let names = pg_get_names(&source_conn.conn, query)?;
+            if !names.iter().any(|name| name == col) {
+                throw!(anyhow!(
+                    "partition column {:?} is not in the result: {:?}",
+                    col,
+                    names
+                ));
+            }
+            quote_ident(col, &PostgreSqlDialect {})
+        }
+        SourceType::SQLite => quote_ident(col, &SQLiteDialect {}),
+        SourceType::MySQL => quote_ident(col, &MySqlDialect {}),
+        SourceType::MsSQL => quote_ident(col, &MsSqlDialect {}),
+        SourceType::Oracle => quote_ident(col, &OracleDialect {}),
+        SourceType::BigQuery => quote_ident(col, &BigQueryDialect {}),
+    }
+}
+
+// the column names `fetch_metadata` finds for `query`
+#[throws(ConnectorXPythonError)]
+fn pg_get_names(conn: &Url, query: &str) -> Vec<String> {
+    let (config, tls) = rewrite_tls_args(conn)?;
+    let queries = [CXQuery::naked(query)];
+    match tls {
+        None => {
+            let mut source = PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, 1)?;
+            source.set_queries(&queries);
+            source.fetch_metadata()?;
+            source.names()
+        }
+        Some(tls_conn) => {
+            let mut source =
+                PostgresSource::<CursorProtocol, MakeTlsConnector>::new(config, tls_conn, 1)?;
+            source.set_queries(&queries);
+            source.fetch_metadata()?;
+            source.names()
+        }
+    }
+}
+
 #[throws(ConnectorXPythonError)]
 fn pg_get_partition_range(conn: &Url, query: &str, col: &str) -> (i64, i64) {
     let (config, tls) = rewrite_tls_args(conn)?;
@@ -0,0 +1,71 @@
+use connectorx::{
+    prelude::*,
+    sources::postgres::{
+        rewrite_tls_args, BinaryProtocol, PostgresSource, PostgresSourcePartition,
+    },
+    sql::CXQuery,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use postgres::NoTls;
+use std::env;
+use url::Url;
+
+const QUERY: &str = "select i::int8 as test_int from generate_series(1, 100000) as t(i)";
+
+fn partition(
+    source: &PostgresSource<BinaryProtocol, NoTls>,
+) -> PostgresSourcePartition<BinaryProtocol, NoTls> {
+    let mut source = source.reset();
+    source.set_queries(&[CXQuery::naked(QUERY)]);
+    source.fetch_metadata().unwrap();
+    source.partition().unwrap().remove(0)
+}
+
+fn bench_produce_i64(c: &mut Criterion) {
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    let mut group = c.benchmark_group("postgres_binary_i64");
+    group.sample_size(20);
+
+    group.bench_function("produce", |b| {
+        b.iter(|| {
+            let mut partition = partition(&source);
+            let mut parser = partition.parser().unwrap();
+            let mut sum = 0i64;
+            loop {
+                let (n, is_last) = parser.fetch_next().unwrap();
+                for _ in 0..n {
+                    let v: i64 = parser.produce().unwrap();
+                    sum += v;
+                }
+                if is_last {
+                    break;
+                }
+            }
+            sum
+        })
+    });
+
+    group.bench_function("produce_all", |b| {
+        b.iter(|| {
+            let mut partition = partition(&source);
+            let mut parser = partition.parser().unwrap();
+            let mut sum = 0i64;
+            loop {
+                let (_, is_last) = parser.fetch_next().unwrap();
+                sum += parser.produce_all::<i64>(0).unwrap().iter().sum::<i64>();
+                if is_last {
+                    break;
+                }
+            }
+            sum
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_produce_i64);
+criterion_main!(benches);
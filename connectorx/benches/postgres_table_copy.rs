@@ -0,0 +1,67 @@
+use connectorx::{
+    prelude::*,
+    sources::postgres::{rewrite_tls_args, BinaryProtocol, PostgresSource},
+    sql::CXQuery,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use postgres::NoTls;
+use std::env;
+use url::Url;
+
+const NROWS: i64 = 1_000_000;
+
+// read all of `query` with the binary protocol, returning the number of rows
+fn read(source: &PostgresSource<BinaryProtocol, NoTls>, query: &str) -> usize {
+    let mut source = source.reset();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let mut nrows = 0;
+    loop {
+        let (n, is_last) = parser.fetch_next().unwrap();
+        for _ in 0..n {
+            let _: i64 = parser.produce().unwrap();
+            let _: f64 = parser.produce().unwrap();
+        }
+        nrows += n;
+        if is_last {
+            break;
+        }
+    }
+    nrows
+}
+
+fn bench_table_copy(c: &mut Criterion) {
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(&format!(
+            "DROP TABLE IF EXISTS cx_bench_copy;
+            CREATE TABLE cx_bench_copy AS
+                SELECT i::int8 AS id, random() AS value FROM generate_series(1, {}) AS t(i);",
+            NROWS
+        ))
+        .unwrap();
+
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    let mut group = c.benchmark_group("postgres_binary_table_copy");
+    group.sample_size(10);
+
+    // copied from the table directly
+    group.bench_function("table", |b| {
+        b.iter(|| read(&source, "select id, value from cx_bench_copy"))
+    });
+
+    // the predicate makes it a `COPY (query)`
+    group.bench_function("query", |b| {
+        b.iter(|| read(&source, "select id, value from cx_bench_copy where true"))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_table_copy);
+criterion_main!(benches);
@@ -132,6 +132,9 @@ pub enum ConnectorXError {
     #[error("Only support single query with SELECT statement, got {0}.")]
     SqlQueryNotSupported(String),
 
+    #[error("Partition column {0} is not an identifier.")]
+    InvalidPartitionColumn(String),
+
     #[error("Cannot get total number of rows in advance.")]
     CountError(),
 
@@ -152,12 +155,21 @@ impl ConnectorXError {
     pub fn cannot_produce<T>(context: Option<String>) -> Self {
         ConnectorXError::CannotProduce(type_name::<T>(), context.into())
     }
+
+    /// Like `cannot_produce`, naming the column the value came from.
+    pub fn cannot_produce_column<T>(column: &str, context: Option<String>) -> Self {
+        ConnectorXError::CannotProduce(
+            type_name::<T>(),
+            ProduceContext::Column(column.to_string(), context),
+        )
+    }
 }
 
 #[derive(Debug)]
 pub enum ProduceContext {
     NoContext,
     Context(String),
+    Column(String, Option<String>),
 }
 
 impl From<Option<String>> for ProduceContext {
@@ -174,6 +186,8 @@ impl fmt::Display for ProduceContext {
         match self {
             ProduceContext::NoContext => write!(f, "No Context"),
             ProduceContext::Context(s) => write!(f, "{}", s),
+            ProduceContext::Column(c, Some(s)) => write!(f, "column '{}': {}", c, s),
+            ProduceContext::Column(c, None) => write!(f, "column '{}'", c),
         }
     }
 }
@@ -2,7 +2,8 @@
 use crate::sources::mysql::{BinaryProtocol as MySQLBinaryProtocol, TextProtocol};
 #[cfg(feature = "src_postgres")]
 use crate::sources::postgres::{
-    rewrite_tls_args, BinaryProtocol as PgBinaryProtocol, CSVProtocol, CursorProtocol,
+    rewrite_conn_args, AutoProtocol, BinaryProtocol as PgBinaryProtocol, CSVProtocol,
+    CursorProtocol,
 };
 use crate::{prelude::*, sql::CXQuery};
 use fehler::{throw, throws};
@@ -29,13 +30,14 @@ pub fn get_arrow(
     match source_conn.ty {
         #[cfg(feature = "src_postgres")]
         SourceType::Postgres => {
-            let (config, tls) = rewrite_tls_args(&source_conn.conn)?;
+            let (config, tls, pool_config) = rewrite_conn_args(&source_conn.conn)?;
             match (protocol, tls) {
                 ("csv", Some(tls_conn)) => {
-                    let sb = PostgresSource::<CSVProtocol, MakeTlsConnector>::new(
+                    let sb = PostgresSource::<CSVProtocol, MakeTlsConnector>::new_with_pool_config(
                         config,
                         tls_conn,
                         queries.len(),
+                        pool_config,
                     )?;
                     let dispatcher = Dispatcher::<
                         _,
@@ -47,8 +49,12 @@ pub fn get_arrow(
                     dispatcher.run()?;
                 }
                 ("csv", None) => {
-                    let sb =
-                        PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, queries.len())?;
+                    let sb = PostgresSource::<CSVProtocol, NoTls>::new_with_pool_config(
+                        config,
+                        NoTls,
+                        queries.len(),
+                        pool_config,
+                    )?;
                     let dispatcher =
                         Dispatcher::<_, _, PostgresArrowTransport<CSVProtocol, NoTls>>::new(
                             sb,
@@ -59,11 +65,13 @@ pub fn get_arrow(
                     dispatcher.run()?;
                 }
                 ("binary", Some(tls_conn)) => {
-                    let sb = PostgresSource::<PgBinaryProtocol, MakeTlsConnector>::new(
-                        config,
-                        tls_conn,
-                        queries.len(),
-                    )?;
+                    let sb =
+                        PostgresSource::<PgBinaryProtocol, MakeTlsConnector>::new_with_pool_config(
+                            config,
+                            tls_conn,
+                            queries.len(),
+                            pool_config,
+                        )?;
                     let dispatcher =
                         Dispatcher::<
                             _,
@@ -73,10 +81,11 @@ pub fn get_arrow(
                     dispatcher.run()?;
                 }
                 ("binary", None) => {
-                    let sb = PostgresSource::<PgBinaryProtocol, NoTls>::new(
+                    let sb = PostgresSource::<PgBinaryProtocol, NoTls>::new_with_pool_config(
                         config,
                         NoTls,
                         queries.len(),
+                        pool_config,
                     )?;
                     let dispatcher = Dispatcher::<
                         _,
@@ -88,32 +97,71 @@ pub fn get_arrow(
                     dispatcher.run()?;
                 }
                 ("cursor", Some(tls_conn)) => {
-                    let sb = PostgresSource::<CursorProtocol, MakeTlsConnector>::new(
+                    let sb =
+                        PostgresSource::<CursorProtocol, MakeTlsConnector>::new_with_pool_config(
+                            config,
+                            tls_conn,
+                            queries.len(),
+                            pool_config,
+                        )?;
+                    let dispatcher = Dispatcher::<
+                        _,
+                        _,
+                        PostgresArrowTransport<CursorProtocol, MakeTlsConnector>,
+                    >::new(
+                        sb, &mut destination, queries, origin_query
+                    );
+                    dispatcher.run()?;
+                }
+                ("cursor", None) => {
+                    let sb = PostgresSource::<CursorProtocol, NoTls>::new_with_pool_config(
                         config,
-                        tls_conn,
+                        NoTls,
                         queries.len(),
+                        pool_config,
                     )?;
                     let dispatcher = Dispatcher::<
                         _,
                         _,
-                        PostgresArrowTransport<CursorProtocol, MakeTlsConnector>,
+                        PostgresArrowTransport<CursorProtocol, NoTls>,
                     >::new(
                         sb, &mut destination, queries, origin_query
                     );
                     dispatcher.run()?;
                 }
-                ("cursor", None) => {
+                ("auto", Some(tls_conn)) => {
                     let sb =
-                        PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, queries.len())?;
+                        PostgresSource::<AutoProtocol, MakeTlsConnector>::new_with_pool_config(
+                            config,
+                            tls_conn,
+                            queries.len(),
+                            pool_config,
+                        )?;
                     let dispatcher = Dispatcher::<
                         _,
                         _,
-                        PostgresArrowTransport<CursorProtocol, NoTls>,
+                        PostgresArrowTransport<AutoProtocol, MakeTlsConnector>,
                     >::new(
                         sb, &mut destination, queries, origin_query
                     );
                     dispatcher.run()?;
                 }
+                ("auto", None) => {
+                    let sb = PostgresSource::<AutoProtocol, NoTls>::new_with_pool_config(
+                        config,
+                        NoTls,
+                        queries.len(),
+                        pool_config,
+                    )?;
+                    let dispatcher =
+                        Dispatcher::<_, _, PostgresArrowTransport<AutoProtocol, NoTls>>::new(
+                            sb,
+                            &mut destination,
+                            queries,
+                            origin_query,
+                        );
+                    dispatcher.run()?;
+                }
                 _ => unimplemented!("{} protocol not supported", protocol),
             }
         }
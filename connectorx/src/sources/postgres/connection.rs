@@ -1,12 +1,85 @@
 use crate::sources::postgres::errors::PostgresSourceError;
+#[cfg(all(feature = "ssh_tunnel", unix))]
+use crate::sources::postgres::tunnel::{SshTunnel, SshTunnelConfig};
+use crate::sources::postgres::PostgresPoolConfig;
 use openssl::ssl::{SslConnector, SslFiletype, SslMethod, SslVerifyMode};
-use postgres::{config::SslMode, Config};
+use postgres::{
+    config::SslMode,
+    tls::{MakeTlsConnect, TlsConnect},
+    Client, Config, Socket,
+};
 use postgres_openssl::MakeTlsConnector;
+use r2d2::ManageConnection;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::PathBuf;
+use std::sync::Arc;
 use url::Url;
 
+/// Returns the password of a new connection, such as a fresh token of a cloud IAM, see
+/// `PostgresPoolConfig::password_provider`.
+pub type PasswordProvider = Arc<dyn Fn() -> anyhow::Result<String> + Send + Sync>;
+
+// the connection manager of the pool, connecting with the password of `password_provider` if
+// there is one, instead of that of `config`
+#[derive(Clone)]
+pub(crate) struct PostgresConnectionManager<C> {
+    config: Config,
+    tls: C,
+    password_provider: Option<PasswordProvider>,
+    // the tunnel `config` points at, closed once the source, its partitions and the pool have
+    // all dropped their manager
+    #[cfg(all(feature = "ssh_tunnel", unix))]
+    tunnel: Option<Arc<SshTunnel>>,
+}
+
+impl<C> PostgresConnectionManager<C> {
+    pub(crate) fn new(config: Config, tls: C, password_provider: Option<PasswordProvider>) -> Self {
+        Self {
+            config,
+            tls,
+            password_provider,
+            #[cfg(all(feature = "ssh_tunnel", unix))]
+            tunnel: None,
+        }
+    }
+
+    #[cfg(all(feature = "ssh_tunnel", unix))]
+    pub(crate) fn set_tunnel(&mut self, tunnel: SshTunnel) {
+        self.tunnel = Some(Arc::new(tunnel));
+    }
+}
+
+impl<C> ManageConnection for PostgresConnectionManager<C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type Connection = Client;
+    type Error = PostgresSourceError;
+
+    fn connect(&self) -> Result<Client, PostgresSourceError> {
+        match &self.password_provider {
+            Some(provider) => {
+                let mut config = self.config.clone();
+                config.password(provider()?);
+                Ok(config.connect(self.tls.clone())?)
+            }
+            None => Ok(self.config.connect(self.tls.clone())?),
+        }
+    }
+
+    fn is_valid(&self, conn: &mut Client) -> Result<(), PostgresSourceError> {
+        Ok(conn.simple_query("").map(|_| ())?)
+    }
+
+    fn has_broken(&self, conn: &mut Client) -> bool {
+        conn.is_closed()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TlsConfig {
     /// Postgres config, pg_config.sslmode (`sslmode`).
@@ -75,7 +148,8 @@ fn strip_bad_opts(url: &Url) -> Url {
     let stripped_query: Vec<(_, _)> = url
         .query_pairs()
         .filter(|p| match &*p.0 {
-            "sslkey" | "sslcert" | "sslrootcert" => false,
+            "sslkey" | "sslcert" | "sslrootcert" | "sshhost" | "sshport" | "sshuser" | "sshkey"
+            | "sshknownhosts" => false,
             _ => true,
         })
         .collect();
@@ -91,8 +165,104 @@ fn strip_bad_opts(url: &Url) -> Url {
     url2
 }
 
+// Point the url at the socket of a tunnel to the database through the bastion `sshhost`, which
+// is only opened by `PostgresSource::new_with_pool_config`. The port of the url names the
+// socket.
+#[cfg(all(feature = "ssh_tunnel", unix))]
+fn ssh_tunnel_args(
+    url: &mut Url,
+    ssh_host: &str,
+    params: &HashMap<String, String>,
+) -> Result<SshTunnelConfig, PostgresSourceError> {
+    let tunnel = SshTunnelConfig {
+        host: ssh_host.to_string(),
+        port: match params.get("sshport") {
+            Some(port) => port
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid sshport {}", port))?,
+            None => 22,
+        },
+        user: params
+            .get("sshuser")
+            .ok_or_else(|| anyhow::anyhow!("sshhost is set without sshuser"))?
+            .clone(),
+        key: params
+            .get("sshkey")
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow::anyhow!("sshhost is set without sshkey"))?,
+        known_hosts: match params.get("sshknownhosts") {
+            Some(known_hosts) => PathBuf::from(known_hosts),
+            None => std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".ssh").join("known_hosts"))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("sshhost is set without sshknownhosts and there is no home")
+                })?,
+        },
+        remote_host: url.host_str().unwrap_or("localhost").to_string(),
+        remote_port: url.port().unwrap_or(5432),
+        socket_dir: SshTunnelConfig::new_socket_dir(),
+    };
+    let socket_dir = tunnel.socket_dir.to_str().ok_or_else(|| {
+        anyhow::anyhow!(
+            "the temporary directory {} is not valid unicode",
+            tunnel.socket_dir.display()
+        )
+    })?;
+    url.set_host(Some(&urlencoding::encode(socket_dir)))
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    url.set_port(Some(tunnel.remote_port))
+        .map_err(|_| anyhow::anyhow!("cannot set the port of {}", url))?;
+    Ok(tunnel)
+}
+
+/// Like `rewrite_tls_args`, and the settings of the pool the url asks for as well: the SSH
+/// tunnel of `sshhost` (with the `ssh_tunnel` feature, on unix), which the config is pointed at.
+/// Nothing is dialed here, the tunnel is opened by `PostgresSource::new_with_pool_config` and
+/// closed along with the source.
+pub fn rewrite_conn_args(
+    conn: &Url,
+) -> Result<(Config, Option<MakeTlsConnector>, PostgresPoolConfig), PostgresSourceError> {
+    let params: HashMap<String, String> = conn.query_pairs().into_owned().collect();
+    #[allow(unused_mut)]
+    let mut pool_config = PostgresPoolConfig::default();
+    #[allow(unused_mut)]
+    let mut stripped_url = strip_bad_opts(conn);
+    if let Some(ssh_host) = params.get("sshhost") {
+        #[cfg(all(feature = "ssh_tunnel", unix))]
+        {
+            pool_config.ssh_tunnel = Some(ssh_tunnel_args(&mut stripped_url, ssh_host, &params)?);
+        }
+        #[cfg(not(all(feature = "ssh_tunnel", unix)))]
+        {
+            let _ = ssh_host;
+            return Err(anyhow::anyhow!(
+                "sshhost requires connectorx to be built with the ssh_tunnel feature, on unix"
+            )
+            .into());
+        }
+    }
+    let (pg_config, tls_connector) = tls_args(&stripped_url, &params)?;
+    Ok((pg_config, tls_connector, pool_config))
+}
+
 pub fn rewrite_tls_args(
     conn: &Url,
+) -> Result<(Config, Option<MakeTlsConnector>), PostgresSourceError> {
+    // a config without the pool settings of `rewrite_conn_args` would bypass the tunnel
+    if conn.query_pairs().any(|(k, _)| k == "sshhost") {
+        return Err(anyhow::anyhow!(
+            "sshhost needs the tunnel of the pool settings, read the url with rewrite_conn_args"
+        )
+        .into());
+    }
+    let params: HashMap<String, String> = conn.query_pairs().into_owned().collect();
+    tls_args(&strip_bad_opts(conn), &params)
+}
+
+// the config and tls connector of the url stripped by `strip_bad_opts`, from its ssl settings
+fn tls_args(
+    stripped_url: &Url,
+    params: &HashMap<String, String>,
 ) -> Result<(Config, Option<MakeTlsConnector>), PostgresSourceError> {
     // We parse the config, then strip unsupported SSL opts and rewrite the URI
     // before calling conn.parse().
@@ -100,8 +270,6 @@ pub fn rewrite_tls_args(
     // For more details on this approach, see the conversation here:
     // https://github.com/sfackler/rust-postgres/pull/774#discussion_r641784774
 
-    let params: HashMap<String, String> = conn.query_pairs().into_owned().collect();
-
     let sslcert = params.get("sslcert").map(PathBuf::from);
     let sslkey = params.get("sslkey").map(PathBuf::from);
     let root_cert = params.get("sslrootcert").map(PathBuf::from);
@@ -110,7 +278,6 @@ pub fn rewrite_tls_args(
         _ => None,
     };
 
-    let stripped_url = strip_bad_opts(conn);
     let pg_config: Config = stripped_url.as_str().parse().unwrap();
 
     let tls_config = TlsConfig {
@@ -23,7 +23,82 @@ pub enum PostgresSourceError {
     #[error(transparent)]
     TlsError(#[from] openssl::error::ErrorStack),
 
+    #[cfg(feature = "ssh_tunnel")]
+    #[error(transparent)]
+    SshError(#[from] ssh2::Error),
+
+    #[error("All {0} connections stayed taken for {1:?}, read the partitions made with more queries than connections concurrently or drop them once read.")]
+    ConnectionWait(usize, std::time::Duration),
+
+    #[error("COPY header {0:?} does not match the columns {1:?} from metadata.")]
+    HeaderMismatch(Vec<String>, Vec<String>),
+
+    #[error("Query {0} has the columns {1:?} instead of {2:?} from metadata.")]
+    ColumnMismatch(String, Vec<String>, Vec<String>),
+
+    #[error("Column {0:?} has type {1} which the {2} protocol cannot parse, please choose another protocol.")]
+    UnsupportedType(String, String, &'static str),
+
+    #[error("Query {0:?} returns no columns, there is nothing to load.")]
+    NoColumns(String),
+
+    #[error("Column {0:?} has type {1} which is not supported yet, please cast it (e.g. to text) in the query.")]
+    UnsupportedExtensionType(String, String),
+
+    #[error("Column {0:?} contains a numeric NaN, which cannot be represented as a decimal.")]
+    NumericNaN(String),
+
+    #[error("Column {0:?} is a numeric without a declared scale, which cannot be read as a scaled integer (the scales are only read with `set_declared_scales`).")]
+    UndeclaredScale(String),
+
+    #[error("Value {1} of column {0:?} does not fit a 64-bit integer at the declared scale.")]
+    ScaledOverflow(String, String),
+
+    #[error("Value {1:?} of column {0:?} is neither truthy nor falsy.")]
+    NotABool(String, String),
+
+    #[error("Column {0:?} has a bit string of {1} bits, which does not fit a {2}-bit integer.")]
+    BitStringTooWide(String, usize, usize),
+
+    #[error("Interval {1:?} of column {0:?} has no length in microseconds: it has months but no month length was set, or it does not fit a 64-bit integer.")]
+    IntervalMicros(String, String),
+
+    #[error("Query {0:?} is not on a single table, so it can only be sampled unpartitioned.")]
+    UnsampleablePartitions(String),
+
+    #[error("Query {0:?} cannot be wrapped in the COPY of the {1} protocol, please read it with the cursor or auto protocol.")]
+    NotCopyable(String, &'static str),
+
+    #[error("Query {0:?} is an EXPLAIN ANALYZE in text format, whose rows can only be counted by running the analyzed statement once more, please use another format (e.g. FORMAT JSON).")]
+    UncountableExplain(String),
+
+    #[error("Query {0:?} was asked for its row count under DecodeErrorPolicy::SkipRow, whose dropped rows would be left empty in a destination sized from it, please read it into a destination that grows as it reads (e.g. arrow).")]
+    SkipRowCounted(String),
+
+    #[error("Query {0:?} is not ordered by its resume key {1:?}, please add an ORDER BY on it.")]
+    NotOrderedByKey(String, String),
+
+    #[error("Reading partition {0:?} failed after {1} rows: {2}")]
+    StreamError(String, usize, #[source] Box<PostgresSourceError>),
+
     /// Any other errors that are too trivial to be put here explicitly.
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
+
+impl PostgresSourceError {
+    /// Whether the error comes from a single value that cannot be parsed, as opposed to the
+    /// query, the connection or the stream.
+    pub fn is_decode_error(&self) -> bool {
+        matches!(
+            self,
+            PostgresSourceError::ConnectorXError(crate::errors::ConnectorXError::CannotProduce(..))
+                | PostgresSourceError::HexError(_)
+                | PostgresSourceError::NumericNaN(_)
+                | PostgresSourceError::ScaledOverflow(..)
+                | PostgresSourceError::NotABool(..)
+                | PostgresSourceError::BitStringTooWide(..)
+                | PostgresSourceError::IntervalMicros(..)
+        )
+    }
+}
@@ -2,38 +2,69 @@
 
 mod connection;
 mod errors;
+#[cfg(feature = "postgis")]
+mod postgis;
+#[cfg(all(feature = "ssh_tunnel", unix))]
+mod tunnel;
 mod typesystem;
 
 pub use self::errors::PostgresSourceError;
-pub use connection::rewrite_tls_args;
-pub use typesystem::{PostgresTypePairs, PostgresTypeSystem};
+pub use connection::{rewrite_conn_args, rewrite_tls_args, PasswordProvider};
+#[cfg(feature = "postgis")]
+pub use postgis::decode_ewkb;
+#[cfg(all(feature = "ssh_tunnel", unix))]
+pub use tunnel::SshTunnelConfig;
+pub use typesystem::{
+    PgRange, PgSparseVec, PostgresInterval, PostgresTimestampTz, PostgresTypePairs,
+    PostgresTypeSystem,
+};
+
+use typesystem::{
+    PostgresBits, PostgresBoolLike, PostgresBytes, PostgresDuration, PostgresFloat, PostgresInt4,
+    PostgresInt8, PostgresInteger, PostgresJson, PostgresMultiRange, PostgresNumeric,
+    PostgresNumericText, PostgresResumeKey, PostgresStringLike, PostgresText, PostgresUnsigned,
+    PostgresVector,
+};
 
 use crate::constants::DB_BUFFER_SIZE;
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
     sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, CXQuery},
+    sql::{
+        count_query, explain_options, ordered_by, plain_table_scan, projection_info,
+        split_statements, tablesample_query, CXQuery, ExplainOptions,
+    },
+    typesystem::{ParameterizedFunc, ParameterizedOn, Realize},
 };
 use anyhow::anyhow;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
 use csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter};
 use fehler::{throw, throws};
 use hex::decode;
+use log::{debug, warn};
 use postgres::{
     binary_copy::{BinaryCopyOutIter, BinaryCopyOutRow},
     fallible_iterator::FallibleIterator,
     tls::{MakeTlsConnect, TlsConnect},
+    types::{FromSql, Kind},
     Config, CopyOutReader, Row, RowIter, Socket,
 };
-use r2d2::{Pool, PooledConnection};
-use r2d2_postgres::PostgresConnectionManager;
+use r2d2::{ManageConnection, Pool, PooledConnection};
+use rayon::prelude::*;
 use rust_decimal::Decimal;
 use serde_json::{from_str, Value};
 use sqlparser::dialect::PostgreSqlDialect;
+use std::any::type_name;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 /// Protocol - Binary based bulk load
@@ -45,7 +76,63 @@ pub enum CSVProtocol {}
 /// Protocol - use Cursor
 pub enum CursorProtocol {}
 
-type PgManager<C> = PostgresConnectionManager<C>;
+/// Protocol - Binary, or Cursor if a column has a type Binary cannot parse (e.g. hstore) or the
+/// query cannot be wrapped in COPY (e.g. an EXPLAIN). The choice is made by each partition from
+/// its query and the schema `fetch_metadata` found, CSV is never picked and has to be asked for
+/// explicitly.
+pub enum AutoProtocol {}
+
+/// The set of types a protocol can parse, `fetch_metadata` rejects the columns outside of it
+/// instead of failing later in `produce`.
+pub trait PostgresProtocol {
+    const NAME: &'static str;
+    /// Whether the partitions are read through `COPY (query) TO STDOUT`, which does not take
+    /// every query (e.g. not an EXPLAIN).
+    const COPY: bool;
+
+    fn supports(ty: PostgresTypeSystem) -> bool;
+}
+
+impl PostgresProtocol for BinaryProtocol {
+    const NAME: &'static str = "binary";
+    const COPY: bool = true;
+
+    fn supports(ty: PostgresTypeSystem) -> bool {
+        use PostgresTypeSystem::*;
+        !matches!(ty, HSTORE(_) | Unsupported(_))
+    }
+}
+
+impl PostgresProtocol for CSVProtocol {
+    const NAME: &'static str = "csv";
+    const COPY: bool = true;
+
+    fn supports(ty: PostgresTypeSystem) -> bool {
+        use PostgresTypeSystem::*;
+        !matches!(ty, HSTORE(_) | BoolArray(_) | UUIDArray(_))
+    }
+}
+
+impl PostgresProtocol for CursorProtocol {
+    const NAME: &'static str = "cursor";
+    const COPY: bool = false;
+
+    fn supports(ty: PostgresTypeSystem) -> bool {
+        use PostgresTypeSystem::*;
+        !matches!(ty, Enum(_) | NodeTree(_) | Unsupported(_))
+    }
+}
+
+impl PostgresProtocol for AutoProtocol {
+    const NAME: &'static str = "auto";
+    const COPY: bool = false;
+
+    fn supports(ty: PostgresTypeSystem) -> bool {
+        BinaryProtocol::supports(ty) || CursorProtocol::supports(ty)
+    }
+}
+
+type PgManager<C> = connection::PostgresConnectionManager<C>;
 type PgConn<C> = PooledConnection<PgManager<C>>;
 
 // take a row and unwrap the interior field from column 0
@@ -54,6 +141,40 @@ fn convert_row<'b, R: TryFrom<usize> + postgres::types::FromSql<'b> + Clone>(row
     nrows.expect("Could not parse int result from count_query")
 }
 
+// check out a connection for a partition of `PostgresSource::partition` that got none up front,
+// waiting past the connection timeout of the pool as long as all of its connections are taken,
+// as they are busy with the partitions before it rather than failing to connect, for up to
+// `timeout` in all
+#[throws(PostgresSourceError)]
+fn wait_for_conn<C>(pool: &Pool<PgManager<C>>, timeout: Duration) -> PgConn<C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    let start = Instant::now();
+    loop {
+        let left = timeout.saturating_sub(start.elapsed());
+        match pool.get_timeout(left.min(pool.connection_timeout())) {
+            Ok(conn) => break conn,
+            Err(e) => {
+                let state = pool.state();
+                if state.idle_connections > 0 || state.connections < pool.max_size() {
+                    throw!(e);
+                }
+                if start.elapsed() >= timeout {
+                    throw!(PostgresSourceError::ConnectionWait(
+                        state.connections as usize,
+                        start.elapsed()
+                    ));
+                }
+                debug!("all {} connections are taken, waiting", state.connections);
+            }
+        }
+    }
+}
+
 #[throws(PostgresSourceError)]
 fn get_total_rows<C>(conn: &mut PgConn<C>, query: &CXQuery<String>) -> usize
 where
@@ -62,6 +183,16 @@ where
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
+    // an EXPLAIN prints a single row in the json, xml and yaml formats, and a row per line of
+    // the plan in text, which can only be counted by running it (refused by `fetch_metadata`
+    // with ANALYZE, which would run the explained statement once more)
+    if let Some(options) = explain_options(query.as_str(), &PostgreSqlDialect {}) {
+        return match options.format {
+            Some(_) => 1,
+            None => conn.query(query.as_str(), &[])?.len(),
+        };
+    }
+
     let dialect = PostgreSqlDialect {};
 
     let row = conn.query_one(count_query(query, &dialect)?.as_str(), &[])?;
@@ -76,467 +207,4668 @@ where
     }
 }
 
-pub struct PostgresSource<P, C>
+// an EXPLAIN can neither be wrapped in COPY nor counted through a subquery
+fn is_explain(query: &str) -> bool {
+    explain_options(query, &PostgreSqlDialect {}).is_some()
+}
+
+// the generated columns of the queried tables that are not named explicitly in the projection
+#[throws(PostgresSourceError)]
+fn get_implicit_generated_columns<C>(conn: &mut PgConn<C>, query: &CXQuery<String>) -> Vec<String>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    pool: Pool<PgManager<C>>,
-    origin_query: Option<String>,
-    queries: Vec<CXQuery<String>>,
-    names: Vec<String>,
-    schema: Vec<PostgresTypeSystem>,
-    pg_schema: Vec<postgres::types::Type>,
-    _protocol: PhantomData<P>,
+    let (tables, explicit) = match projection_info(query.as_str(), &PostgreSqlDialect {}) {
+        Some(info) => info,
+        None => return vec![],
+    };
+    // unquoted identifiers are folded to lower case, as Postgres does
+    let explicit: Vec<String> = explicit
+        .into_iter()
+        .map(|ident| match ident.quote_style {
+            Some(_) => ident.value,
+            None => ident.value.to_lowercase(),
+        })
+        .collect();
+
+    // `to_regclass` skips the names that are no relation, such as a table function called
+    // without arguments, which parses the same as a table
+    conn.query(
+        "SELECT attname::text FROM pg_attribute \
+         WHERE attrelid IN (SELECT to_regclass(t) FROM unnest($1::text[]) AS t) \
+         AND attnum > 0 AND NOT attisdropped AND attgenerated <> ''",
+        &[&tables],
+    )?
+    .iter()
+    .map(|row| row.get::<_, String>(0))
+    .filter(|name| !explicit.contains(name))
+    .collect()
 }
 
-impl<P, C> PostgresSource<P, C>
+// the labels of the enum type `oid`, in the order the type defines
+#[throws(PostgresSourceError)]
+fn get_enum_labels<C>(conn: &mut PgConn<C>, oid: u32) -> Vec<String>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    #[throws(PostgresSourceError)]
-    pub fn new(config: Config, tls: C, nconn: usize) -> Self {
-        let manager = PostgresConnectionManager::new(config, tls);
-        let pool = Pool::builder().max_size(nconn as u32).build(manager)?;
+    conn.query(
+        "SELECT enumlabel::text FROM pg_enum WHERE enumtypid = $1 ORDER BY enumsortorder",
+        &[&oid],
+    )?
+    .iter()
+    .map(|row| row.get::<_, String>(0))
+    .collect()
+}
 
-        Self {
-            pool,
-            origin_query: None,
-            queries: vec![],
-            names: vec![],
-            schema: vec![],
-            pg_schema: vec![],
-            _protocol: PhantomData,
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// expand a composite column into its fields, recursively, as (name, expression, type). A field
+// `y` of a column `x` is named `x.y`.
+fn flatten_column(
+    name: &str,
+    expr: String,
+    ty: &postgres::types::Type,
+    columns: &mut Vec<(String, String, postgres::types::Type)>,
+) {
+    match ty.kind() {
+        Kind::Composite(fields) => {
+            for field in fields {
+                flatten_column(
+                    &format!("{}.{}", name, field.name()),
+                    format!("({}).{}", expr, quote_ident(field.name())),
+                    field.type_(),
+                    columns,
+                );
+            }
         }
+        _ => columns.push((name.to_string(), expr, ty.clone())),
     }
 }
 
-impl<P, C> Source for PostgresSource<P, C>
+// the scale each column of `query` declares in its typmod, if any. A prepared statement does
+// not expose the typmods, so they are read back from a temporary view that is rolled back.
+#[throws(PostgresSourceError)]
+fn get_declared_scales<C>(conn: &mut PgConn<C>, query: &CXQuery<String>) -> Vec<Option<u32>>
 where
-    PostgresSourcePartition<P, C>:
-        SourcePartition<TypeSystem = PostgresTypeSystem, Error = PostgresSourceError>,
-    P: Send,
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    const DATA_ORDERS: &'static [DataOrder] = &[DataOrder::RowMajor];
-    type Partition = PostgresSourcePartition<P, C>;
-    type TypeSystem = PostgresTypeSystem;
-    type Error = PostgresSourceError;
+    let mut tx = conn.transaction()?;
+    tx.batch_execute(&format!("CREATE TEMP VIEW cx_declared_scales AS {}", query))?;
+    let scales = tx
+        .query(
+            "SELECT atttypmod FROM pg_attribute \
+             WHERE attrelid = 'cx_declared_scales'::regclass AND attnum > 0 ORDER BY attnum",
+            &[],
+        )?
+        .iter()
+        // a numeric typmod is ((precision << 16) | scale) + 4, or -1 if unconstrained
+        .map(|row| match row.get::<_, i32>(0) {
+            typmod if typmod >= 4 => Some(((typmod - 4) & 0xffff) as u32),
+            _ => None,
+        })
+        .collect();
+    tx.rollback()?;
+    scales
+}
+
+/// The values read as `true` and `false` from integer and text columns when producing a bool,
+/// compared ignoring ASCII case. An integer is compared by its decimal representation.
+#[derive(Clone, Debug)]
+pub struct BoolCoercion {
+    pub truthy: Vec<String>,
+    pub falsy: Vec<String>,
+}
+
+impl Default for BoolCoercion {
+    fn default() -> Self {
+        Self {
+            truthy: ["1", "t", "true", "y", "yes"].map(String::from).to_vec(),
+            falsy: ["0", "f", "false", "n", "no"].map(String::from).to_vec(),
+        }
+    }
+}
 
+impl BoolCoercion {
     #[throws(PostgresSourceError)]
-    fn set_data_order(&mut self, data_order: DataOrder) {
-        if !matches!(data_order, DataOrder::RowMajor) {
-            throw!(ConnectorXError::UnsupportedDataOrder(data_order));
+    fn coerce(&self, name: &str, v: &str) -> bool {
+        if self.truthy.iter().any(|t| t.eq_ignore_ascii_case(v)) {
+            true
+        } else if self.falsy.iter().any(|f| f.eq_ignore_ascii_case(v)) {
+            false
+        } else {
+            throw!(PostgresSourceError::NotABool(
+                name.to_string(),
+                v.to_string()
+            ))
         }
     }
+}
 
-    fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+/// How a bytea is written when produced as a `String`, for destinations that only hold text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteaEncoding {
+    /// `\x` followed by two lowercase hex digits per byte, as Postgres prints it. The default.
+    Hex,
+    /// Standard base64, with padding.
+    Base64,
+}
+
+impl Default for ByteaEncoding {
+    fn default() -> Self {
+        ByteaEncoding::Hex
     }
+}
 
-    fn set_origin_query(&mut self, query: Option<String>) {
-        self.origin_query = query;
+impl ByteaEncoding {
+    fn encode(self, v: &[u8]) -> String {
+        match self {
+            ByteaEncoding::Hex => format!("\\x{}", hex::encode(v)),
+            ByteaEncoding::Base64 => base64::encode(v),
+        }
     }
+}
 
-    #[throws(PostgresSourceError)]
-    fn fetch_metadata(&mut self) {
-        assert!(!self.queries.is_empty());
+/// What to do with a value the CSV protocol cannot parse into the type of its column, such as
+/// a malformed numeric in a dirty text column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeErrorPolicy {
+    /// Fail the partition, the default.
+    Fail,
+    /// Read the value as NULL and count it in the `DecodeErrorReport` of the source.
+    Null,
+    /// Drop the whole row in `fetch_next` and count it in the `DecodeErrorReport` of the source,
+    /// for any protocol. A destination is written each value as it is produced, so a row cannot
+    /// be dropped once its first value is: every buffered row is decoded up front as the rust
+    /// types of the `PostgresTypeSystem` of its columns instead, which doubles the decoding
+    /// cost. A transport reading a column as another type can still fail on a row kept here,
+    /// which then fails the partition as under `Fail`. The rows dropped cannot be known before
+    /// the read, so asking the row count (as a destination sized before the read, e.g. pandas,
+    /// does) is an error; read into one growing as it reads (e.g. arrow) instead.
+    SkipRow,
+}
 
-        let mut conn = self.pool.get()?;
-        let first_query = &self.queries[0];
+impl Default for DecodeErrorPolicy {
+    fn default() -> Self {
+        DecodeErrorPolicy::Fail
+    }
+}
 
-        let stmt = conn.prepare(first_query.as_str())?;
+/// The number of values per column read as NULL under `DecodeErrorPolicy::Null`, and read as
+/// the value of `PostgresSource::set_decode_fallback` instead, shared by all the partitions of
+/// a source.
+#[derive(Clone, Debug, Default)]
+pub struct DecodeErrorReport {
+    nulls: Arc<Mutex<HashMap<String, usize>>>,
+    fallbacks: Arc<Mutex<HashMap<String, usize>>>,
+    skipped_rows: Arc<AtomicUsize>,
+}
 
-        let (names, pg_types): (Vec<String>, Vec<postgres::types::Type>) = stmt
-            .columns()
-            .iter()
-            .map(|col| (col.name().to_string(), col.type_().clone()))
-            .unzip();
+impl DecodeErrorReport {
+    pub fn counts(&self) -> HashMap<String, usize> {
+        self.nulls.lock().unwrap().clone()
+    }
 
-        self.names = names;
-        self.schema = pg_types
-            .iter()
-            .map(|t| PostgresTypeSystem::from(t))
-            .collect();
-        self.pg_schema = self
-            .schema
-            .iter()
-            .zip(pg_types.iter())
-            .map(|(t1, t2)| PostgresTypePairs(t2, t1).into())
-            .collect();
+    pub fn fallback_counts(&self) -> HashMap<String, usize> {
+        self.fallbacks.lock().unwrap().clone()
     }
 
-    #[throws(PostgresSourceError)]
-    fn result_rows(&mut self) -> Option<usize> {
-        match &self.origin_query {
-            Some(q) => {
-                let cxq = CXQuery::Naked(q.clone());
-                let mut conn = self.pool.get()?;
-                let nrows = get_total_rows(&mut conn, &cxq)?;
-                Some(nrows)
-            }
-            None => None,
-        }
+    /// The number of rows dropped under `DecodeErrorPolicy::SkipRow`.
+    pub fn skipped_rows(&self) -> usize {
+        self.skipped_rows.load(Ordering::Relaxed)
     }
 
-    fn names(&self) -> Vec<String> {
-        self.names.clone()
+    fn record(&self, name: &str) {
+        *self
+            .nulls
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert(0) += 1;
     }
 
-    fn schema(&self) -> Vec<Self::TypeSystem> {
-        self.schema.clone()
+    fn record_fallback(&self, name: &str) {
+        *self
+            .fallbacks
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert(0) += 1;
     }
+}
 
-    #[throws(PostgresSourceError)]
-    fn partition(self) -> Vec<Self::Partition> {
-        let mut ret = vec![];
-        for query in self.queries {
-            let conn = self.pool.get()?;
+// the values of `PostgresSource::set_decode_fallback` by the name of the rust type they are
+// read as, and the report to count them in
+#[derive(Clone, Debug)]
+struct DecodeFallbacks {
+    values: HashMap<&'static str, String>,
+    report: DecodeErrorReport,
+}
 
-            ret.push(PostgresSourcePartition::<P, C>::new(
-                conn,
-                &query,
-                &self.schema,
-                &self.pg_schema,
-            ));
+impl DecodeFallbacks {
+    fn get<T>(&self) -> Option<&String> {
+        self.values.get(type_name::<T>())
+    }
+}
+
+// the name of the rust type a `PostgresTypeSystem` is produced as
+struct FTypeName;
+
+impl ParameterizedFunc for FTypeName {
+    type Function = fn() -> &'static str;
+}
+
+impl<T> ParameterizedOn<T> for FTypeName {
+    fn parameterize() -> Self::Function {
+        type_name::<T>
+    }
+}
+
+/// How `PostgresSource::set_sample` samples the queries of a source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostgresSample {
+    /// Percentage of the pages to read with `TABLESAMPLE SYSTEM`, for a query on a single table.
+    /// Whole pages are read, so the sample is approximate and clustered.
+    pub percent: f64,
+    /// Number of rows to pick with `ORDER BY random()` from any other query. This reads and
+    /// sorts the whole result on the server, so it is no faster than loading it.
+    pub rows: usize,
+    /// Seed of `REPEATABLE`, the same seed samples the same pages of an unchanged table.
+    pub seed: i64,
+}
+
+// sample a query of a source, see `PostgresSource::set_sample`
+#[throws(PostgresSourceError)]
+fn sample_query(query: &str, sample: &PostgresSample, partitioned: bool) -> String {
+    match tablesample_query(query, sample.percent, sample.seed, &PostgreSqlDialect {})? {
+        Some(sampled) => sampled,
+        // every partition would pick rows of its own, and a different set on each count
+        None if partitioned => throw!(PostgresSourceError::UnsampleablePartitions(
+            query.to_string()
+        )),
+        None => {
+            warn!(
+                "{} is not on a single table, sampling it by sorting the whole result at random",
+                query
+            );
+            format!(
+                "SELECT * FROM ({}) AS CXTMPTAB_SAMPLE ORDER BY random() LIMIT {}",
+                query, sample.rows
+            )
         }
-        ret
     }
 }
 
-pub struct PostgresSourcePartition<P, C>
+/// Settings of the connection pool backing a `PostgresSource`.
+#[derive(Clone)]
+pub struct PostgresPoolConfig {
+    /// Check a connection is alive (an empty query round-trip) before handing it out, so stale
+    /// connections left by a server restart or network reset are replaced instead of failing the COPY.
+    pub test_on_check_out: bool,
+    /// Send TCP keepalives on idle connections, so that a NAT or firewall does not silently
+    /// drop a connection waiting on a long query. Overrides `keepalives` of the connection string.
+    pub keepalives: bool,
+    /// How long a connection is idle before the first keepalive, overriding `keepalives_idle` of
+    /// the connection string. The interval and count of the probes are left to the OS, the
+    /// `postgres` crate does not expose them.
+    pub keepalives_idle: Option<Duration>,
+    /// Give up connecting after this long, overriding `connect_timeout` of the connection string.
+    pub connect_timeout: Option<Duration>,
+    /// Called for the password of each new connection, overriding `password` of the connection
+    /// string. For the short-lived tokens of RDS IAM or Azure AD authentication, which would
+    /// expire before the connections opened late in a long read (to replace a broken one, or
+    /// one for `PostgresSourcePartition::resume`) if passed once in the connection string. It
+    /// is called from the threads the pool connects from, which may be several at a time.
+    pub password_provider: Option<PasswordProvider>,
+    /// How long a partition made with more queries than connections waits in all for one of
+    /// them to be given back before failing. The connections are only given back as the
+    /// partitions holding them are dropped, so such partitions must be read concurrently (as
+    /// the dispatcher does) or dropped once read.
+    pub conn_wait_timeout: Duration,
+    /// Connect through this SSH bastion, as read from the url by `rewrite_conn_args` (which
+    /// points the config at the tunnel). It is opened by `new_with_pool_config` and closed once
+    /// the source, its partitions and its pool are all dropped.
+    #[cfg(all(feature = "ssh_tunnel", unix))]
+    pub ssh_tunnel: Option<SshTunnelConfig>,
+}
+
+impl std::fmt::Debug for PostgresPoolConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut f = f.debug_struct("PostgresPoolConfig");
+        f.field("test_on_check_out", &self.test_on_check_out)
+            .field("keepalives", &self.keepalives)
+            .field("keepalives_idle", &self.keepalives_idle)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("password_provider", &self.password_provider.is_some())
+            .field("conn_wait_timeout", &self.conn_wait_timeout);
+        #[cfg(all(feature = "ssh_tunnel", unix))]
+        f.field("ssh_tunnel", &self.ssh_tunnel);
+        f.finish()
+    }
+}
+
+impl Default for PostgresPoolConfig {
+    fn default() -> Self {
+        Self {
+            test_on_check_out: true,
+            keepalives: true,
+            // well below the idle timeout of common NATs (e.g. 350s on AWS), unlike the 2 hours
+            // the `postgres` crate defaults to
+            keepalives_idle: Some(Duration::from_secs(60)),
+            connect_timeout: None,
+            password_provider: None,
+            conn_wait_timeout: Duration::from_secs(600),
+            #[cfg(all(feature = "ssh_tunnel", unix))]
+            ssh_tunnel: None,
+        }
+    }
+}
+
+// the table `PostgresSource::materialize` created, shared by the partitions reading it
+struct MaterializedTable<C>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    conn: PgConn<C>,
-    query: CXQuery<String>,
-    schema: Vec<PostgresTypeSystem>,
-    pg_schema: Vec<postgres::types::Type>,
-    nrows: usize,
-    ncols: usize,
-    _protocol: PhantomData<P>,
+    pool: Pool<PgManager<C>>,
+    name: String,
 }
 
-impl<P, C> PostgresSourcePartition<P, C>
+impl<C> Drop for MaterializedTable<C>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    pub fn new(
-        conn: PgConn<C>,
-        query: &CXQuery<String>,
-        schema: &[PostgresTypeSystem],
-        pg_schema: &[postgres::types::Type],
-    ) -> Self {
-        Self {
-            conn,
-            query: query.clone(),
-            schema: schema.to_vec(),
-            pg_schema: pg_schema.to_vec(),
-            nrows: 0,
-            ncols: schema.len(),
-            _protocol: PhantomData,
+    fn drop(&mut self) {
+        let res = self
+            .pool
+            .get()
+            .map(|mut conn| conn.batch_execute(&format!("DROP TABLE IF EXISTS {}", self.name)));
+        if !matches!(res, Ok(Ok(()))) {
+            warn!("failed to drop the materialized table {}", self.name);
         }
     }
 }
 
-impl<C> SourcePartition for PostgresSourcePartition<BinaryProtocol, C>
+/// The chunks of a large object from `PostgresSource::large_object_chunks`, read a few pages of
+/// `pg_largeobject` at a time over a connection of the pool, which is held until this is dropped.
+pub struct PostgresLargeObjectChunks<C>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    type TypeSystem = PostgresTypeSystem;
-    type Parser<'a> = PostgresBinarySourcePartitionParser<'a>;
-    type Error = PostgresSourceError;
-
-    #[throws(PostgresSourceError)]
-    fn result_rows(&mut self) -> () {
-        self.nrows = get_total_rows(&mut self.conn, &self.query)?;
-    }
+    conn: PgConn<C>,
+    loid: u32,
+    pages_per_chunk: i64,
+    // the size of a page, `LOBLKSIZE` of the server
+    page_size: usize,
+    next_page: i32,
+    // the number of bytes read so far, where the next page is expected to start
+    offset: usize,
+    done: bool,
+}
 
+impl<C> PostgresLargeObjectChunks<C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    // the next pages, with the holes of a sparse object (pages never written, or written short
+    // before a later page) filled with zeros
     #[throws(PostgresSourceError)]
-    fn parser(&mut self) -> Self::Parser<'_> {
-        let query = format!("COPY ({}) TO STDOUT WITH BINARY", self.query);
-        let reader = self.conn.copy_out(&*query)?; // unless reading the data, it seems like issue the query is fast
-        let iter = BinaryCopyOutIter::new(reader, &self.pg_schema);
-
-        PostgresBinarySourcePartitionParser::new(iter, &self.schema)
-    }
-
-    fn nrows(&self) -> usize {
-        self.nrows
-    }
+    fn next_chunk(&mut self) -> Option<Vec<u8>> {
+        let rows = self.conn.query(
+            "SELECT pageno, data FROM pg_largeobject WHERE loid = $1 AND pageno >= $2 ORDER BY pageno LIMIT $3",
+            &[&self.loid, &self.next_page, &self.pages_per_chunk],
+        )?;
+        if rows.len() < self.pages_per_chunk as usize {
+            self.done = true;
+        }
+        if rows.is_empty() {
+            return None;
+        }
 
-    fn ncols(&self) -> usize {
-        self.ncols
+        let mut chunk = vec![];
+        for row in &rows {
+            let pageno: i32 = row.try_get(0)?;
+            let data: &[u8] = row.try_get(1)?;
+            let start = pageno as usize * self.page_size;
+            if start > self.offset {
+                chunk.resize(chunk.len() + start - self.offset, 0);
+            }
+            chunk.extend_from_slice(data);
+            self.offset = start + data.len();
+            self.next_page = pageno + 1;
+        }
+        Some(chunk)
     }
 }
 
-impl<C> SourcePartition for PostgresSourcePartition<CSVProtocol, C>
+impl<C> Iterator for PostgresLargeObjectChunks<C>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    type TypeSystem = PostgresTypeSystem;
-    type Parser<'a> = PostgresCSVSourceParser<'a>;
-    type Error = PostgresSourceError;
+    type Item = Result<Vec<u8>, PostgresSourceError>;
 
-    #[throws(PostgresSourceError)]
-    fn result_rows(&mut self) {
-        self.nrows = get_total_rows(&mut self.conn, &self.query)?;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let ret = self.next_chunk();
+        if ret.is_err() {
+            self.done = true;
+        }
+        ret.transpose()
     }
+}
 
-    #[throws(PostgresSourceError)]
-    fn parser(&mut self) -> Self::Parser<'_> {
-        let query = format!("COPY ({}) TO STDOUT WITH CSV", self.query);
-        let reader = self.conn.copy_out(&*query)?; // unless reading the data, it seems like issue the query is fast
-        let iter = ReaderBuilder::new()
-            .has_headers(false)
-            .from_reader(reader)
-            .into_records();
-
-        PostgresCSVSourceParser::new(iter, &self.schema)
-    }
+/// Called by `partition` with each partition's query and the time it waited for a connection.
+pub type ConnWaitCallback = Arc<dyn Fn(&CXQuery<String>, Duration) + Send + Sync>;
 
-    fn nrows(&self) -> usize {
-        self.nrows
-    }
+// the settings of a `PostgresSource`, which `reset` carries over to the new source
+#[derive(Clone)]
+struct PostgresOptions {
+    csv_header: bool,
+    csv_null: Option<String>,
+    exclude_generated: bool,
+    json_paths: HashMap<String, Vec<String>>,
+    flatten_composites: bool,
+    enum_order: bool,
+    declared_scales: bool,
+    lazy_nrows: bool,
+    bool_coercion: Option<BoolCoercion>,
+    decode_error_policy: DecodeErrorPolicy,
+    decode_fallbacks: HashMap<&'static str, String>,
+    strict_dates: bool,
+    bytea_encoding: ByteaEncoding,
+    timezone: Tz,
+    interval_month_days: Option<i64>,
+    assume_utc: bool,
+    sample: Option<PostgresSample>,
+    max_rows: Option<usize>,
+    partition_sql: Option<String>,
+    snapshot: Option<String>,
+    resume_key: Option<String>,
+    hybrid_key: Option<String>,
+    warm_up: bool,
+    check_columns: bool,
+    conn_wait_callback: Option<ConnWaitCallback>,
+    conn_wait_timeout: Duration,
+}
 
-    fn ncols(&self) -> usize {
-        self.ncols
+impl Default for PostgresOptions {
+    fn default() -> Self {
+        Self {
+            csv_header: false,
+            csv_null: None,
+            exclude_generated: false,
+            json_paths: HashMap::new(),
+            flatten_composites: false,
+            enum_order: false,
+            declared_scales: false,
+            lazy_nrows: false,
+            bool_coercion: None,
+            bytea_encoding: ByteaEncoding::Hex,
+            timezone: Tz::UTC,
+            interval_month_days: None,
+            decode_error_policy: DecodeErrorPolicy::Fail,
+            decode_fallbacks: HashMap::new(),
+            strict_dates: false,
+            assume_utc: false,
+            sample: None,
+            max_rows: None,
+            partition_sql: None,
+            snapshot: None,
+            resume_key: None,
+            hybrid_key: None,
+            warm_up: false,
+            check_columns: false,
+            conn_wait_callback: None,
+            conn_wait_timeout: PostgresPoolConfig::default().conn_wait_timeout,
+        }
     }
 }
 
-impl<C> SourcePartition for PostgresSourcePartition<CursorProtocol, C>
+pub struct PostgresSource<P, C>
 where
     C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
     C::TlsConnect: Send,
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    type TypeSystem = PostgresTypeSystem;
-    type Parser<'a> = PostgresRawSourceParser<'a>;
-    type Error = PostgresSourceError;
-
-    #[throws(PostgresSourceError)]
-    fn result_rows(&mut self) {
-        self.nrows = get_total_rows(&mut self.conn, &self.query)?;
-    }
-
-    #[throws(PostgresSourceError)]
-    fn parser(&mut self) -> Self::Parser<'_> {
-        let iter = self
-            .conn
-            .query_raw::<_, bool, _>(self.query.as_str(), vec![])?; // unless reading the data, it seems like issue the query is fast
-        PostgresRawSourceParser::new(iter, &self.schema)
+    pool: Pool<PgManager<C>>,
+    // to open the connections outside of the pool, see `set_hybrid_key`
+    manager: PgManager<C>,
+    // to cancel the reads of the partitions dropped before their end
+    tls: C,
+    origin_query: Option<String>,
+    queries: Vec<CXQuery<String>>,
+    names: Vec<String>,
+    schema: Vec<PostgresTypeSystem>,
+    pg_schema: Vec<postgres::types::Type>,
+    scales: Vec<Option<u32>>,
+    opts: PostgresOptions,
+    decode_errors: DecodeErrorReport,
+    materialized: Option<Arc<MaterializedTable<C>>>,
+    // whether each table a partition scanned can be copied directly, see `copy_target`
+    copy_tables: Arc<Mutex<HashMap<String, bool>>>,
+    _protocol: PhantomData<P>,
+}
+
+impl<P, C> PostgresSource<P, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// `nconn` caps the connections to the server, and so how many partitions read at a time.
+    /// With more queries than that, the partitions do not get a connection up front but check
+    /// one out of the pool when they start to read and return it when dropped, so they are read
+    /// in waves as connections free up.
+    #[throws(PostgresSourceError)]
+    pub fn new(config: Config, tls: C, nconn: usize) -> Self {
+        Self::new_with_pool_config(config, tls, nconn, PostgresPoolConfig::default())?
+    }
+
+    #[throws(PostgresSourceError)]
+    pub fn new_with_pool_config(
+        config: Config,
+        tls: C,
+        nconn: usize,
+        pool_config: PostgresPoolConfig,
+    ) -> Self {
+        let mut config = config;
+        config.keepalives(pool_config.keepalives);
+        if let Some(keepalives_idle) = pool_config.keepalives_idle {
+            config.keepalives_idle(keepalives_idle);
+        }
+        if let Some(connect_timeout) = pool_config.connect_timeout {
+            config.connect_timeout(connect_timeout);
+        }
+        #[allow(unused_mut)]
+        let mut manager = PgManager::new(config, tls.clone(), pool_config.password_provider);
+        #[cfg(all(feature = "ssh_tunnel", unix))]
+        if let Some(tunnel) = &pool_config.ssh_tunnel {
+            manager.set_tunnel(tunnel.open()?);
+        }
+        let pool = Pool::builder()
+            .max_size(nconn as u32)
+            .test_on_check_out(pool_config.test_on_check_out)
+            .build(manager.clone())?;
+
+        let mut source = Self::with_pool(pool, manager, tls);
+        source.opts.conn_wait_timeout = pool_config.conn_wait_timeout;
+        source
+    }
+
+    fn with_pool(pool: Pool<PgManager<C>>, manager: PgManager<C>, tls: C) -> Self {
+        Self {
+            pool,
+            manager,
+            tls,
+            origin_query: None,
+            queries: vec![],
+            names: vec![],
+            schema: vec![],
+            pg_schema: vec![],
+            scales: vec![],
+            opts: PostgresOptions::default(),
+            decode_errors: DecodeErrorReport::default(),
+            materialized: None,
+            copy_tables: Arc::default(),
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Drop the generated (`GENERATED ALWAYS AS`) columns of the queried tables from the result
+    /// in `fetch_metadata`, requires Postgres 12 or later. Only the columns a `*` brings in are
+    /// dropped, a generated column named explicitly in the projection (e.g. `SELECT *, total`)
+    /// is kept. The queries are then wrapped to select the remaining columns by name, so the
+    /// result must not contain duplicate column names.
+    pub fn set_exclude_generated(&mut self, exclude_generated: bool) {
+        self.opts.exclude_generated = exclude_generated;
+    }
+
+    /// Read only the value at a path (e.g. `["address", "city"]`) of the given json or jsonb
+    /// columns, extracted by the server with `#>>` so that the rest of the documents is not
+    /// transferred. Such a column is then a text, NULL where the path does not exist; cast the
+    /// column in the query instead for another type. Like `set_exclude_generated`, the queries are
+    /// wrapped in `fetch_metadata` to select the columns by name.
+    pub fn set_json_paths(&mut self, json_paths: HashMap<String, Vec<String>>) {
+        self.opts.json_paths = json_paths;
+    }
+
+    /// Expand each composite column in `fetch_metadata` into one column per field, named
+    /// `column.field`, so that the fields are read with their own types. Nested composites are
+    /// expanded as well. Like `set_exclude_generated`, the queries are then wrapped to select the
+    /// fields by name.
+    pub fn set_flatten_composites(&mut self, flatten_composites: bool) {
+        self.opts.flatten_composites = flatten_composites;
+    }
+
+    /// Read each column of an enum type as the position of its label in the order the type
+    /// defines (1 for the first, as listed by `pg_enum`), rather than as the label, so that the
+    /// values sort like the enum does in Postgres. Such a column is then an `Int4`. Like
+    /// `set_exclude_generated`, the queries are wrapped in `fetch_metadata` to select the
+    /// columns by name.
+    pub fn set_enum_order(&mut self, enum_order: bool) {
+        self.opts.enum_order = enum_order;
+    }
+
+    /// Read the scale each numeric column declares (e.g. 4 for a `numeric(18,4)`) in
+    /// `fetch_metadata`, so that the column can be produced as an unscaled `i64` and its scale.
+    /// Postgres only exposes the scales through a temporary view of the first query, which takes
+    /// a transaction and a catalog lookup, so this is off by default.
+    pub fn set_declared_scales(&mut self, declared_scales: bool) {
+        self.opts.declared_scales = declared_scales;
+    }
+
+    /// Let the partitions count their rows on the first call to `nrows` if `result_rows` was not
+    /// called, instead of reporting 0. The count costs an extra round trip either way. If the
+    /// count fails, `nrows` reports 0 and the next read of the partition fails with the error.
+    pub fn set_lazy_nrows(&mut self, lazy_nrows: bool) {
+        self.opts.lazy_nrows = lazy_nrows;
+    }
+
+    /// Let a bool be produced from integer and text columns, such as `0`/`1` or `'Y'`/`'N'`
+    /// flags of a legacy schema. A value in neither set of `coercion` fails the partition.
+    pub fn set_bool_coercion(&mut self, coercion: Option<BoolCoercion>) {
+        self.opts.bool_coercion = coercion;
+    }
+
+    /// Choose what the CSV protocol (or any protocol, for `DecodeErrorPolicy::SkipRow`) does
+    /// with a value it cannot parse, see `DecodeErrorPolicy`.
+    pub fn set_decode_error_policy(&mut self, policy: DecodeErrorPolicy) {
+        self.opts.decode_error_policy = policy;
+    }
+
+    /// Read a value the CSV protocol cannot parse as a `ty` as `value` instead, such as `"0"`
+    /// for a `Numeric` or `"1970-01-01"` for a `Date`. `value` is parsed like the text of the
+    /// column, and applies to every type produced as the same rust type as `ty` (e.g. `Money`
+    /// too for a `Numeric`). This comes before the `DecodeErrorPolicy`, which still applies to
+    /// the other types and to a `value` that cannot be parsed either.
+    #[throws(PostgresSourceError)]
+    pub fn set_decode_fallback(&mut self, ty: PostgresTypeSystem, value: String) {
+        let name = Realize::<FTypeName>::realize(ty)?();
+        // a nullable type is realized as its option, while the value is produced as the type
+        let name = name
+            .strip_prefix("core::option::Option<")
+            .and_then(|name| name.strip_suffix('>'))
+            .unwrap_or(name);
+        self.opts.decode_fallbacks.insert(name, value);
+    }
+
+    /// The values the partitions of this source read as NULL under `DecodeErrorPolicy::Null`
+    /// or as a fallback of `set_decode_fallback`, complete once they are all parsed. A source
+    /// from `reset` starts a report of its own.
+    pub fn decode_errors(&self) -> DecodeErrorReport {
+        self.decode_errors.clone()
+    }
+
+    /// Fail on a date read by the CSV protocol that carries a time, such as a timestamp cast
+    /// by mistake, instead of truncating it to the date.
+    pub fn set_strict_dates(&mut self, strict_dates: bool) {
+        self.opts.strict_dates = strict_dates;
+    }
+
+    /// The time zone a timestamptz is converted to when produced as a `DateTime<Tz>` or in a
+    /// json row, UTC by default. The session time zone is left alone, so the values produced as
+    /// a `DateTime<Utc>` are the same for all the protocols.
+    pub fn set_output_timezone(&mut self, timezone: Tz) {
+        self.opts.timezone = timezone;
+    }
+
+    /// The number of days a month of an interval is taken to last when the interval is produced
+    /// as an `i64` of microseconds, as for an arrow `Duration(Microsecond)`. A month has no fixed
+    /// length, so by default (`None`) an interval with months (or years) fails to be produced as
+    /// one rather than being given a length it may not have. Set it to 30 to follow postgres'
+    /// `justify_days` and `extract(epoch from ...)`, which also takes a year as 12 months. A day
+    /// is always taken as 24 hours.
+    pub fn set_interval_month_days(&mut self, days: Option<i64>) {
+        self.opts.interval_month_days = days;
+    }
+
+    /// Choose how a bytea column is encoded when produced as a `String`, see `ByteaEncoding`.
+    pub fn set_bytea_encoding(&mut self, encoding: ByteaEncoding) {
+        self.opts.bytea_encoding = encoding;
+    }
+
+    /// Take the `timestamp` (without time zone) columns as UTC, for schemas storing instants
+    /// that way: they are then `TimestampTz` in the schema and produce a `DateTime<Utc>`.
+    pub fn set_assume_utc(&mut self, assume_utc: bool) {
+        self.opts.assume_utc = assume_utc;
+    }
+
+    /// Read a random sample of the queries in `fetch_metadata` rather than their whole result,
+    /// for a quick look at a large table. A query on a single table (including the partition
+    /// queries wrapping it) reads `percent` of its pages, any other query gets `rows` rows
+    /// picked at random, which cannot be partitioned.
+    pub fn set_sample(&mut self, sample: Option<PostgresSample>) {
+        self.opts.sample = sample;
+    }
+
+    pub fn sample(&self) -> Option<PostgresSample> {
+        self.opts.sample
+    }
+
+    /// Read at most `max_rows` rows of each partition, for a preview of a query that may return
+    /// far more than wanted. A `LIMIT` is appended to every partition query in `fetch_metadata`
+    /// and the row counts are those of the limited queries. Which rows are read is up to the
+    /// database unless the query is ordered, and the result is silently incomplete whenever a
+    /// partition has more rows than the cap.
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) {
+        self.opts.max_rows = max_rows;
+    }
+
+    pub fn max_rows(&self) -> Option<usize> {
+        self.opts.max_rows
+    }
+
+    /// Run `sql` (e.g. `SET LOCAL enable_seqscan = off`) in a transaction of its own before each
+    /// read of every partition, see `PostgresSourcePartition::set_partition_sql`.
+    pub fn set_partition_sql(&mut self, sql: Option<String>) {
+        self.opts.partition_sql = sql;
+    }
+
+    /// Read every partition at `snapshot`, a snapshot exported by another transaction, such as
+    /// the one `CREATE_REPLICATION_SLOT ... EXPORT_SNAPSHOT` returns to bootstrap change data
+    /// capture with the state of the tables right before the first change of the slot. Each
+    /// partition imports it with `SET TRANSACTION SNAPSHOT` into a repeatable read transaction
+    /// of its own, which also runs the count of `result_rows` and `set_partition_sql`.
+    ///
+    /// A snapshot can only be imported while the transaction that exported it is open. For a
+    /// replication slot, that is until the replication connection that created it runs another
+    /// command or closes, so keep it idle until every partition started reading (a partition
+    /// that reconnects, see `set_resume_key`, imports the snapshot again). Once imported, the
+    /// snapshot lasts until the partition ends its read.
+    pub fn set_snapshot(&mut self, snapshot: Option<String>) {
+        self.opts.snapshot = snapshot;
+    }
+
+    /// Make the partitions resumable on `key`, an integer column that must be unique, not null
+    /// and ordered ascending by every partition query (`fetch_metadata` checks the ORDER BY and
+    /// the type). Each partition tracks the last key the destination got, and a `parser` called
+    /// again after a read failed reads only the rows past it (`WHERE key > last`), over a new
+    /// connection if the failure closed the previous one. The key is taken when the next batch
+    /// is fetched, i.e. once the previous one was consumed, so the rows of the batch in progress
+    /// when the read failed are read again: the resume is at-least-once, and a destination that
+    /// kept part of that batch gets those rows twice.
+    pub fn set_resume_key(&mut self, key: Option<String>) {
+        self.opts.resume_key = key;
+    }
+
+    /// Read the hstore columns, which the binary protocol cannot COPY, with a second query
+    /// instead of falling back to the cursor protocol for the whole result. The COPY sends the
+    /// other columns (and NULLs in place of the hstore ones), and for each batch of rows a
+    /// second connection, opened by each parser outside of the pool, reads the hstore columns
+    /// of the rows of the batch by `key`: `SELECT key, ... FROM (query) WHERE key = ANY(keys)`.
+    /// `key` must be an integer column, not null, unique in the result and stable: the two
+    /// queries run at different times, so a row whose key changed or that was deleted in
+    /// between fails the read, and the hstore values are those of the second query. A primary
+    /// key of a table not updated during the read fits. Only for `BinaryProtocol`.
+    pub fn set_hybrid_key(&mut self, key: Option<String>) {
+        self.opts.hybrid_key = key;
+    }
+
+    /// Check out and validate the connections of the partitions concurrently in `partition`,
+    /// rather than one after the other, so that reconnecting the ones the pool found dead (TLS
+    /// handshake and authentication included) and the round trips of the checks overlap. This
+    /// helps short reads dominated by the connection setup. Nothing is warmed up with more
+    /// queries than `nconn`, as their partitions check out their connections as they read.
+    pub fn set_warm_up(&mut self, warm_up: bool) {
+        self.opts.warm_up = warm_up;
+    }
+
+    /// Check in `fetch_metadata` that every partition query has the columns of the first one, by
+    /// name and in the same order, failing with `ColumnMismatch` otherwise. The columns are only
+    /// read from the first query, so a hand-written partition query listing them in another order
+    /// would otherwise be read misaligned. Costs a round trip to prepare each query.
+    pub fn set_check_columns(&mut self, check_columns: bool) {
+        self.opts.check_columns = check_columns;
+    }
+
+    /// Report how long each partition waits for a connection from the pool. With more
+    /// partitions than connections the wait grows, which points to raising `nconn`. Those
+    /// partitions check out their connection as they read, so are not reported here, see
+    /// `PostgresSourcePartition::conn_wait` for their wait.
+    pub fn set_conn_wait_callback(&mut self, callback: ConnWaitCallback) {
+        self.opts.conn_wait_callback = Some(callback);
+    }
+
+    /// Ask the CSV protocol to copy with `HEADER` and check the header row against the column
+    /// names from `fetch_metadata`, failing the partition if they diverge.
+    pub fn set_csv_header(&mut self, csv_header: bool) {
+        self.opts.csv_header = csv_header;
+    }
+
+    /// Ask the CSV protocol to copy NULL as `null` instead of an empty field, which is otherwise
+    /// indistinguishable from an empty string once parsed. An empty field is then read as an
+    /// empty string, and so is `null` itself: pick a sentinel that does not occur in the data.
+    pub fn set_csv_null(&mut self, null: Option<String>) {
+        self.opts.csv_null = null;
+    }
+
+    /// Create a fresh source for a new set of queries, sharing the connection pool of this one.
+    /// Since `partition` consumes the source, call this beforehand to keep the pool around.
+    pub fn reset(&self) -> Self {
+        let mut source = Self::with_pool(self.pool.clone(), self.manager.clone(), self.tls.clone());
+        source.opts = self.opts.clone();
+        source
+    }
+
+    // the connections of the first partitions (up to the size of the pool) checked out and
+    // validated concurrently, with how long each one took, see `set_warm_up`
+    #[throws(PostgresSourceError)]
+    fn warm_up_conns(&self) -> Vec<(PgConn<C>, Duration)> {
+        let pool = &self.pool;
+        let n = self.queries.len().min(pool.max_size() as usize);
+        let start = Instant::now();
+        let conns = (0..n)
+            .into_par_iter()
+            .map(|_| {
+                let start = Instant::now();
+                let mut conn = pool.get()?;
+                conn.batch_execute("")?;
+                Ok((conn, start.elapsed()))
+            })
+            .collect::<Result<Vec<_>, PostgresSourceError>>()?;
+        debug!("warmed up {} connections in {:?}", n, start.elapsed());
+        conns
+    }
+
+    /// Split a semicolon-separated batch of queries into one source per statement, sharing the
+    /// connection pool and settings of this one. Each result set is a table of its own: the
+    /// source comes with its statement as the query, call `fetch_metadata` for the schema and
+    /// `partition` for the partitions (or `set_queries` first to partition it by hand).
+    #[throws(PostgresSourceError)]
+    pub fn split_batch(&self, sql: &str) -> Vec<Self> {
+        split_statements(sql, &PostgreSqlDialect {})?
+            .into_iter()
+            .map(|query| {
+                let mut source = self.reset();
+                source.queries = vec![query];
+                source
+            })
+            .collect()
+    }
+
+    /// Run `query` once into a table on the server and point this source at `num` ranges of
+    /// it by `ctid`, for an expensive query that cannot be partitioned on a column. A `TEMP`
+    /// table is only visible to the session creating it, so an `UNLOGGED` one is used for the
+    /// partitions to read it in parallel over their own connections. It takes as much disk on
+    /// the server as the result of `query` and is dropped along with the last partition (or
+    /// this source, if never partitioned). If the process dies before, or the drop fails (it
+    /// takes a connection of the pool), the table is left on the server, see
+    /// `drop_materialized` to remove it. A range of `ctid` is only read by a TID range scan
+    /// from Postgres 14 on, before which every partition would scan the whole table, so the
+    /// table is read by a single query there.
+    #[throws(PostgresSourceError)]
+    pub fn materialize(&mut self, query: &str, num: usize) {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!(e))?
+            .as_nanos();
+        let name = format!("cx_materialized_{}_{}", std::process::id(), nanos);
+
+        let mut conn = self.pool.get()?;
+        conn.batch_execute(&format!("CREATE UNLOGGED TABLE {} AS {}", name, query))?;
+        self.materialized = Some(Arc::new(MaterializedTable {
+            pool: self.pool.clone(),
+            name: name.clone(),
+        }));
+        let npages: i64 = conn
+            .query_one(
+                &*format!(
+                    "SELECT pg_relation_size('{}') / current_setting('block_size')::bigint",
+                    name
+                ),
+                &[],
+            )?
+            .get(0);
+
+        let version: i32 = conn
+            .query_one("SELECT current_setting('server_version_num')::int", &[])?
+            .get(0);
+        let num = match version >= 140000 {
+            true => num.max(1),
+            false => {
+                warn!(
+                    "reading {} by a single query, ranges of ctid scan the whole table before Postgres 14",
+                    name
+                );
+                1
+            }
+        };
+        let step = (npages as usize + num - 1) / num;
+        self.origin_query = Some(format!("SELECT * FROM {}", name));
+        self.queries = match step {
+            0 => vec![CXQuery::Wrapped(format!("SELECT * FROM {}", name))],
+            _ => (0..num)
+                .map(|i| {
+                    let lower = format!("ctid >= '({},0)'::tid", i * step);
+                    CXQuery::Wrapped(match i + 1 == num {
+                        true => format!("SELECT * FROM {} WHERE {}", name, lower),
+                        false => format!(
+                            "SELECT * FROM {} WHERE {} AND ctid < '({},0)'::tid",
+                            name,
+                            lower,
+                            (i + 1) * step
+                        ),
+                    })
+                })
+                .collect(),
+        };
+    }
+
+    /// Drop the tables of `materialize` created more than `older_than` ago, which a process
+    /// killed before dropping them left on the server, and return their names. Pass an age
+    /// longer than any read still running, whose table would be dropped from under it.
+    #[throws(PostgresSourceError)]
+    pub fn drop_materialized(&self, older_than: Duration) -> Vec<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!(e))?
+            .as_nanos();
+        let mut conn = self.pool.get()?;
+        let tables: Vec<(String, String)> = conn
+            .query(
+                "SELECT n.nspname, c.relname FROM pg_class c \
+                 JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE c.relkind = 'r' AND c.relname LIKE 'cx\\_materialized\\_%'",
+                &[],
+            )?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect();
+        let mut dropped = vec![];
+        for (schema, table) in tables {
+            // named cx_materialized_<pid>_<nanoseconds since the epoch>
+            let created = table
+                .rsplit('_')
+                .next()
+                .and_then(|v| v.parse::<u128>().ok());
+            match created {
+                Some(created) if now.saturating_sub(created) > older_than.as_nanos() => {
+                    conn.batch_execute(&format!(
+                        "DROP TABLE IF EXISTS {}.{}",
+                        quote_ident(&schema),
+                        quote_ident(&table)
+                    ))?;
+                    dropped.push(table);
+                }
+                _ => {}
+            }
+        }
+        dropped
+    }
+
+    /// Read the large object `loid` as successive chunks of up to `pages_per_chunk` pages of
+    /// `pg_largeobject` (2kB each by default), for an object too large to load at once. Reading
+    /// `pg_largeobject` takes a superuser or a grant on it. The pages are read by separate
+    /// queries, so a concurrent write to the object can show up in the later chunks only.
+    #[throws(PostgresSourceError)]
+    pub fn large_object_chunks(
+        &self,
+        loid: u32,
+        pages_per_chunk: usize,
+    ) -> PostgresLargeObjectChunks<C> {
+        let mut conn = self.pool.get()?;
+        if conn
+            .query_opt(
+                "SELECT 1 FROM pg_largeobject_metadata WHERE oid = $1",
+                &[&loid],
+            )?
+            .is_none()
+        {
+            throw!(anyhow!("large object {} does not exist", loid));
+        }
+        let page_size: i32 = conn
+            .query_one("SELECT current_setting('block_size')::int4 / 4", &[])?
+            .get(0);
+        PostgresLargeObjectChunks {
+            conn,
+            loid,
+            pages_per_chunk: pages_per_chunk.max(1) as i64,
+            page_size: page_size as usize,
+            next_page: 0,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Point this source at the partitions of the declaratively partitioned `table`, one query
+    /// `SELECT {projection} FROM ONLY <partition>` each, so that the read is split along the
+    /// storage of the table rather than by ranges of a column. Sub-partitions are expanded down
+    /// to the leaves, which are the only ones holding rows. `table` is resolved as a `regclass`,
+    /// so it may be schema-qualified; a table without partitions is read as a single query.
+    #[throws(PostgresSourceError)]
+    pub fn partition_by_children(&mut self, table: &str, projection: &str) {
+        let mut conn = self.pool.get()?;
+        let leaves: Vec<String> = conn
+            .query(
+                "WITH RECURSIVE tree(relid) AS (
+                    SELECT $1::text::regclass::oid
+                    UNION ALL
+                    SELECT i.inhrelid FROM pg_inherits i JOIN tree t ON i.inhparent = t.relid
+                )
+                SELECT t.relid::regclass::text FROM tree t JOIN pg_class c ON c.oid = t.relid
+                WHERE c.relkind <> 'p' ORDER BY 1",
+                &[&table],
+            )?
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+        debug!("{} partitions to read from {}", leaves.len(), table);
+
+        self.origin_query = Some(format!("SELECT {} FROM {}", projection, table));
+        self.queries = match leaves.is_empty() {
+            true => vec![CXQuery::Wrapped(format!(
+                "SELECT {} FROM {}",
+                projection, table
+            ))],
+            // `ONLY`, as a table of the old inheritance is not a leaf and holds rows of its own
+            false => leaves
+                .iter()
+                .map(|leaf| CXQuery::Wrapped(format!("SELECT {} FROM ONLY {}", projection, leaf)))
+                .collect(),
+        };
+    }
+}
+
+impl<P, C> Source for PostgresSource<P, C>
+where
+    PostgresSourcePartition<P, C>:
+        SourcePartition<TypeSystem = PostgresTypeSystem, Error = PostgresSourceError>,
+    P: PostgresProtocol + Send,
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    const DATA_ORDERS: &'static [DataOrder] = &[DataOrder::RowMajor];
+    type Partition = PostgresSourcePartition<P, C>;
+    type TypeSystem = PostgresTypeSystem;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn set_data_order(&mut self, data_order: DataOrder) {
+        if !matches!(data_order, DataOrder::RowMajor) {
+            throw!(ConnectorXError::UnsupportedDataOrder(data_order));
+        }
+    }
+
+    fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
+        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+    }
+
+    fn set_origin_query(&mut self, query: Option<String>) {
+        self.origin_query = query;
+    }
+
+    #[throws(PostgresSourceError)]
+    fn fetch_metadata(&mut self) {
+        assert!(!self.queries.is_empty());
+
+        if let Some(key) = &self.opts.resume_key {
+            if let Some(q) = self
+                .queries
+                .iter()
+                .find(|q| !ordered_by(q.as_str(), key, &PostgreSqlDialect {}))
+            {
+                throw!(PostgresSourceError::NotOrderedByKey(
+                    q.to_string(),
+                    key.clone()
+                ));
+            }
+        }
+
+        if let Some(sample) = &self.opts.sample {
+            let partitioned = self.queries.len() > 1;
+            self.queries = self
+                .queries
+                .iter()
+                .map(|q| sample_query(q.as_str(), sample, partitioned).map(CXQuery::Wrapped))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(q) = &self.origin_query {
+                self.origin_query = Some(sample_query(q, sample, false)?);
+            }
+        }
+
+        if let Some(max_rows) = self.opts.max_rows {
+            self.queries = self
+                .queries
+                .iter()
+                .map(|q| match is_explain(q.as_str()) {
+                    true => q.clone(),
+                    false => CXQuery::Wrapped(format!(
+                        "SELECT * FROM ({}) AS CXTMPTAB_LIMIT LIMIT {}",
+                        q, max_rows
+                    )),
+                })
+                .collect();
+            // the count of the whole query is not the sum of the limited partitions
+            self.origin_query = None;
+        }
+
+        if P::COPY {
+            if let Some(q) = self.queries.iter().find(|q| is_explain(q.as_str())) {
+                throw!(PostgresSourceError::NotCopyable(q.to_string(), P::NAME));
+            }
+        }
+        if let Some(q) = self.queries.iter().find(|q| {
+            matches!(
+                explain_options(q.as_str(), &PostgreSqlDialect {}),
+                Some(ExplainOptions {
+                    analyze: true,
+                    format: None
+                })
+            )
+        }) {
+            throw!(PostgresSourceError::UncountableExplain(q.to_string()));
+        }
+
+        let mut conn = self.pool.get()?;
+        let first_query = &self.queries[0];
+
+        let stmt = conn.prepare(first_query.as_str())?;
+
+        let (mut names, mut pg_types): (Vec<String>, Vec<postgres::types::Type>) = stmt
+            .columns()
+            .iter()
+            .map(|col| (col.name().to_string(), col.type_().clone()))
+            .unzip();
+        if names.is_empty() {
+            throw!(PostgresSourceError::NoColumns(first_query.to_string()));
+        }
+        if self.opts.check_columns {
+            for query in &self.queries[1..] {
+                let columns: Vec<String> = conn
+                    .prepare(query.as_str())?
+                    .columns()
+                    .iter()
+                    .map(|col| col.name().to_string())
+                    .collect();
+                if columns != names {
+                    throw!(PostgresSourceError::ColumnMismatch(
+                        query.to_string(),
+                        columns,
+                        names
+                    ));
+                }
+            }
+        }
+        for (name, pg_ty) in names.iter().zip(&pg_types) {
+            if pg_ty.name() == "halfvec" {
+                throw!(PostgresSourceError::UnsupportedExtensionType(
+                    name.clone(),
+                    pg_ty.name().to_string()
+                ));
+            }
+        }
+
+        // the options below read a column through an expression of it rather than as is, and are
+        // applied one after the other to the expressions, so the queries are wrapped once at the
+        // end to select them all by name
+        let mut exprs: Vec<String> = names
+            .iter()
+            .map(|name| format!("CXTMPTAB_PROJ.{}", quote_ident(name)))
+            .collect();
+        let mut wrap = false;
+
+        if self.opts.exclude_generated {
+            let generated = get_implicit_generated_columns(&mut conn, first_query)?;
+            if !generated.is_empty() {
+                let columns: Vec<_> = names
+                    .into_iter()
+                    .zip(pg_types)
+                    .zip(exprs)
+                    .filter(|((name, _), _)| !generated.contains(name))
+                    .collect();
+                names = vec![];
+                pg_types = vec![];
+                exprs = vec![];
+                for ((name, ty), expr) in columns {
+                    names.push(name);
+                    pg_types.push(ty);
+                    exprs.push(expr);
+                }
+                wrap = true;
+            }
+        }
+
+        if !self.opts.json_paths.is_empty() {
+            for column in self.opts.json_paths.keys() {
+                match names.iter().position(|name| name == column) {
+                    Some(i)
+                        if [postgres::types::Type::JSON, postgres::types::Type::JSONB]
+                            .contains(&pg_types[i]) => {}
+                    Some(_) => throw!(anyhow!(
+                        "column {:?} is not json, no path can be extracted from it",
+                        column
+                    )),
+                    None => throw!(anyhow!(
+                        "column {:?} to extract a json path from is not in the result",
+                        column
+                    )),
+                }
+            }
+            for ((name, ty), expr) in names.iter().zip(pg_types.iter_mut()).zip(exprs.iter_mut()) {
+                if let Some(path) = self.opts.json_paths.get(name) {
+                    *expr = format!(
+                        "({}) #>> ARRAY[{}]::text[]",
+                        expr,
+                        path.iter()
+                            .map(|p| format!("'{}'", p.replace('\'', "''")))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                    *ty = postgres::types::Type::TEXT;
+                }
+            }
+            wrap = true;
+        }
+
+        if self.opts.enum_order && pg_types.iter().any(|ty| matches!(ty.kind(), Kind::Enum(_))) {
+            for (ty, expr) in pg_types.iter_mut().zip(exprs.iter_mut()) {
+                if !matches!(ty.kind(), Kind::Enum(_)) {
+                    continue;
+                }
+                let labels = get_enum_labels(&mut conn, ty.oid())?;
+                let position = match labels.is_empty() {
+                    // no value but NULL to read
+                    true => "NULL".to_string(),
+                    false => format!(
+                        "CASE {}{} END",
+                        expr,
+                        labels
+                            .iter()
+                            .enumerate()
+                            .map(|(i, label)| format!(
+                                " WHEN '{}' THEN {}",
+                                label.replace('\'', "''"),
+                                i + 1
+                            ))
+                            .collect::<String>()
+                    ),
+                };
+                *expr = format!("({})::int4", position);
+                *ty = postgres::types::Type::INT4;
+            }
+            wrap = true;
+        }
+
+        if self.opts.flatten_composites
+            && pg_types
+                .iter()
+                .any(|ty| matches!(ty.kind(), Kind::Composite(_)))
+        {
+            let mut columns = vec![];
+            for ((name, ty), expr) in names.iter().zip(&pg_types).zip(exprs) {
+                flatten_column(name, expr, ty, &mut columns);
+            }
+            names = vec![];
+            pg_types = vec![];
+            exprs = vec![];
+            for (name, expr, ty) in columns {
+                names.push(name);
+                exprs.push(expr);
+                pg_types.push(ty);
+            }
+            wrap = true;
+        }
+
+        // the fields of an anonymous record have no types to read them with, while `to_json`
+        // keeps them along with their names
+        for (ty, expr) in pg_types.iter().zip(exprs.iter_mut()) {
+            if ty.name() == "_record" {
+                *expr = format!("to_json({})", expr);
+                wrap = true;
+            }
+        }
+
+        // the extended statistics are sent by their binary send functions in an internal format,
+        // while their text output is the readable one
+        for (ty, expr) in pg_types.iter_mut().zip(exprs.iter_mut()) {
+            if matches!(ty.name(), "pg_ndistinct" | "pg_dependencies") {
+                *expr = format!("({})::text", expr);
+                *ty = postgres::types::Type::TEXT;
+                wrap = true;
+            }
+        }
+
+        if wrap {
+            let projection = names
+                .iter()
+                .zip(&exprs)
+                .map(|(name, expr)| format!("{} AS {}", expr, quote_ident(name)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.queries = self
+                .queries
+                .iter()
+                .map(|q| {
+                    CXQuery::Wrapped(format!(
+                        "SELECT {} FROM ({}) AS CXTMPTAB_PROJ",
+                        projection, q
+                    ))
+                })
+                .collect();
+        }
+
+        self.names = names;
+        self.schema = pg_types
+            .iter()
+            .map(|t| match PostgresTypeSystem::from(t) {
+                PostgresTypeSystem::Timestamp(n) if self.opts.assume_utc => {
+                    PostgresTypeSystem::TimestampTz(n)
+                }
+                ty => ty,
+            })
+            .collect();
+
+        for (what, key) in [
+            ("resume", &self.opts.resume_key),
+            ("hybrid", &self.opts.hybrid_key),
+        ] {
+            if let Some(key) = key {
+                match self.names.iter().position(|name| name == key) {
+                    Some(i)
+                        if matches!(
+                            self.schema[i],
+                            PostgresTypeSystem::Int2(_)
+                                | PostgresTypeSystem::Int4(_)
+                                | PostgresTypeSystem::Int8(_)
+                        ) => {}
+                    Some(_) => throw!(anyhow!("{} key {:?} is not an integer column", what, key)),
+                    None => throw!(anyhow!("{} key {:?} is not in the result", what, key)),
+                }
+            }
+        }
+
+        // the hstore columns are read by the second query of `set_hybrid_key`
+        let hybrid = P::NAME == BinaryProtocol::NAME && self.opts.hybrid_key.is_some();
+        for ((name, ty), pg_ty) in self.names.iter().zip(&self.schema).zip(&pg_types) {
+            if !P::supports(*ty) && !(hybrid && matches!(ty, PostgresTypeSystem::HSTORE(_))) {
+                throw!(PostgresSourceError::UnsupportedType(
+                    name.clone(),
+                    pg_ty.name().to_string(),
+                    P::NAME,
+                ));
+            }
+        }
+        self.pg_schema = self
+            .schema
+            .iter()
+            .zip(pg_types.iter())
+            .map(|(t1, t2)| PostgresTypePairs(t2, t1).into())
+            .collect();
+
+        self.scales = vec![None; self.schema.len()];
+        if self.opts.declared_scales
+            && self
+                .schema
+                .iter()
+                .any(|ty| matches!(ty, PostgresTypeSystem::Numeric(_)))
+        {
+            match get_declared_scales(&mut conn, &self.queries[0]) {
+                Ok(scales) => {
+                    for (i, ty) in self.schema.iter().enumerate() {
+                        if let PostgresTypeSystem::Numeric(_) = ty {
+                            self.scales[i] = scales.get(i).copied().flatten();
+                        }
+                    }
+                }
+                Err(e) => warn!(
+                    "cannot read the declared scales of the numeric columns: {}",
+                    e
+                ),
+            }
+        }
+        if self.schema.iter().any(|ty| {
+            matches!(
+                ty,
+                PostgresTypeSystem::Money(_) | PostgresTypeSystem::MoneyArray(_)
+            )
+        }) {
+            // money is an integer of the smallest unit of the currency of lc_monetary, whose
+            // number of fraction digits is the scale of money cast to numeric
+            let scale: i32 = conn
+                .query_one("SELECT scale('1'::money::numeric)", &[])?
+                .get(0);
+            for (i, ty) in self.schema.iter().enumerate() {
+                if let PostgresTypeSystem::Money(_) | PostgresTypeSystem::MoneyArray(_) = ty {
+                    self.scales[i] = Some(scale as u32);
+                }
+            }
+        }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn result_rows(&mut self) -> Option<usize> {
+        if self.opts.decode_error_policy == DecodeErrorPolicy::SkipRow {
+            throw!(PostgresSourceError::SkipRowCounted(
+                self.origin_query.clone().unwrap_or_default()
+            ));
+        }
+        match &self.origin_query {
+            Some(q) => {
+                let cxq = CXQuery::Naked(q.clone());
+                let mut conn = self.pool.get()?;
+                let nrows = get_total_rows(&mut conn, &cxq)?;
+                Some(nrows)
+            }
+            None => None,
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+
+    fn schema(&self) -> Vec<Self::TypeSystem> {
+        self.schema.clone()
+    }
+
+    #[throws(PostgresSourceError)]
+    fn partition(self) -> Vec<Self::Partition> {
+        // with more queries than connections, checking them all out here would wait for
+        // connections only given back once the partitions are dropped
+        let lazy = self.queries.len() > self.pool.max_size() as usize;
+        let mut warm = match self.opts.warm_up && !lazy {
+            true => self.warm_up_conns()?,
+            false => vec![],
+        }
+        .into_iter();
+        let mut ret = vec![];
+        for query in self.queries {
+            let start = Instant::now();
+            let (conn, conn_wait) = match (lazy, warm.next()) {
+                (true, _) => (None, Duration::ZERO),
+                (false, Some((conn, conn_wait))) => (Some(conn), conn_wait),
+                (false, None) => (Some(self.pool.get()?), start.elapsed()),
+            };
+            if !lazy {
+                debug!("waited {:?} for the connection of {}", conn_wait, query);
+                if let Some(callback) = &self.opts.conn_wait_callback {
+                    callback(&query, conn_wait);
+                }
+            }
+
+            let mut partition = PostgresSourcePartition::<P, C>::new(
+                conn,
+                &query,
+                &self.names,
+                &self.schema,
+                &self.pg_schema,
+            );
+            partition.set_csv_header(self.opts.csv_header);
+            partition.set_csv_null(self.opts.csv_null.clone());
+            partition.set_lazy_nrows(self.opts.lazy_nrows);
+            partition.scales = self.scales.clone();
+            partition.bool_coercion = self.opts.bool_coercion.clone();
+            match self.opts.decode_error_policy {
+                DecodeErrorPolicy::Null => {
+                    partition.decode_errors = Some(self.decode_errors.clone())
+                }
+                DecodeErrorPolicy::SkipRow => {
+                    partition.skip_rows = Some(self.decode_errors.clone())
+                }
+                DecodeErrorPolicy::Fail => {}
+            }
+            if !self.opts.decode_fallbacks.is_empty() {
+                partition.decode_fallbacks = Some(DecodeFallbacks {
+                    values: self.opts.decode_fallbacks.clone(),
+                    report: self.decode_errors.clone(),
+                });
+            }
+            partition.strict_dates = self.opts.strict_dates;
+            partition.bytea_encoding = self.opts.bytea_encoding;
+            partition.timezone = self.opts.timezone;
+            partition.interval_month_days = self.opts.interval_month_days;
+            partition.conn_wait = conn_wait;
+            partition.conn_wait_timeout = self.opts.conn_wait_timeout;
+            partition.set_partition_sql(self.opts.partition_sql.clone());
+            partition.set_snapshot(self.opts.snapshot.clone());
+            if lazy {
+                partition.lazy = true;
+                partition.pool = Some(self.pool.clone());
+            }
+            if let Some(key) = &self.opts.resume_key {
+                let cidx = self.names.iter().position(|name| name == key);
+                partition.resume_key = cidx.map(|cidx| (key.clone(), cidx));
+                partition.pool = Some(self.pool.clone());
+            }
+            if let Some(key) = &self.opts.hybrid_key {
+                partition.hybrid_key = self.names.iter().position(|name| name == key);
+                partition.manager = Some(self.manager.clone());
+            }
+            partition.materialized = self.materialized.clone();
+            partition.copy_tables = self.copy_tables.clone();
+            partition.tls = Some(self.tls.clone());
+            ret.push(partition);
+        }
+        ret
+    }
+}
+
+pub struct PostgresSourcePartition<P, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    // in a RefCell for `nrows` to run the count query in lazy mode, and None until checked out
+    // of `pool` for a partition made with more queries than connections, see `lazy`
+    conn: RefCell<Option<PgConn<C>>>,
+    query: CXQuery<String>,
+    names: Vec<String>,
+    schema: Vec<PostgresTypeSystem>,
+    pg_schema: Vec<postgres::types::Type>,
+    scales: Vec<Option<u32>>,
+    bool_coercion: Option<BoolCoercion>,
+    bytea_encoding: ByteaEncoding,
+    timezone: Tz,
+    interval_month_days: Option<i64>,
+    // where to count the values read as NULL, when decode errors are not to fail the partition
+    decode_errors: Option<DecodeErrorReport>,
+    decode_fallbacks: Option<DecodeFallbacks>,
+    // where to count the rows dropped, when rows with a decode error are to be skipped
+    skip_rows: Option<DecodeErrorReport>,
+    strict_dates: bool,
+    csv_header: bool,
+    csv_null: Option<String>,
+    lazy_nrows: bool,
+    nrows: Cell<Option<usize>>,
+    // the error of a failed count of `nrows` in lazy mode, returned by the next read
+    count_error: RefCell<Option<PostgresSourceError>>,
+    ncols: usize,
+    conn_wait: Duration,
+    // how long to wait for a connection of `pool` when all of them are taken, see `lazy`
+    conn_wait_timeout: Duration,
+    // set while a parser is reading the result, cleared by the parser once it reached the end
+    reading: AtomicBool,
+    tls: Option<C>,
+    partition_sql: Option<String>,
+    snapshot: Option<String>,
+    // whether `conn` is in the transaction of `partition_sql` or `snapshot`, to roll back before
+    // it is reused
+    in_transaction: bool,
+    // the resume key and its column, the last key the destination got, and the pool to take a
+    // new connection from if a failed read closed `conn`, or the first one if `lazy`
+    resume_key: Option<(String, usize)>,
+    last_key: Cell<Option<i64>>,
+    pool: Option<Pool<PgManager<C>>>,
+    // whether `conn` is checked out as the partition starts to read, and given back after
+    // counting its rows, so that partitions beyond the connections of the pool are read in waves
+    lazy: bool,
+    // the column of the key of `PostgresSource::set_hybrid_key`, and how to connect for the
+    // second query
+    hybrid_key: Option<usize>,
+    manager: Option<PgManager<C>>,
+    // after `conn`, so the connection is back in the pool when the table gets dropped
+    materialized: Option<Arc<MaterializedTable<C>>>,
+    copy_tables: Arc<Mutex<HashMap<String, bool>>>,
+    _protocol: PhantomData<P>,
+}
+
+impl<P, C> PostgresSourcePartition<P, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    pub fn new(
+        conn: Option<PgConn<C>>,
+        query: &CXQuery<String>,
+        names: &[String],
+        schema: &[PostgresTypeSystem],
+        pg_schema: &[postgres::types::Type],
+    ) -> Self {
+        Self {
+            conn: RefCell::new(conn),
+            query: query.clone(),
+            names: names.to_vec(),
+            schema: schema.to_vec(),
+            pg_schema: pg_schema.to_vec(),
+            scales: vec![None; schema.len()],
+            bool_coercion: None,
+            bytea_encoding: ByteaEncoding::Hex,
+            timezone: Tz::UTC,
+            interval_month_days: None,
+            decode_errors: None,
+            decode_fallbacks: None,
+            skip_rows: None,
+            strict_dates: false,
+            csv_header: false,
+            csv_null: None,
+            lazy_nrows: false,
+            nrows: Cell::new(None),
+            count_error: RefCell::new(None),
+            ncols: schema.len(),
+            conn_wait: Duration::ZERO,
+            conn_wait_timeout: PostgresPoolConfig::default().conn_wait_timeout,
+            reading: AtomicBool::new(false),
+            tls: None,
+            partition_sql: None,
+            snapshot: None,
+            in_transaction: false,
+            resume_key: None,
+            last_key: Cell::new(None),
+            pool: None,
+            lazy: false,
+            hybrid_key: None,
+            manager: None,
+            materialized: None,
+            copy_tables: Arc::default(),
+            _protocol: PhantomData,
+        }
+    }
+
+    pub fn set_csv_header(&mut self, csv_header: bool) {
+        self.csv_header = csv_header;
+    }
+
+    pub fn set_csv_null(&mut self, null: Option<String>) {
+        self.csv_null = null;
+    }
+
+    pub fn set_lazy_nrows(&mut self, lazy_nrows: bool) {
+        self.lazy_nrows = lazy_nrows;
+    }
+
+    /// Run `sql` before each read of this partition, in a transaction the read runs in too, so
+    /// that its `SET LOCAL` (or `SET CONSTRAINTS`) apply to this partition only. Unlike a plain
+    /// `SET`, they end with the transaction, which is rolled back before the connection is read
+    /// from again or goes back to the pool.
+    pub fn set_partition_sql(&mut self, sql: Option<String>) {
+        self.partition_sql = sql;
+    }
+
+    /// Read this partition at an exported snapshot, see `PostgresSource::set_snapshot`.
+    pub fn set_snapshot(&mut self, snapshot: Option<String>) {
+        self.snapshot = snapshot;
+    }
+
+    /// How long `PostgresSource::partition` waited for the connection of this partition, or with
+    /// more partitions than connections, how long the partition waited for one when it started
+    /// to read.
+    pub fn conn_wait(&self) -> Duration {
+        self.conn_wait
+    }
+
+    /// The last key of `PostgresSource::set_resume_key` the destination got, after which the
+    /// next `parser` resumes the read.
+    pub fn last_key(&self) -> Option<i64> {
+        self.last_key.get()
+    }
+
+    // the query to read: the partition query, or only its rows past the last key once a read
+    // of a resumable partition got some
+    fn read_query(&self) -> String {
+        match (&self.resume_key, self.last_key.get()) {
+            (Some((key, _)), Some(last)) => {
+                let key = quote_ident(key);
+                format!(
+                    "SELECT * FROM ({}) AS CXTMPTAB_RESUME WHERE CXTMPTAB_RESUME.{} > {} ORDER BY CXTMPTAB_RESUME.{}",
+                    self.query, key, last, key
+                )
+            }
+            _ => self.query.to_string(),
+        }
+    }
+
+    // take a new connection for a resumable partition whose last read failed with the
+    // connection, there is nothing left to abort on a closed one
+    #[throws(PostgresSourceError)]
+    fn reconnect(&mut self) {
+        let closed = matches!(self.conn.get_mut(), Some(conn) if conn.is_closed());
+        if let (Some(pool), true) = (&self.pool, closed) {
+            debug!("reconnecting to resume {}", self.query);
+            self.reading.store(false, Ordering::Relaxed);
+            self.in_transaction = false;
+            // the closed connection goes back to the pool first, which may have no other
+            *self.conn.get_mut() = None;
+            *self.conn.get_mut() = Some(wait_for_conn(pool, self.conn_wait_timeout)?);
+        }
+    }
+
+    // check out the connection of a lazy partition that has none
+    #[throws(PostgresSourceError)]
+    fn check_out(&mut self) {
+        if self.conn.get_mut().is_some() {
+            return;
+        }
+        let pool = self
+            .pool
+            .as_ref()
+            .expect("a partition without a connection has a pool");
+        let start = Instant::now();
+        *self.conn.get_mut() = Some(wait_for_conn(pool, self.conn_wait_timeout)?);
+        let conn_wait = start.elapsed();
+        debug!(
+            "waited {:?} for the connection of {}",
+            conn_wait, self.query
+        );
+        self.conn_wait += conn_wait;
+    }
+
+    // give the connection of a lazy partition back to the pool between counting and reading, as
+    // the dispatcher counts all the partitions before it reads any. The read is then in a new
+    // transaction, at the same snapshot only with `snapshot`
+    fn check_in(&mut self) {
+        if self.lazy {
+            self.end_transaction();
+            *self.conn.get_mut() = None;
+        }
+    }
+
+    // get the connection ready for a new read: stop the previous one and end its transaction,
+    // then open the transaction of `snapshot` and `partition_sql` for this one
+    #[throws(PostgresSourceError)]
+    fn start_read(&mut self) {
+        if let Some(e) = self.count_error.get_mut().take() {
+            throw!(e);
+        }
+        self.check_out()?;
+        self.reconnect()?;
+        self.abort_read();
+        self.end_transaction();
+        let mut sql = vec![];
+        if let Some(snapshot) = &self.snapshot {
+            // the snapshot must be imported before any query of the transaction
+            sql.push("BEGIN ISOLATION LEVEL REPEATABLE READ".to_string());
+            sql.push(format!(
+                "SET TRANSACTION SNAPSHOT '{}'",
+                snapshot.replace('\'', "''")
+            ));
+        } else if self.partition_sql.is_some() {
+            sql.push("BEGIN".to_string());
+        }
+        sql.extend(self.partition_sql.clone());
+        if !sql.is_empty() {
+            // rolled back even if `sql` fails, the transaction is open from the BEGIN on
+            self.in_transaction = true;
+            connected(&mut self.conn).batch_execute(&sql.join("; "))?;
+        }
+    }
+
+    fn end_transaction(&mut self) {
+        if !std::mem::take(&mut self.in_transaction) {
+            return;
+        }
+        if let Err(e) = connected(&mut self.conn).batch_execute("ROLLBACK") {
+            warn!(
+                "failed to end the transaction of the partition sql of {}: {}",
+                self.query, e
+            );
+        }
+    }
+
+    // where a parser records the last key of the batches it fetches
+    fn resume(&self) -> Option<(usize, &Cell<Option<i64>>)> {
+        self.resume_key
+            .as_ref()
+            .map(|(_, cidx)| (*cidx, &self.last_key))
+    }
+
+    // Stop the read of a parser dropped before the end of the result (e.g. as the destination
+    // failed), so the rest of it is not pending on the connection when it is used again or goes
+    // back to the pool. Postgres ignores the cancel request if the query finished meanwhile.
+    fn abort_read(&mut self) {
+        if !self.reading.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let conn = connected(&mut self.conn);
+        match &self.tls {
+            Some(tls) => {
+                if let Err(e) = conn.cancel_token().cancel_query(tls.clone()) {
+                    warn!("failed to cancel {}: {}", self.query, e);
+                }
+            }
+            None => debug!(
+                "reading the rest of {} to release the connection",
+                self.query
+            ),
+        }
+        // waits for the server to be done with the query, discarding its rows and error
+        if let Err(e) = conn.batch_execute("") {
+            warn!(
+                "connection still busy after the read of {} was stopped: {}",
+                self.query, e
+            );
+        }
+    }
+
+    // What to COPY: the table itself for a plain scan of a regular table, which saves planning
+    // and running the query on top of it, or else the query. A table with generated columns is
+    // excluded, as `COPY table` leaves them out where `SELECT *` does not, and so is the parent
+    // of inheritance children, as `COPY table` leaves out the rows of the children. The check is
+    // done once per table for all the partitions of the source.
+    #[throws(PostgresSourceError)]
+    fn copy_target(&mut self, query: &str) -> String {
+        if let Some((table, columns)) = plain_table_scan(query, &PostgreSqlDialect {}) {
+            let cached = self.copy_tables.lock().unwrap().get(&table).copied();
+            let copyable = match cached {
+                Some(copyable) => copyable,
+                None => {
+                    let row = connected(&mut self.conn).query_opt(
+                        "SELECT c.relkind::text, c.relkind = 'r' AND NOT EXISTS (
+                                SELECT 1 FROM information_schema.columns i
+                                WHERE i.table_schema = n.nspname AND i.table_name = c.relname
+                                AND i.is_generated = 'ALWAYS'
+                            ) AND NOT EXISTS (SELECT 1 FROM pg_inherits WHERE inhparent = c.oid)
+                            FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+                            WHERE c.oid = to_regclass($1::text)",
+                        &[&table],
+                    )?;
+                    let (relkind, copyable): (Option<String>, bool) = match row {
+                        Some(row) => (
+                            Some(row.get(0)),
+                            row.get::<_, Option<bool>>(1) == Some(true),
+                        ),
+                        None => (None, false),
+                    };
+                    // a view has no physical layout of its own to copy, or to partition by
+                    // (e.g. ranges of ctid), looked up once per source like the rest
+                    if let Some("v" | "m") = relkind.as_deref() {
+                        warn!(
+                            "{} is a view, it is copied through its query, partition it on a column rather than by its physical layout (e.g. ctid)",
+                            table
+                        );
+                    }
+                    self.copy_tables
+                        .lock()
+                        .unwrap()
+                        .insert(table.clone(), copyable);
+                    copyable
+                }
+            };
+            if copyable {
+                debug!("copying {} from the table directly", query);
+                return match columns {
+                    Some(columns) => format!("{} ({})", table, columns.join(", ")),
+                    None => table,
+                };
+            }
+        }
+        format!("({})", query)
+    }
+
+    // the hstore columns of a binary read, and the query copying NULLs in their place, see
+    // `PostgresSource::set_hybrid_key`
+    #[throws(PostgresSourceError)]
+    fn hybrid_columns(&self, read_query: &str) -> Option<(HybridColumns, String)> {
+        let (key, manager) = match (self.hybrid_key, &self.manager) {
+            (Some(key), Some(manager)) => (key, manager),
+            _ => return None,
+        };
+        let columns: Vec<usize> = (0..self.schema.len())
+            .filter(|&cidx| matches!(self.schema[cidx], PostgresTypeSystem::HSTORE(_)))
+            .collect();
+        if columns.is_empty() {
+            return None;
+        }
+
+        let column = |name: &str| format!("CXTMPTAB_HYBRID.{}", quote_ident(name));
+        let projection = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(cidx, name)| match columns.contains(&cidx) {
+                true => format!("NULL::text AS {}", quote_ident(name)),
+                false => column(name),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let copy_query = format!(
+            "SELECT {} FROM ({}) AS CXTMPTAB_HYBRID",
+            projection, read_query
+        );
+        let query = format!(
+            "SELECT {}, {} FROM ({}) AS CXTMPTAB_HYBRID WHERE {} = ANY($1::int8[])",
+            column(&self.names[key]),
+            columns
+                .iter()
+                .map(|&cidx| column(&self.names[cidx]))
+                .collect::<Vec<_>>()
+                .join(", "),
+            read_query,
+            column(&self.names[key]),
+        );
+        let hybrid = HybridColumns {
+            conn: manager.connect()?,
+            query,
+            key,
+            columns,
+            values: HashMap::new(),
+        };
+        Some((hybrid, copy_query))
+    }
+
+    #[throws(PostgresSourceError)]
+    fn binary_parser(&mut self) -> PostgresBinarySourcePartitionParser<'_> {
+        self.start_read()?;
+        let read_query = self.read_query();
+        let (hybrid, copy_query) = match self.hybrid_columns(&read_query)? {
+            Some((hybrid, copy_query)) => (Some(hybrid), copy_query),
+            None => (None, read_query.clone()),
+        };
+        let query = format!(
+            "COPY {} TO STDOUT WITH BINARY",
+            self.copy_target(&copy_query)?
+        );
+        let reader = connected(&mut self.conn).copy_out(&*query)?; // unless reading the data, it seems like issue the query is fast
+        self.reading.store(true, Ordering::Relaxed);
+        let iter = BinaryCopyOutIter::new(reader, &self.pg_schema);
+
+        let mut parser = PostgresBinarySourcePartitionParser::new(
+            iter,
+            read_query.as_str(),
+            &self.names,
+            &self.schema,
+        );
+        parser.scales = self.scales.clone();
+        parser.bool_coercion = self.bool_coercion.clone();
+        parser.bytea_encoding = self.bytea_encoding;
+        parser.timezone = self.timezone;
+        parser.interval_month_days = self.interval_month_days;
+        parser.reading = Some(&self.reading);
+        parser.resume = self.resume();
+        parser.skip_rows = self.skip_rows.clone();
+        parser.hybrid = hybrid;
+        parser
+    }
+
+    #[throws(PostgresSourceError)]
+    fn cursor_parser(&mut self) -> PostgresRawSourceParser<'_> {
+        self.start_read()?;
+        let read_query = self.read_query();
+        let iter =
+            connected(&mut self.conn).query_raw::<_, bool, _>(read_query.as_str(), vec![])?; // unless reading the data, it seems like issue the query is fast
+        self.reading.store(true, Ordering::Relaxed);
+        let mut parser =
+            PostgresRawSourceParser::new(iter, read_query.as_str(), &self.names, &self.schema);
+        parser.scales = self.scales.clone();
+        parser.bool_coercion = self.bool_coercion.clone();
+        parser.bytea_encoding = self.bytea_encoding;
+        parser.timezone = self.timezone;
+        parser.interval_month_days = self.interval_month_days;
+        parser.reading = Some(&self.reading);
+        parser.resume = self.resume();
+        parser.skip_rows = self.skip_rows.clone();
+        parser
+    }
+
+    #[throws(PostgresSourceError)]
+    fn count_rows(&mut self) {
+        if self.skip_rows.is_some() {
+            throw!(PostgresSourceError::SkipRowCounted(self.query.to_string()));
+        }
+        // counted in the transaction of the read, so at the same snapshot
+        self.start_read()?;
+        let nrows = get_total_rows(connected(&mut self.conn), &self.query)?;
+        self.nrows.set(Some(nrows));
+        self.check_in();
+    }
+
+    // count the rows for `nrows` in lazy mode, with a connection of the pool for the count alone
+    // if the partition has none
+    #[throws(PostgresSourceError)]
+    fn count_lazily(&self) -> usize {
+        let mut conn = self.conn.borrow_mut();
+        match conn.as_mut() {
+            Some(conn) => get_total_rows(conn, &self.query)?,
+            None => {
+                let pool = self
+                    .pool
+                    .as_ref()
+                    .expect("a partition without a connection has a pool");
+                get_total_rows(
+                    &mut wait_for_conn(pool, self.conn_wait_timeout)?,
+                    &self.query,
+                )?
+            }
+        }
+    }
+
+    fn get_nrows(&self) -> usize {
+        match self.nrows.get() {
+            Some(nrows) => nrows,
+            None if self.lazy_nrows => {
+                // `nrows` cannot fail, so the error waits for the read
+                let nrows = self.count_lazily().unwrap_or_else(|e| {
+                    warn!("could not count the rows of {}: {}", self.query, e);
+                    *self.count_error.borrow_mut() = Some(e);
+                    0
+                });
+                self.nrows.set(Some(nrows));
+                nrows
+            }
+            None => 0,
+        }
+    }
+}
+
+// the connection of a partition that started to read, which has checked it out if lazy
+fn connected<C>(conn: &mut RefCell<Option<PgConn<C>>>) -> &mut PgConn<C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    conn.get_mut()
+        .as_mut()
+        .expect("the connection is checked out by `start_read`")
+}
+
+impl<P, C> Drop for PostgresSourcePartition<P, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    fn drop(&mut self) {
+        self.abort_read();
+        self.end_transaction();
+    }
+}
+
+impl<C> SourcePartition for PostgresSourcePartition<BinaryProtocol, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type TypeSystem = PostgresTypeSystem;
+    type Parser<'a> = PostgresBinarySourcePartitionParser<'a>;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn result_rows(&mut self) -> () {
+        self.count_rows()?;
+    }
+
+    #[throws(PostgresSourceError)]
+    fn parser(&mut self) -> Self::Parser<'_> {
+        self.binary_parser()?
+    }
+
+    fn nrows(&self) -> usize {
+        self.get_nrows()
+    }
+
+    fn ncols(&self) -> usize {
+        self.ncols
+    }
+}
+
+impl<C> SourcePartition for PostgresSourcePartition<CSVProtocol, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type TypeSystem = PostgresTypeSystem;
+    type Parser<'a> = PostgresCSVSourceParser<'a>;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn result_rows(&mut self) {
+        self.count_rows()?;
+    }
+
+    #[throws(PostgresSourceError)]
+    fn parser(&mut self) -> Self::Parser<'_> {
+        self.start_read()?;
+        let mut read_query = self.read_query();
+        // money is printed with the currency symbol, separators and fraction digits of
+        // lc_monetary, while as numeric it has the scale of the currency and nothing else
+        if self.schema.iter().any(|ty| {
+            matches!(
+                ty,
+                PostgresTypeSystem::Money(_) | PostgresTypeSystem::MoneyArray(_)
+            )
+        }) {
+            let projection = self
+                .names
+                .iter()
+                .zip(self.schema.iter())
+                .map(|(name, ty)| match ty {
+                    PostgresTypeSystem::Money(_) => format!(
+                        "CXTMPTAB_MONEY.{}::numeric AS {}",
+                        quote_ident(name),
+                        quote_ident(name)
+                    ),
+                    PostgresTypeSystem::MoneyArray(_) => format!(
+                        "CXTMPTAB_MONEY.{}::numeric[] AS {}",
+                        quote_ident(name),
+                        quote_ident(name)
+                    ),
+                    _ => format!("CXTMPTAB_MONEY.{}", quote_ident(name)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            read_query = format!(
+                "SELECT {} FROM ({}) AS CXTMPTAB_MONEY",
+                projection, read_query
+            );
+        }
+        let mut options = vec!["FORMAT csv".to_string()];
+        if self.csv_header {
+            options.push("HEADER".to_string());
+        }
+        if let Some(null) = &self.csv_null {
+            options.push(format!("NULL '{}'", null.replace('\'', "''")));
+        }
+        let query = format!(
+            "COPY {} TO STDOUT WITH ({})",
+            self.copy_target(&read_query)?,
+            options.join(", ")
+        );
+        // COPY renders dates in the session's DateStyle, while the parsers below expect ISO. It is
+        // set for the transaction of the read only, the connection goes back to the pool with the
+        // settings of the user
+        let mut sql = vec![];
+        if !self.in_transaction {
+            sql.push("BEGIN");
+            self.in_transaction = true;
+        }
+        sql.push("SET LOCAL DateStyle = 'ISO, YMD'");
+        connected(&mut self.conn).batch_execute(&sql.join("; "))?;
+        let reader = connected(&mut self.conn).copy_out(&*query)?; // unless reading the data, it seems like issue the query is fast
+        self.reading.store(true, Ordering::Relaxed);
+        let mut reader = ReaderBuilder::new()
+            .has_headers(self.csv_header)
+            .from_reader(reader);
+
+        if self.csv_header {
+            let headers = reader.headers()?;
+            if !headers.iter().eq(self.names.iter().map(String::as_str)) {
+                throw!(PostgresSourceError::HeaderMismatch(
+                    headers.iter().map(ToString::to_string).collect(),
+                    self.names.clone(),
+                ));
+            }
+        }
+        let iter = reader.into_records();
+
+        let mut parser =
+            PostgresCSVSourceParser::new(iter, read_query.as_str(), &self.names, &self.schema);
+        parser.scales = self.scales.clone();
+        parser.bool_coercion = self.bool_coercion.clone();
+        parser.bytea_encoding = self.bytea_encoding;
+        parser.timezone = self.timezone;
+        parser.interval_month_days = self.interval_month_days;
+        parser.decode_errors = self.decode_errors.clone();
+        parser.decode_fallbacks = self.decode_fallbacks.clone();
+        parser.strict_dates = self.strict_dates;
+        if let Some(null) = &self.csv_null {
+            parser.null = null.clone();
+        }
+        parser.reading = Some(&self.reading);
+        parser.resume = self.resume();
+        parser.skip_rows = self.skip_rows.clone();
+        parser
+    }
+
+    fn nrows(&self) -> usize {
+        self.get_nrows()
+    }
+
+    fn ncols(&self) -> usize {
+        self.ncols
+    }
+}
+
+impl<C> SourcePartition for PostgresSourcePartition<CursorProtocol, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type TypeSystem = PostgresTypeSystem;
+    type Parser<'a> = PostgresRawSourceParser<'a>;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn result_rows(&mut self) {
+        self.count_rows()?;
+    }
+
+    #[throws(PostgresSourceError)]
+    fn parser(&mut self) -> Self::Parser<'_> {
+        self.cursor_parser()?
+    }
+
+    fn nrows(&self) -> usize {
+        self.get_nrows()
+    }
+
+    fn ncols(&self) -> usize {
+        self.ncols
+    }
+}
+
+impl<C> SourcePartition for PostgresSourcePartition<AutoProtocol, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type TypeSystem = PostgresTypeSystem;
+    type Parser<'a> = PostgresAutoSourceParser<'a>;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn result_rows(&mut self) {
+        self.count_rows()?;
+    }
+
+    #[throws(PostgresSourceError)]
+    fn parser(&mut self) -> Self::Parser<'_> {
+        let binary = self.schema.iter().all(|ty| BinaryProtocol::supports(*ty))
+            && !is_explain(self.query.as_str());
+        match binary {
+            true => PostgresAutoSourceParser::Binary(self.binary_parser()?),
+            false => {
+                debug!("reading {} with the cursor protocol", self.query);
+                PostgresAutoSourceParser::Cursor(self.cursor_parser()?)
+            }
+        }
+    }
+
+    fn nrows(&self) -> usize {
+        self.get_nrows()
+    }
+
+    fn ncols(&self) -> usize {
+        self.ncols
+    }
+}
+// the hstore columns of a binary read, read for the keys of each batch by `query` over a
+// connection of their own, see `PostgresSource::set_hybrid_key`
+struct HybridColumns {
+    conn: postgres::Client,
+    query: String,
+    // the column of the key, and those of the hstores
+    key: usize,
+    columns: Vec<usize>,
+    // the hstores of the rows of the batch by key, in the order of `columns`
+    values: HashMap<i64, Vec<Option<HashMap<String, Option<String>>>>>,
+}
+
+pub struct PostgresBinarySourcePartitionParser<'a> {
+    iter: BinaryCopyOutIter<'a>,
+    rowbuf: Vec<BinaryCopyOutRow>,
+    query: String,
+    names: Vec<String>,
+    schema: Vec<PostgresTypeSystem>,
+    scales: Vec<Option<u32>>,
+    bool_coercion: Option<BoolCoercion>,
+    bytea_encoding: ByteaEncoding,
+    timezone: Tz,
+    interval_month_days: Option<i64>,
+    ncols: usize,
+    nrows_read: usize,
+    current_col: usize,
+    current_row: usize,
+    // the flag of the partition to clear at the end of the result
+    reading: Option<&'a AtomicBool>,
+    // the column of the resume key and where the partition keeps the last one
+    resume: Option<(usize, &'a Cell<Option<i64>>)>,
+    // where to count the rows dropped under `DecodeErrorPolicy::SkipRow`, and how many this
+    // parser dropped
+    skip_rows: Option<DecodeErrorReport>,
+    skipped_rows: usize,
+    hybrid: Option<HybridColumns>,
+}
+
+impl<'a> PostgresBinarySourcePartitionParser<'a> {
+    pub fn new(
+        iter: BinaryCopyOutIter<'a>,
+        query: &str,
+        names: &[String],
+        schema: &[PostgresTypeSystem],
+    ) -> Self {
+        Self {
+            iter,
+            rowbuf: Vec::with_capacity(DB_BUFFER_SIZE),
+            query: query.to_string(),
+            names: names.to_vec(),
+            schema: schema.to_vec(),
+            scales: vec![None; schema.len()],
+            bool_coercion: None,
+            bytea_encoding: ByteaEncoding::Hex,
+            timezone: Tz::UTC,
+            interval_month_days: None,
+            ncols: schema.len(),
+            nrows_read: 0,
+            current_row: 0,
+            current_col: 0,
+            reading: None,
+            resume: None,
+            skip_rows: None,
+            skipped_rows: 0,
+            hybrid: None,
+        }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn next_loc(&mut self) -> (usize, usize) {
+        if self.ncols == 0 {
+            throw!(PostgresSourceError::NoColumns(self.query.clone()));
+        }
+        let ret = (self.current_row, self.current_col);
+        self.current_row += (self.current_col + 1) / self.ncols;
+        self.current_col = (self.current_col + 1) % self.ncols;
+        ret
+    }
+
+    // attach the partition query and the number of rows read so far to an error from the stream
+    fn stream_error<E: Into<PostgresSourceError>>(&self, e: E) -> PostgresSourceError {
+        PostgresSourceError::StreamError(
+            self.query.clone(),
+            self.nrows_read + self.rowbuf.len(),
+            Box::new(e.into()),
+        )
+    }
+
+    /// Read column `cidx` of all the buffered rows at once, use `Option<T>` for nullable columns.
+    /// This does not move the cursor used by `produce`, so do not mix the two on one batch.
+    #[throws(PostgresSourceError)]
+    pub fn produce_all<'r, T: FromSql<'r>>(&'r self, cidx: usize) -> Vec<T> {
+        self.rowbuf
+            .iter()
+            .map(|row| row.try_get(cidx))
+            .collect::<Result<Vec<T>, _>>()?
+    }
+}
+
+impl<'a> PartitionParser<'a> for PostgresBinarySourcePartitionParser<'a> {
+    type TypeSystem = PostgresTypeSystem;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn fetch_next(&mut self) -> (usize, bool) {
+        // the previous batch was consumed, so a resumed read can start past it
+        if let (Some((cidx, last_key)), Some(row)) = (self.resume, self.rowbuf.last()) {
+            last_key.set(Some(row.try_get::<PostgresResumeKey>(cidx)?.0));
+        }
+        if !self.rowbuf.is_empty() {
+            self.rowbuf.drain(..);
+        }
+        for _ in 0..DB_BUFFER_SIZE {
+            match self.iter.next().map_err(|e| self.stream_error(e))? {
+                Some(row) => {
+                    self.rowbuf.push(row);
+                }
+                None => {
+                    if let Some(reading) = self.reading {
+                        reading.store(false, Ordering::Relaxed);
+                    }
+                    break;
+                }
+            }
+        }
+        self.nrows_read += self.rowbuf.len();
+        let is_last = self.rowbuf.len() < DB_BUFFER_SIZE;
+        self.fetch_hybrid()?;
+        self.skip_undecodable_rows(is_last);
+        self.current_row = 0;
+        self.current_col = 0;
+        (self.rowbuf.len(), is_last)
+    }
+}
+
+macro_rules! impl_produce {
+    ($($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for PostgresBinarySourcePartitionParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let row = &self.rowbuf[ridx];
+                    let val = row.try_get(cidx)?;
+                    val
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for PostgresBinarySourcePartitionParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let row = &self.rowbuf[ridx];
+                    let val = row.try_get(cidx)?;
+                    val
+                }
+            }
+        )+
+    };
+}
+
+impl_produce!(
+    Vec<i16>,
+    Vec<i32>,
+    Vec<i64>,
+    Vec<u32>,
+    Vec<String>,
+    Vec<Uuid>,
+    // borrowed from the row buffer, so a large bytea can be streamed out through its `Read`
+    // impl without another copy
+    &'r [u8],
+    NaiveTime,
+    NaiveDateTime,
+    NaiveDate,
+    Uuid,
+    PostgresInterval,
+    Vec<NaiveTime>,
+    Vec<PostgresInterval>,
+    PgSparseVec,
+);
+
+#[cfg(feature = "time")]
+impl_produce!(
+    time::OffsetDateTime,
+    time::PrimitiveDateTime,
+    time::Date,
+    time::Time,
+);
+
+impl PostgresNumeric {
+    #[throws(PostgresSourceError)]
+    fn into_decimal(self, name: &str) -> Decimal {
+        match self {
+            PostgresNumeric::Decimal(v) => v,
+            PostgresNumeric::NaN => throw!(PostgresSourceError::NumericNaN(name.to_string())),
+        }
+    }
+}
+
+// `v` as an unscaled integer at the scale the column declares, e.g. 12.5 in a numeric(18,4) is
+// (125000, 4)
+#[throws(PostgresSourceError)]
+fn into_scaled(mut v: Decimal, name: &str, scale: Option<u32>) -> (i64, u32) {
+    let scale = scale.ok_or_else(|| PostgresSourceError::UndeclaredScale(name.to_string()))?;
+    v.rescale(scale);
+    match (v.scale() == scale, i64::try_from(v.mantissa())) {
+        (true, Ok(mantissa)) => (mantissa, scale),
+        _ => throw!(PostgresSourceError::ScaledOverflow(
+            name.to_string(),
+            v.to_string()
+        )),
+    }
+}
+
+macro_rules! impl_numeric_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'a> $p<'a> {
+                // money is decoded at 2 fraction digits, see `PostgresNumeric`, and takes the
+                // scale of the currency of the source from `scales` here
+                #[throws(PostgresSourceError)]
+                fn decimal(&self, cidx: usize, v: PostgresNumeric) -> Decimal {
+                    let mut v = v.into_decimal(&self.names[cidx])?;
+                    if let (
+                        PostgresTypeSystem::Money(_) | PostgresTypeSystem::MoneyArray(_),
+                        Some(scale),
+                    ) = (self.schema[cidx], self.scales[cidx])
+                    {
+                        v.set_scale(scale).map_err(|e| anyhow!("{}", e))?;
+                    }
+                    v
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Decimal> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Decimal {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresNumeric = self.rowbuf[ridx].try_get(cidx)?;
+                    self.decimal(cidx, val)?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Decimal>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Decimal> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresNumeric> = self.rowbuf[ridx].try_get(cidx)?;
+                    match val {
+                        Some(v) => Some(self.decimal(cidx, v)?),
+                        None => None,
+                    }
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Vec<Decimal>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<Decimal> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Vec<PostgresNumeric> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.into_iter()
+                        .map(|v| self.decimal(cidx, v))
+                        .collect::<Result<Vec<_>, _>>()?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<Decimal>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<Decimal>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<Vec<PostgresNumeric>> = self.rowbuf[ridx].try_get(cidx)?;
+                    match val {
+                        Some(val) => Some(
+                            val.into_iter()
+                                .map(|v| self.decimal(cidx, v))
+                                .collect::<Result<Vec<_>, _>>()?,
+                        ),
+                        None => None,
+                    }
+                }
+            }
+
+            // the exact text of a numeric, for a precision or scale beyond `Decimal`, or a bytea
+            // in its `ByteaEncoding`
+            impl<'r, 'a> Produce<'r, String> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> String {
+                    let (ridx, cidx) = self.next_loc()?;
+                    match self.rowbuf[ridx].try_get(cidx)? {
+                        PostgresStringLike::Text(v) => v,
+                        PostgresStringLike::Bytes(v) => self.bytea_encoding.encode(&v),
+                    }
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<String>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<String> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    match self.rowbuf[ridx].try_get(cidx)? {
+                        Some(PostgresStringLike::Text(v)) => Some(v),
+                        Some(PostgresStringLike::Bytes(v)) => Some(self.bytea_encoding.encode(&v)),
+                        None => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_numeric_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a numeric as its unscaled value and the scale of the column, see `into_scaled`
+macro_rules! impl_scaled_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, (i64, u32)> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> (i64, u32) {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresNumeric = self.rowbuf[ridx].try_get(cidx)?;
+                    let name = &self.names[cidx];
+                    into_scaled(self.decimal(cidx, val)?, name, self.scales[cidx])?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<(i64, u32)>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<(i64, u32)> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresNumeric> = self.rowbuf[ridx].try_get(cidx)?;
+                    let name = &self.names[cidx];
+                    match val {
+                        Some(v) => Some(into_scaled(self.decimal(cidx, v)?, name, self.scales[cidx])?),
+                        None => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_scaled_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a bytea, or a uuid as its raw bytes. `[u8; 16]` and `u128` are only for uuid. A uuid[] is
+// read either as the bytes of its elements one after the other (16 per element) or as a
+// `Vec<[u8; 16]>`, for a packed export of uuid keys.
+macro_rules! impl_bytes_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<u8>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<u8> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresBytes = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<u8>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<u8>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresBytes> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+
+            // the bytes of a `Vec<u8>` without its spare capacity, for a fixed-size buffer
+            impl<'r, 'a> Produce<'r, Box<[u8]>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Box<[u8]> {
+                    Produce::<Vec<u8>>::produce(self)?.into_boxed_slice()
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Box<[u8]>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Box<[u8]>> {
+                    Produce::<Option<Vec<u8>>>::produce(self)?.map(Vec::into_boxed_slice)
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, [u8; 16]> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> [u8; 16] {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Uuid = self.rowbuf[ridx].try_get(cidx)?;
+                    *val.as_bytes()
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<[u8; 16]>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<[u8; 16]> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<Uuid> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| *v.as_bytes())
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Vec<[u8; 16]>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<[u8; 16]> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Vec<Uuid> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.iter().map(|v| *v.as_bytes()).collect()
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<[u8; 16]>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<[u8; 16]>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<Vec<Uuid>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.iter().map(|v| *v.as_bytes()).collect())
+                }
+            }
+
+            // the 16 bytes of a uuid as a big-endian integer
+            impl<'r, 'a> Produce<'r, u128> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> u128 {
+                    u128::from_be_bytes(Produce::<[u8; 16]>::produce(self)?)
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<u128>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<u128> {
+                    Produce::<Option<[u8; 16]>>::produce(self)?.map(u128::from_be_bytes)
+                }
+            }
+        )+
+    };
+}
+
+impl_bytes_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// an int8, a timestamp or timestamptz as microseconds since the unix epoch, or an interval as
+// microseconds
+macro_rules! impl_int8_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, i64> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> i64 {
+                    let (ridx, cidx) = self.next_loc()?;
+                    match self.schema[cidx] {
+                        PostgresTypeSystem::Interval(_) => {
+                            let val: PostgresInterval = self.rowbuf[ridx].try_get(cidx)?;
+                            interval_micros(&self.names[cidx], &val, self.interval_month_days)?
+                        }
+                        _ => {
+                            let val: PostgresInt8 = self.rowbuf[ridx].try_get(cidx)?;
+                            val.0
+                        }
+                    }
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<i64>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<i64> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    match self.schema[cidx] {
+                        PostgresTypeSystem::Interval(_) => {
+                            let val: Option<PostgresInterval> = self.rowbuf[ridx].try_get(cidx)?;
+                            match val {
+                                Some(val) => Some(interval_micros(
+                                    &self.names[cidx],
+                                    &val,
+                                    self.interval_month_days,
+                                )?),
+                                None => None,
+                            }
+                        }
+                        _ => {
+                            let val: Option<PostgresInt8> = self.rowbuf[ridx].try_get(cidx)?;
+                            val.map(|v| v.0)
+                        }
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_int8_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// the small integers also read bool columns, as 1 and 0
+macro_rules! impl_integer_produce {
+    ($p: ident => $($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresInteger<$t> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresInteger<$t>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_integer_produce!(PostgresBinarySourcePartitionParser => i8, i16,);
+impl_integer_produce!(PostgresRawSourceParser => i8, i16,);
+
+// an int4 (also reading bool columns), or a date as days since the unix epoch
+macro_rules! impl_int4_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, i32> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> i32 {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresInt4 = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<i32>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<i32> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresInt4> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_int4_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// with a `BoolCoercion`, a bool also reads integer and text columns
+macro_rules! impl_bool_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'a> $p<'a> {
+                #[throws(PostgresSourceError)]
+                fn coerce_bool(&self, cidx: usize, val: PostgresBoolLike) -> bool {
+                    match (val, &self.bool_coercion) {
+                        (PostgresBoolLike::Bool(v), _) => v,
+                        (PostgresBoolLike::Int(v), Some(coercion)) => {
+                            coercion.coerce(&self.names[cidx], &v.to_string())?
+                        }
+                        (PostgresBoolLike::Text(v), Some(coercion)) => {
+                            coercion.coerce(&self.names[cidx], v)?
+                        }
+                        (_, None) => throw!(ConnectorXError::cannot_produce_column::<bool>(
+                            &self.names[cidx],
+                            None
+                        )),
+                    }
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, bool> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> bool {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresBoolLike = self.rowbuf[ridx].try_get(cidx)?;
+                    self.coerce_bool(cidx, val)?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<bool>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<bool> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresBoolLike> = self.rowbuf[ridx].try_get(cidx)?;
+                    match val {
+                        Some(v) => Some(self.coerce_bool(cidx, v)?),
+                        None => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_bool_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a bool array, or the bits of a bit string
+macro_rules! impl_bits_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<bool>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<bool> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresBits = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<bool>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<bool>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresBits> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_bits_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// `val` as a `T`, failing if its bit string is wider than `T`
+#[throws(PostgresSourceError)]
+fn narrow_unsigned<T: TryFrom<u64>>(val: PostgresUnsigned, name: &str) -> T {
+    let width = 8 * std::mem::size_of::<T>();
+    match (val.width <= width, T::try_from(val.value)) {
+        (true, Ok(v)) => v,
+        _ => throw!(PostgresSourceError::BitStringTooWide(
+            name.to_string(),
+            val.width,
+            width
+        )),
+    }
+}
+
+// an oid, or a bit string packed into an integer as wide, e.g. a bitmask
+macro_rules! impl_unsigned_produce {
+    ($p: ident => $($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresUnsigned = self.rowbuf[ridx].try_get(cidx)?;
+                    narrow_unsigned(val, &self.names[cidx])?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresUnsigned> = self.rowbuf[ridx].try_get(cidx)?;
+                    match val {
+                        Some(val) => Some(narrow_unsigned(val, &self.names[cidx])?),
+                        None => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_unsigned_produce!(PostgresBinarySourcePartitionParser => u32, u64,);
+impl_unsigned_produce!(PostgresRawSourceParser => u32, u64,);
+
+// a timestamptz, or a timestamp taken as UTC
+macro_rules! impl_timestamptz_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, DateTime<Utc>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> DateTime<Utc> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresTimestampTz = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<DateTime<Utc>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<DateTime<Utc>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresTimestampTz> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_timestamptz_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a timestamptz in the time zone of `PostgresSource::set_output_timezone`
+macro_rules! impl_timestamptz_local_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, DateTime<Tz>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> DateTime<Tz> {
+                    let timezone = self.timezone;
+                    Produce::<DateTime<Utc>>::produce(self)?.with_timezone(&timezone)
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<DateTime<Tz>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<DateTime<Tz>> {
+                    let timezone = self.timezone;
+                    Produce::<Option<DateTime<Utc>>>::produce(self)?
+                        .map(|v| v.with_timezone(&timezone))
+                }
+            }
+        )+
+    };
+}
+
+impl_timestamptz_local_produce!(
+    PostgresBinarySourcePartitionParser,
+    PostgresCSVSourceParser,
+    PostgresRawSourceParser,
+);
+
+// the float arrays also read pgvector columns
+macro_rules! impl_vector_produce {
+    ($p: ident => $($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<$t>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<$t> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresVector<$t> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<$t>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<$t>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresVector<$t>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_vector_produce!(PostgresBinarySourcePartitionParser => f32, f64,);
+impl_vector_produce!(PostgresRawSourceParser => f32, f64,);
+
+// the (index, value) pairs of a sparsevec, without its dimension
+macro_rules! impl_sparsevec_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<(i32, f32)>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<(i32, f32)> {
+                    Produce::<PgSparseVec>::produce(self)?.elements
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<(i32, f32)>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<(i32, f32)>> {
+                    Produce::<Option<PgSparseVec>>::produce(self)?.map(|v| v.elements)
+                }
+            }
+        )+
+    };
+}
+
+impl_sparsevec_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// the floats also read numeric columns, rounded to the nearest float
+macro_rules! impl_float_produce {
+    ($p: ident => $($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresFloat<$t> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresFloat<$t>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_float_produce!(PostgresBinarySourcePartitionParser => f32, f64,);
+impl_float_produce!(PostgresRawSourceParser => f32, f64,);
+
+// a text, or an xml as its text
+macro_rules! impl_text_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, &'r str> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> &'r str {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresText = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<&'r str>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<&'r str> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresText> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_text_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a json or jsonb, an hstore as a json object, or an xml as a json string
+macro_rules! impl_json_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Value> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Value {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresJson = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Value>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Value> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresJson> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+impl_json_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a record[] column, which `fetch_metadata` reads as json
+macro_rules! impl_record_array_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<Value>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<Value> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresJson = self.rowbuf[ridx].try_get(cidx)?;
+                    json_array(&self.names[cidx], val.0)?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<Value>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<Value>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresJson> = self.rowbuf[ridx].try_get(cidx)?;
+                    match val {
+                        Some(v) => Some(json_array(&self.names[cidx], v.0)?),
+                        None => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_record_array_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+#[throws(ConnectorXError)]
+fn json_array(name: &str, v: Value) -> Vec<Value> {
+    match v {
+        Value::Array(v) => v,
+        v => throw!(ConnectorXError::cannot_produce_column::<Vec<Value>>(
+            name,
+            Some(v.to_string())
+        )),
+    }
+}
+
+// the ranges of a multirange
+macro_rules! impl_multirange_produce {
+    ($p: ident => $($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<PgRange<$t>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<PgRange<$t>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresMultiRange<$t> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<PgRange<$t>>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<PgRange<$t>>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresMultiRange<$t>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+// a time as the duration since midnight, or an interval without months as a duration, and
+// arrays of them
+macro_rules! impl_duration_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, chrono::Duration> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> chrono::Duration {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresDuration = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<chrono::Duration>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<chrono::Duration> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresDuration> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Vec<chrono::Duration>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<chrono::Duration> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Vec<PostgresDuration> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.into_iter().map(|v| v.0).collect()
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<chrono::Duration>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<chrono::Duration>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<Vec<PostgresDuration>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.into_iter().map(|v| v.0).collect())
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Vec<Option<chrono::Duration>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<Option<chrono::Duration>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Vec<Option<PostgresDuration>> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.into_iter().map(|v| v.map(|v| v.0)).collect()
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<Option<chrono::Duration>>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<Option<chrono::Duration>>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<Vec<Option<PostgresDuration>>> =
+                        self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.into_iter().map(|v| v.map(|v| v.0)).collect())
+                }
+            }
+        )+
+    };
+}
+
+impl_duration_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// an interval as its months, days and microseconds, the three fields Postgres stores it as
+macro_rules! impl_interval_parts_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, (i32, i32, i64)> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> (i32, i32, i64) {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: PostgresInterval = self.rowbuf[ridx].try_get(cidx)?;
+                    (val.months, val.days, val.microseconds)
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<(i32, i32, i64)>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<(i32, i32, i64)> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<PostgresInterval> = self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| (v.months, v.days, v.microseconds))
+                }
+            }
+        )+
+    };
+}
+
+impl_interval_parts_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+// a PostGIS geometry or geography decoded from its EWKB. `Produce<String>` gives its hex instead.
+#[cfg(feature = "postgis")]
+macro_rules! impl_geometry_produce {
+    ($($p: ident,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, geo_types::Geometry<f64>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> geo_types::Geometry<f64> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: postgis::PostgresGeometry = self.rowbuf[ridx].try_get(cidx)?;
+                    val.0
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<geo_types::Geometry<f64>>> for $p<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<geo_types::Geometry<f64>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let val: Option<postgis::PostgresGeometry> =
+                        self.rowbuf[ridx].try_get(cidx)?;
+                    val.map(|v| v.0)
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "postgis")]
+impl_geometry_produce!(PostgresBinarySourcePartitionParser, PostgresRawSourceParser,);
+
+impl_multirange_produce!(
+    PostgresBinarySourcePartitionParser => i32, i64, NaiveDate, NaiveDateTime, DateTime<Utc>,
+);
+impl_multirange_produce!(
+    PostgresRawSourceParser => i32, i64, NaiveDate, NaiveDateTime, DateTime<Utc>,
+);
+
+impl<'a> PostgresBinarySourcePartitionParser<'a> {
+    // read the hstore columns of the buffered rows, see `HybridColumns`
+    #[throws(PostgresSourceError)]
+    fn fetch_hybrid(&mut self) {
+        let hybrid = match &mut self.hybrid {
+            Some(hybrid) => hybrid,
+            None => return,
+        };
+        hybrid.values.clear();
+        let keys = self
+            .rowbuf
+            .iter()
+            .map(|row| Ok(row.try_get::<PostgresResumeKey>(hybrid.key)?.0))
+            .collect::<Result<Vec<i64>, PostgresSourceError>>()?;
+        if keys.is_empty() {
+            return;
+        }
+        for row in hybrid.conn.query(hybrid.query.as_str(), &[&keys])? {
+            let key: PostgresResumeKey = row.try_get(0)?;
+            let values = (1..row.len())
+                .map(|i| row.try_get(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            hybrid.values.insert(key.0, values);
+        }
+    }
+
+    // the hstore at `cidx` of the buffered row `ridx`, from the second query of `HybridColumns`
+    #[throws(PostgresSourceError)]
+    fn hybrid_value(&self, ridx: usize, cidx: usize) -> Option<HashMap<String, Option<String>>> {
+        let hybrid = match &self.hybrid {
+            Some(hybrid) => hybrid,
+            None => throw!(anyhow!(
+                "column {:?} can only be read as an hstore with PostgresSource::set_hybrid_key",
+                self.names[cidx]
+            )),
+        };
+        let key = self.rowbuf[ridx]
+            .try_get::<PostgresResumeKey>(hybrid.key)?
+            .0;
+        let values = hybrid.values.get(&key).ok_or_else(|| {
+            anyhow!(
+                "the row of key {} of {} is missing from the query of its hstore columns, the key is not stable",
+                key,
+                self.query
+            )
+        })?;
+        match hybrid.columns.iter().position(|&c| c == cidx) {
+            Some(i) => values[i].clone(),
+            None => throw!(anyhow!("column {:?} is not an hstore", self.names[cidx])),
+        }
+    }
+}
+
+// an hstore is not sent by the COPY, see `PostgresSource::set_hybrid_key`
+impl<'r, 'a> Produce<'r, HashMap<String, Option<String>>>
+    for PostgresBinarySourcePartitionParser<'a>
+{
+    type Error = PostgresSourceError;
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> HashMap<String, Option<String>> {
+        let (ridx, cidx) = self.next_loc()?;
+        self.hybrid_value(ridx, cidx)?.ok_or_else(|| {
+            ConnectorXError::cannot_produce_column::<HashMap<String, Option<String>>>(
+                &self.names[cidx],
+                None,
+            )
+        })?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<HashMap<String, Option<String>>>>
+    for PostgresBinarySourcePartitionParser<'a>
+{
+    type Error = PostgresSourceError;
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<HashMap<String, Option<String>>> {
+        let (ridx, cidx) = self.next_loc()?;
+        self.hybrid_value(ridx, cidx)?
+    }
+}
+
+// "char" is a single byte; it is read as i8 by the postgres crate and reinterpreted here
+impl<'r, 'a> Produce<'r, u8> for PostgresBinarySourcePartitionParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> u8 {
+        let (ridx, cidx) = self.next_loc()?;
+        let row = &self.rowbuf[ridx];
+        let val: i8 = row.try_get(cidx)?;
+        val as u8
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<u8>> for PostgresBinarySourcePartitionParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<u8> {
+        let (ridx, cidx) = self.next_loc()?;
+        let row = &self.rowbuf[ridx];
+        let val: Option<i8> = row.try_get(cidx)?;
+        val.map(|v| v as u8)
+    }
+}
+
+pub struct PostgresCSVSourceParser<'a> {
+    iter: StringRecordsIntoIter<CopyOutReader<'a>>,
+    rowbuf: Vec<StringRecord>,
+    query: String,
+    names: Vec<String>,
+    schema: Vec<PostgresTypeSystem>,
+    scales: Vec<Option<u32>>,
+    bool_coercion: Option<BoolCoercion>,
+    bytea_encoding: ByteaEncoding,
+    timezone: Tz,
+    interval_month_days: Option<i64>,
+    decode_errors: Option<DecodeErrorReport>,
+    decode_fallbacks: Option<DecodeFallbacks>,
+    strict_dates: bool,
+    // how COPY printed NULL
+    null: String,
+    ncols: usize,
+    nrows_read: usize,
+    current_col: usize,
+    current_row: usize,
+    // the flag of the partition to clear at the end of the result
+    reading: Option<&'a AtomicBool>,
+    // the column of the resume key and where the partition keeps the last one
+    resume: Option<(usize, &'a Cell<Option<i64>>)>,
+    // where to count the rows dropped under `DecodeErrorPolicy::SkipRow`, and how many this
+    // parser dropped
+    skip_rows: Option<DecodeErrorReport>,
+    skipped_rows: usize,
+}
+
+impl<'a> PostgresCSVSourceParser<'a> {
+    pub fn new(
+        iter: StringRecordsIntoIter<CopyOutReader<'a>>,
+        query: &str,
+        names: &[String],
+        schema: &[PostgresTypeSystem],
+    ) -> Self {
+        Self {
+            iter,
+            rowbuf: Vec::with_capacity(DB_BUFFER_SIZE),
+            query: query.to_string(),
+            names: names.to_vec(),
+            schema: schema.to_vec(),
+            scales: vec![None; schema.len()],
+            bool_coercion: None,
+            bytea_encoding: ByteaEncoding::Hex,
+            timezone: Tz::UTC,
+            interval_month_days: None,
+            decode_errors: None,
+            decode_fallbacks: None,
+            strict_dates: false,
+            null: String::new(),
+            ncols: schema.len(),
+            nrows_read: 0,
+            current_row: 0,
+            current_col: 0,
+            reading: None,
+            resume: None,
+            skip_rows: None,
+            skipped_rows: 0,
+        }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn next_loc(&mut self) -> (usize, usize) {
+        if self.ncols == 0 {
+            throw!(PostgresSourceError::NoColumns(self.query.clone()));
+        }
+        let ret = (self.current_row, self.current_col);
+        self.current_row += (self.current_col + 1) / self.ncols;
+        self.current_col = (self.current_col + 1) % self.ncols;
+        ret
+    }
+
+    // produce a nullable value through the produce of its non-null type. An empty field is read
+    // as NULL. A value that cannot be parsed is read as the fallback of its type if there is
+    // one, or else as NULL if there is a report to count it in.
+    #[throws(PostgresSourceError)]
+    fn produce_nullable<T>(&mut self) -> Option<T>
+    where
+        for<'r> Self: Produce<'r, T, Error = PostgresSourceError>,
+    {
+        let (ridx, cidx) = (self.current_row, self.current_col);
+        if self.ncols > 0 && self.rowbuf[ridx][cidx] == self.null {
+            self.next_loc()?;
+            return None;
+        }
+        let e = match Produce::<T>::produce(self) {
+            Ok(v) => return Some(v),
+            Err(e) if e.is_decode_error() => e,
+            Err(e) => throw!(e),
+        };
+        let fallback = self
+            .decode_fallbacks
+            .as_ref()
+            .and_then(|f| Some((f.get::<T>()?.clone(), f.report.clone())));
+        if let Some((value, report)) = fallback {
+            // parse the fallback in place of the value, from the same cell
+            let mut row = StringRecord::new();
+            for (i, v) in self.rowbuf[ridx].iter().enumerate() {
+                row.push_field(if i == cidx { &value } else { v });
+            }
+            self.rowbuf[ridx] = row;
+            self.current_row = ridx;
+            self.current_col = cidx;
+            match Produce::<T>::produce(self) {
+                Ok(v) => {
+                    report.record_fallback(&self.names[cidx]);
+                    return Some(v);
+                }
+                Err(e) if !e.is_decode_error() => throw!(e),
+                Err(_) => {}
+            }
+        }
+        match &self.decode_errors {
+            Some(report) => {
+                report.record(&self.names[cidx]);
+                None
+            }
+            None => throw!(e),
+        }
+    }
+
+    // the error for a value of column `cidx` that cannot be parsed as a `T`
+    fn cannot_produce<T>(&self, cidx: usize, v: &str) -> ConnectorXError {
+        ConnectorXError::cannot_produce_column::<T>(&self.names[cidx], Some(v.into()))
+    }
+
+    // attach the partition query and the number of rows read so far to an error from the stream
+    fn stream_error<E: Into<PostgresSourceError>>(&self, e: E) -> PostgresSourceError {
+        PostgresSourceError::StreamError(
+            self.query.clone(),
+            self.nrows_read + self.rowbuf.len(),
+            Box::new(e.into()),
+        )
+    }
+
+    /// Read column `cidx` of all the buffered rows at once, an empty field (or the sentinel of
+    /// `set_csv_null`) is read as NULL.
+    /// This does not move the cursor used by `produce`, so do not mix the two on one batch.
+    #[throws(PostgresSourceError)]
+    pub fn produce_all<T: FromStr>(&self, cidx: usize) -> Vec<Option<T>> {
+        self.rowbuf
+            .iter()
+            .map(|row| match &row[cidx] {
+                v if v == self.null => Ok(None),
+                v => v
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| self.cannot_produce::<T>(cidx, v)),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    }
+
+    fn parse_integer<T: FromStr + From<bool>>(&self, cidx: usize, v: &str) -> Option<T> {
+        match (self.schema[cidx], v) {
+            (PostgresTypeSystem::Bool(_), "t") => Some(T::from(true)),
+            (PostgresTypeSystem::Bool(_), "f") => Some(T::from(false)),
+            _ => v.parse().ok(),
+        }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn parse_bool(&self, cidx: usize, v: &str) -> bool {
+        match (self.schema[cidx], v, &self.bool_coercion) {
+            (PostgresTypeSystem::Bool(_), "t", _) => true,
+            (PostgresTypeSystem::Bool(_), "f", _) => false,
+            (PostgresTypeSystem::Bit(_), "1", _) => true,
+            (PostgresTypeSystem::Bit(_), "0", _) => false,
+            // coerce the same integer and text columns the binary protocol does
+            (
+                PostgresTypeSystem::Int2(_)
+                | PostgresTypeSystem::Int4(_)
+                | PostgresTypeSystem::Int8(_)
+                | PostgresTypeSystem::Text(_)
+                | PostgresTypeSystem::BpChar(_)
+                | PostgresTypeSystem::VarChar(_)
+                | PostgresTypeSystem::Name(_),
+                _,
+                Some(coercion),
+            ) => coercion.coerce(&self.names[cidx], v)?,
+            _ => throw!(self.cannot_produce::<bool>(cidx, v)),
+        }
+    }
+
+    // a timestamp is truncated to its date unless dates are strict
+    fn parse_naive_date(&self, v: &str) -> Option<NaiveDate> {
+        match NaiveDate::parse_from_str(v, "%Y-%m-%d") {
+            Ok(date) => Some(date),
+            Err(_) if !self.strict_dates => parse_naive_datetime(v).map(|dt| dt.date()),
+            Err(_) => None,
+        }
+    }
+
+    // a time as the duration since midnight, or an interval without months as a duration
+    fn parse_duration(&self, cidx: usize, v: &str) -> Option<chrono::Duration> {
+        match self.schema[cidx] {
+            PostgresTypeSystem::Time(_) | PostgresTypeSystem::TimeArray(_) => {
+                Some(parse_time_of_day(v)? - NaiveTime::from_hms(0, 0, 0))
+            }
+            _ => parse_interval(v)?.to_duration(),
+        }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn parse_decimal(&self, cidx: usize, v: &str) -> Decimal {
+        let ret = match self.schema[cidx] {
+            _ if v == "NaN" => throw!(PostgresSourceError::NumericNaN(self.names[cidx].clone())),
+            _ => Decimal::from_str(v)
+                .or_else(|_| Decimal::from_scientific(v))
+                .ok(),
+        };
+        ret.ok_or_else(|| self.cannot_produce::<Decimal>(cidx, v))?
+    }
+}
+
+impl<'a> PartitionParser<'a> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+    type TypeSystem = PostgresTypeSystem;
+
+    #[throws(PostgresSourceError)]
+    fn fetch_next(&mut self) -> (usize, bool) {
+        // the previous batch was consumed, so a resumed read can start past it
+        if let (Some((cidx, last_key)), Some(row)) = (self.resume, self.rowbuf.last()) {
+            let key = row[cidx]
+                .parse::<i64>()
+                .map_err(|_| self.cannot_produce::<i64>(cidx, &row[cidx]))?;
+            last_key.set(Some(key));
+        }
+        if !self.rowbuf.is_empty() {
+            self.rowbuf.drain(..);
+        }
+        for _ in 0..DB_BUFFER_SIZE {
+            if let Some(row) = self.iter.next() {
+                let row = row.map_err(|e| self.stream_error(e))?;
+                self.rowbuf.push(row);
+            } else {
+                if let Some(reading) = self.reading {
+                    reading.store(false, Ordering::Relaxed);
+                }
+                break;
+            }
+        }
+        self.nrows_read += self.rowbuf.len();
+        let is_last = self.rowbuf.len() < DB_BUFFER_SIZE;
+        // the fallbacks still apply to the rows checked for `DecodeErrorPolicy::SkipRow`, but
+        // are counted when the rows are produced for real only
+        let fallbacks_report = match (&self.skip_rows, &mut self.decode_fallbacks) {
+            (Some(_), Some(fallbacks)) => Some(std::mem::take(&mut fallbacks.report)),
+            _ => None,
+        };
+        self.skip_undecodable_rows(is_last);
+        if let (Some(fallbacks), Some(report)) = (&mut self.decode_fallbacks, fallbacks_report) {
+            fallbacks.report = report;
+        }
+        self.current_row = 0;
+        self.current_col = 0;
+        (self.rowbuf.len(), is_last)
+    }
+}
+
+macro_rules! impl_csv_produce {
+    ($($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let (ridx, cidx) = self.next_loc()?;
+                    self.rowbuf[ridx][cidx].parse().map_err(|_| {
+                        self.cannot_produce::<$t>(cidx, &self.rowbuf[ridx][cidx])
+                    })?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    self.produce_nullable::<$t>()?
+                }
+            }
+        )+
+    };
+}
+
+// a numeric is parsed from its text like a float, so it is rounded to the nearest float too
+impl_csv_produce!(f32, f64, Uuid,);
+
+// an oid, or a bit string packed into an integer as wide, see `PostgresUnsigned`
+macro_rules! impl_csv_unsigned_produce {
+    ($($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let cidx = self.current_col;
+                    match self.schema.get(cidx) {
+                        Some(PostgresTypeSystem::Bit(_)) => {
+                            let bits = Produce::<Vec<bool>>::produce(self)?;
+                            narrow_unsigned(PostgresUnsigned::from_bits(&bits), &self.names[cidx])?
+                        }
+                        _ => {
+                            let (ridx, cidx) = self.next_loc()?;
+                            self.rowbuf[ridx][cidx].parse().map_err(|_| {
+                                self.cannot_produce::<$t>(cidx, &self.rowbuf[ridx][cidx])
+                            })?
+                        }
+                    }
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    self.produce_nullable::<$t>()?
+                }
+            }
+        )+
+    };
+}
+
+impl_csv_unsigned_produce!(u32, u64,);
+
+fn epoch_micros(v: NaiveDateTime) -> i64 {
+    v.timestamp() * 1_000_000 + v.timestamp_subsec_micros() as i64
+}
+
+// an int8, a timestamp or timestamptz as microseconds since the unix epoch, or an interval as
+// microseconds
+impl<'r, 'a> Produce<'r, i64> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> i64 {
+        match self.schema.get(self.current_col) {
+            Some(PostgresTypeSystem::Timestamp(_)) => {
+                epoch_micros(Produce::<NaiveDateTime>::produce(self)?)
+            }
+            Some(PostgresTypeSystem::TimestampTz(_)) => {
+                epoch_micros(Produce::<DateTime<Utc>>::produce(self)?.naive_utc())
+            }
+            Some(PostgresTypeSystem::Interval(_)) => {
+                let cidx = self.current_col;
+                let val = Produce::<PostgresInterval>::produce(self)?;
+                interval_micros(&self.names[cidx], &val, self.interval_month_days)?
+            }
+            _ => {
+                let (ridx, cidx) = self.next_loc()?;
+                let v = &self.rowbuf[ridx][cidx];
+                v.parse().map_err(|_| self.cannot_produce::<i64>(cidx, v))?
+            }
+        }
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<i64>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<i64> {
+        self.produce_nullable::<i64>()?
+    }
+}
+
+// the small integers also read bool columns, as 1 and 0
+macro_rules! impl_csv_integer_produce {
+    ($($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, $t> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> $t {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let v = &self.rowbuf[ridx][cidx];
+                    self.parse_integer(cidx, v)
+                        .ok_or_else(|| self.cannot_produce::<$t>(cidx, v))?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<$t>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<$t> {
+                    self.produce_nullable::<$t>()?
+                }
+            }
+        )+
+    };
+}
+
+impl_csv_integer_produce!(i8, i16,);
+
+// an int4 (also reading bool columns), or a date as days since the unix epoch
+impl<'r, 'a> Produce<'r, i32> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> i32 {
+        match self.schema.get(self.current_col) {
+            Some(PostgresTypeSystem::Date(_)) => {
+                let v = Produce::<NaiveDate>::produce(self)?;
+                (v - NaiveDate::from_ymd(1970, 1, 1)).num_days() as i32
+            }
+            _ => {
+                let (ridx, cidx) = self.next_loc()?;
+                let v = &self.rowbuf[ridx][cidx];
+                self.parse_integer(cidx, v)
+                    .ok_or_else(|| self.cannot_produce::<i32>(cidx, v))?
+            }
+        }
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<i32>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<i32> {
+        self.produce_nullable::<i32>()?
+    }
+}
+
+macro_rules! impl_csv_vec_produce {
+    ($($t: ty,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<$t>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&mut self) -> Vec<$t> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let s = &self.rowbuf[ridx][cidx][..];
+                    split_vector(s)
+                        .ok_or_else(|| self.cannot_produce::<$t>(cidx, s))?
+                        .into_iter()
+                        .map(|v| {
+                            v.parse()
+                                .map_err(|_| self.cannot_produce::<$t>(cidx, s))
+                        })
+                        .collect::<Result<Vec<$t>, ConnectorXError>>()?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<$t>>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&mut self) -> Option<Vec<$t>> {
+                    self.produce_nullable::<Vec<$t>>()?
+                }
+            }
+        )+
+    };
+}
+
+impl_csv_vec_produce!(i8, i16, i32, i64, u32, f32, f64, Uuid,);
+
+impl<'r, 'a> Produce<'r, PgSparseVec> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> PgSparseVec {
+        let (ridx, cidx) = self.next_loc()?;
+        let s = &self.rowbuf[ridx][cidx];
+        parse_sparsevec(s).ok_or_else(|| self.cannot_produce::<PgSparseVec>(cidx, s))?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<PgSparseVec>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<PgSparseVec> {
+        self.produce_nullable::<PgSparseVec>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Vec<(i32, f32)>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Vec<(i32, f32)> {
+        Produce::<PgSparseVec>::produce(self)?.elements
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<Vec<(i32, f32)>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Vec<(i32, f32)>> {
+        self.produce_nullable::<Vec<(i32, f32)>>()?
+    }
+}
+
+// a timestamp with a trailing offset (e.g. a timestamptz cast to text) is read with the offset dropped
+fn parse_naive_datetime(s: &str) -> Option<NaiveDateTime> {
+    if let Ok(v) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f") {
+        return Some(v);
+    }
+    let (date, time) = s.split_once(' ')?;
+    let (time, offset) = time.split_at(time.rfind(['+', '-'])?);
+    let v = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S%.f")
+        .ok()?;
+    warn!("ignoring the offset {} of timestamp {}", offset, s);
+    Some(v)
+}
+
+// int2vector and oidvector are printed space separated like 1 2 3, pgvector like [1,2,3] and
+// other arrays like {1,2,3}
+fn split_vector(s: &str) -> Option<Vec<&str>> {
+    match s.chars().next() {
+        Some('{') => split_array(s),
+        Some('[') => split_pgvector(s),
+        _ => Some(s.split_whitespace().collect()),
+    }
+}
+
+// a pgvector has no quoting or nesting, and at least one element
+fn split_pgvector(s: &str) -> Option<Vec<&str>> {
+    let inner = s.strip_prefix('[')?.strip_suffix(']')?;
+    Some(inner.split(',').collect())
+}
+
+// a sparsevec is printed like {1:0.5,3:0.2}/5, with 1-based indices and no element if all are 0
+fn parse_sparsevec(s: &str) -> Option<PgSparseVec> {
+    let (inner, dim) = s.strip_prefix('{')?.split_once("}/")?;
+    let elements = match inner.is_empty() {
+        true => vec![],
+        false => inner
+            .split(',')
+            .map(|e| {
+                let (i, v) = e.split_once(':')?;
+                Some((i.parse().ok()?, v.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()?,
+    };
+    Some(PgSparseVec {
+        dim: dim.parse().ok()?,
+        elements,
+    })
+}
+
+// split a one dimensional array literal like {1,"$1,000.00"} into its elements
+fn split_array(s: &str) -> Option<Vec<&str>> {
+    let inner = s.strip_prefix('{')?.strip_suffix('}')?;
+    if inner.is_empty() {
+        return Some(vec![]);
+    }
+    let mut elems = vec![];
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                elems.push(inner[start..i].trim_matches('"'));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elems.push(inner[start..].trim_matches('"'));
+    Some(elems)
+}
+
+// the elements of a one dimensional text array like {a,"b c","d\"e"}, unquoted and unescaped.
+// A quoted element is kept as is, so a char(n) keeps its padding like the scalar does. An
+// unquoted NULL has no `String` to be read as.
+fn parse_text_array(s: &str) -> Option<Vec<String>> {
+    let inner = s.strip_prefix('{')?.strip_suffix('}')?;
+    if inner.is_empty() {
+        return Some(vec![]);
+    }
+    let mut elems = vec![];
+    let mut elem = String::new();
+    let (mut quoted, mut was_quoted) = (false, false);
+    let mut chars = inner.chars();
+    loop {
+        match chars.next() {
+            Some('\\') => elem.push(chars.next()?),
+            Some('"') => {
+                quoted = !quoted;
+                was_quoted = true;
+            }
+            Some(c) if c != ',' || quoted => elem.push(c),
+            c => {
+                if !was_quoted && elem == "NULL" {
+                    return None;
+                }
+                elems.push(std::mem::take(&mut elem));
+                was_quoted = false;
+                if c.is_none() {
+                    return Some(elems);
+                }
+            }
+        }
+    }
+}
+
+impl<'r, 'a> Produce<'r, Decimal> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Decimal {
+        let (ridx, cidx) = self.next_loc()?;
+        self.parse_decimal(cidx, &self.rowbuf[ridx][cidx])?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<Decimal>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Decimal> {
+        self.produce_nullable::<Decimal>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, (i64, u32)> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> (i64, u32) {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = self.parse_decimal(cidx, &self.rowbuf[ridx][cidx])?;
+        into_scaled(v, &self.names[cidx], self.scales[cidx])?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<(i64, u32)>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<(i64, u32)> {
+        self.produce_nullable::<(i64, u32)>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Vec<Decimal>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Vec<Decimal> {
+        let (ridx, cidx) = self.next_loc()?;
+        let s = &self.rowbuf[ridx][cidx][..];
+        split_array(s)
+            .ok_or_else(|| self.cannot_produce::<Vec<Decimal>>(cidx, s))?
+            .into_iter()
+            .map(|v| self.parse_decimal(cidx, v))
+            .collect::<Result<Vec<_>, _>>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<Vec<Decimal>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Vec<Decimal>> {
+        self.produce_nullable::<Vec<Decimal>>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Vec<String>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Vec<String> {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        parse_text_array(v).ok_or_else(|| self.cannot_produce::<Vec<String>>(cidx, v))?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<Vec<String>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Vec<String>> {
+        self.produce_nullable::<Vec<String>>()?
+    }
+}
+
+impl<'a> PostgresCSVSourceParser<'a> {
+    // an hstore is rejected for CSV by `fetch_metadata`, this is for the other ways to a produce
+    fn unsupported_hstore(&self) -> PostgresSourceError {
+        PostgresSourceError::UnsupportedType(
+            self.names
+                .get(self.current_col)
+                .cloned()
+                .unwrap_or_default(),
+            "hstore".to_string(),
+            CSVProtocol::NAME,
+        )
+    }
+}
+
+impl<'r, 'a> Produce<'r, HashMap<String, Option<String>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> HashMap<String, Option<String>> {
+        throw!(self.unsupported_hstore())
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<HashMap<String, Option<String>>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<HashMap<String, Option<String>>> {
+        throw!(self.unsupported_hstore())
+    }
+}
+
+impl<'r, 'a> Produce<'r, bool> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> bool {
+        let (ridx, cidx) = self.next_loc()?;
+        self.parse_bool(cidx, &self.rowbuf[ridx][cidx])?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<bool>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<bool> {
+        self.produce_nullable::<bool>()?
+    }
+}
+
+// a bit string, printed as its bits, e.g. `0101`
+impl<'r, 'a> Produce<'r, Vec<bool>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Vec<bool> {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        if !matches!(self.schema[cidx], PostgresTypeSystem::Bit(_)) {
+            throw!(self.cannot_produce::<Vec<bool>>(cidx, v));
+        }
+        v.chars()
+            .map(|c| match c {
+                '1' => Ok(true),
+                '0' => Ok(false),
+                _ => Err(self.cannot_produce::<Vec<bool>>(cidx, v)),
+            })
+            .collect::<Result<Vec<bool>, _>>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<Vec<bool>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<Vec<bool>> {
+        self.produce_nullable::<Vec<bool>>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, DateTime<Utc>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> DateTime<Utc> {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: &str = &self.rowbuf[ridx][cidx][..];
+        // postgres csv return example: 1970-01-01 00:00:01+00, without the offset for a
+        // timestamp taken as UTC
+        format!("{}:00", s)
+            .parse()
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+                    .ok()
+                    .map(|v| DateTime::from_utc(v, Utc))
+            })
+            .ok_or_else(|| self.cannot_produce::<DateTime<Utc>>(cidx, s))?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<DateTime<Utc>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<DateTime<Utc>> {
+        self.produce_nullable::<DateTime<Utc>>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, NaiveDate> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> NaiveDate {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        self.parse_naive_date(v)
+            .ok_or_else(|| self.cannot_produce::<NaiveDate>(cidx, v))?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<NaiveDate>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<NaiveDate> {
+        self.produce_nullable::<NaiveDate>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, NaiveDateTime> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> NaiveDateTime {
+        let (ridx, cidx) = self.next_loc()?;
+        parse_naive_datetime(&self.rowbuf[ridx][cidx])
+            .ok_or_else(|| self.cannot_produce::<NaiveDateTime>(cidx, &self.rowbuf[ridx][cidx]))?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<NaiveDateTime>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<NaiveDateTime> {
+        self.produce_nullable::<NaiveDateTime>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, NaiveTime> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> NaiveTime {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        parse_time_of_day(v).ok_or_else(|| self.cannot_produce::<NaiveTime>(cidx, v))?
     }
+}
 
-    fn nrows(&self) -> usize {
-        self.nrows
+impl<'r, 'a> Produce<'r, Option<NaiveTime>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&mut self) -> Option<NaiveTime> {
+        self.produce_nullable::<NaiveTime>()?
     }
+}
 
-    fn ncols(&self) -> usize {
-        self.ncols
+impl<'r, 'a> Produce<'r, &'r str> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> &'r str {
+        let (ridx, cidx) = self.next_loc()?;
+        &self.rowbuf[ridx][cidx]
     }
 }
-pub struct PostgresBinarySourcePartitionParser<'a> {
-    iter: BinaryCopyOutIter<'a>,
-    rowbuf: Vec<BinaryCopyOutRow>,
-    ncols: usize,
-    current_col: usize,
-    current_row: usize,
+
+impl<'r, 'a> Produce<'r, Option<&'r str>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<&'r str> {
+        let (ridx, cidx) = self.next_loc()?;
+        match &self.rowbuf[ridx][cidx][..] {
+            v if v == self.null => None,
+            v => Some(v),
+        }
+    }
 }
 
-impl<'a> PostgresBinarySourcePartitionParser<'a> {
-    pub fn new(iter: BinaryCopyOutIter<'a>, schema: &[PostgresTypeSystem]) -> Self {
-        Self {
-            iter,
-            rowbuf: Vec::with_capacity(DB_BUFFER_SIZE),
-            ncols: schema.len(),
-            current_row: 0,
-            current_col: 0,
+// the text as COPY printed it, e.g. the exact value of a numeric, or a bytea in its
+// `ByteaEncoding`. COPY prints a bytea as hex already.
+impl<'r, 'a> Produce<'r, String> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> String {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        match (self.schema[cidx], self.bytea_encoding) {
+            (PostgresTypeSystem::ByteA(_), ByteaEncoding::Base64) => {
+                base64::encode(decode(&v[2..])?) // escape \x in the beginning
+            }
+            _ => v.to_string(),
         }
     }
+}
+
+impl<'r, 'a> Produce<'r, Option<String>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn next_loc(&mut self) -> (usize, usize) {
-        let ret = (self.current_row, self.current_col);
-        self.current_row += (self.current_col + 1) / self.ncols;
-        self.current_col = (self.current_col + 1) % self.ncols;
-        ret
+    fn produce(&'r mut self) -> Option<String> {
+        self.produce_nullable::<String>()?
     }
 }
 
-impl<'a> PartitionParser<'a> for PostgresBinarySourcePartitionParser<'a> {
-    type TypeSystem = PostgresTypeSystem;
+impl<'r, 'a> Produce<'r, Vec<u8>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn fetch_next(&mut self) -> (usize, bool) {
-        if !self.rowbuf.is_empty() {
-            self.rowbuf.drain(..);
-        }
-        for _ in 0..DB_BUFFER_SIZE {
-            match self.iter.next()? {
-                Some(row) => {
-                    self.rowbuf.push(row);
-                }
-                None => break,
+    fn produce(&'r mut self) -> Vec<u8> {
+        match self.schema.get(self.current_col) {
+            Some(PostgresTypeSystem::UUID(_)) => Produce::<[u8; 16]>::produce(self)?.to_vec(),
+            _ => {
+                let (ridx, cidx) = self.next_loc()?;
+                decode(&self.rowbuf[ridx][cidx][2..])? // escape \x in the beginning
             }
         }
-        self.current_row = 0;
-        self.current_col = 0;
-        (self.rowbuf.len(), self.rowbuf.len() < DB_BUFFER_SIZE)
     }
 }
 
-macro_rules! impl_produce {
-    ($($t: ty,)+) => {
-        $(
-            impl<'r, 'a> Produce<'r, $t> for PostgresBinarySourcePartitionParser<'a> {
-                type Error = PostgresSourceError;
+impl<'r, 'a> Produce<'r, Option<Vec<u8>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
 
-                #[throws(PostgresSourceError)]
-                fn produce(&'r mut self) -> $t {
-                    let (ridx, cidx) = self.next_loc()?;
-                    let row = &self.rowbuf[ridx];
-                    let val = row.try_get(cidx)?;
-                    val
-                }
-            }
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Vec<u8>> {
+        self.produce_nullable::<Vec<u8>>()?
+    }
+}
 
-            impl<'r, 'a> Produce<'r, Option<$t>> for PostgresBinarySourcePartitionParser<'a> {
-                type Error = PostgresSourceError;
+impl<'r, 'a> Produce<'r, Box<[u8]>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
 
-                #[throws(PostgresSourceError)]
-                fn produce(&'r mut self) -> Option<$t> {
-                    let (ridx, cidx) = self.next_loc()?;
-                    let row = &self.rowbuf[ridx];
-                    let val = row.try_get(cidx)?;
-                    val
-                }
-            }
-        )+
-    };
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Box<[u8]> {
+        Produce::<Vec<u8>>::produce(self)?.into_boxed_slice()
+    }
 }
 
-impl_produce!(
-    i8,
-    i16,
-    i32,
-    i64,
-    f32,
-    f64,
-    Decimal,
-    Vec<i16>,
-    Vec<i32>,
-    Vec<i64>,
-    Vec<f32>,
-    Vec<f64>,
-    Vec<Decimal>,
-    bool,
-    &'r str,
-    Vec<u8>,
-    NaiveTime,
-    NaiveDateTime,
-    DateTime<Utc>,
-    NaiveDate,
-    Uuid,
-    Value,
-);
+impl<'r, 'a> Produce<'r, Option<Box<[u8]>>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
 
-impl<'r, 'a> Produce<'r, HashMap<String, Option<String>>>
-    for PostgresBinarySourcePartitionParser<'a>
-{
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Box<[u8]>> {
+        self.produce_nullable::<Box<[u8]>>()?
+    }
+}
+
+impl<'r, 'a> Produce<'r, [u8; 16]> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
+
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> HashMap<String, Option<String>> {
-        unimplemented!("Please use `cursor` protocol for hstore type");
+    fn produce(&'r mut self) -> [u8; 16] {
+        *Produce::<Uuid>::produce(self)?.as_bytes()
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<HashMap<String, Option<String>>>>
-    for PostgresBinarySourcePartitionParser<'a>
-{
+impl<'r, 'a> Produce<'r, Option<[u8; 16]>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<[u8; 16]> {
+        self.produce_nullable::<[u8; 16]>()?
+    }
+}
+
+// a uuid as a big-endian integer
+impl<'r, 'a> Produce<'r, u128> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
+
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<HashMap<String, Option<String>>> {
-        unimplemented!("Please use `cursor` protocol for hstore type");
+    fn produce(&'r mut self) -> u128 {
+        Produce::<Uuid>::produce(self)?.as_u128()
     }
 }
 
-pub struct PostgresCSVSourceParser<'a> {
-    iter: StringRecordsIntoIter<CopyOutReader<'a>>,
-    rowbuf: Vec<StringRecord>,
-    ncols: usize,
-    current_col: usize,
-    current_row: usize,
+impl<'r, 'a> Produce<'r, Option<u128>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<u128> {
+        self.produce_nullable::<u128>()?
+    }
 }
 
-impl<'a> PostgresCSVSourceParser<'a> {
-    pub fn new(
-        iter: StringRecordsIntoIter<CopyOutReader<'a>>,
-        schema: &[PostgresTypeSystem],
-    ) -> Self {
-        Self {
-            iter,
-            rowbuf: Vec::with_capacity(DB_BUFFER_SIZE),
-            ncols: schema.len(),
-            current_row: 0,
-            current_col: 0,
+// an xml is read as a json string of its text
+impl<'r, 'a> Produce<'r, Value> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Value {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        match self.schema[cidx] {
+            PostgresTypeSystem::Xml(_) => Value::String(v.to_string()),
+            _ => from_str(v).map_err(|_| self.cannot_produce::<Value>(cidx, v))?,
         }
     }
+}
+
+impl<'r, 'a> Produce<'r, Vec<Value>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn next_loc(&mut self) -> (usize, usize) {
-        let ret = (self.current_row, self.current_col);
-        self.current_row += (self.current_col + 1) / self.ncols;
-        self.current_col = (self.current_col + 1) % self.ncols;
-        ret
+    fn produce(&'r mut self) -> Vec<Value> {
+        let cidx = self.current_col;
+        json_array(&self.names[cidx], Produce::<Value>::produce(self)?)?
     }
 }
 
-impl<'a> PartitionParser<'a> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<Vec<Value>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
-    type TypeSystem = PostgresTypeSystem;
 
     #[throws(PostgresSourceError)]
-    fn fetch_next(&mut self) -> (usize, bool) {
-        if !self.rowbuf.is_empty() {
-            self.rowbuf.drain(..);
-        }
-        for _ in 0..DB_BUFFER_SIZE {
-            if let Some(row) = self.iter.next() {
-                self.rowbuf.push(row?);
-            } else {
-                break;
-            }
-        }
-        self.current_row = 0;
-        self.current_col = 0;
-        (self.rowbuf.len(), self.rowbuf.len() < DB_BUFFER_SIZE)
+    fn produce(&'r mut self) -> Option<Vec<Value>> {
+        self.produce_nullable::<Vec<Value>>()?
     }
 }
 
-macro_rules! impl_csv_produce {
-    ($($t: ty,)+) => {
+impl<'r, 'a> Produce<'r, Option<Value>> for PostgresCSVSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<Value> {
+        self.produce_nullable::<Value>()?
+    }
+}
+
+#[cfg(feature = "time")]
+fn parse_date(s: &str) -> Option<time::Date> {
+    time::Date::parse(s, time::macros::format_description!("[year]-[month]-[day]")).ok()
+}
+
+#[cfg(feature = "time")]
+fn parse_time(s: &str) -> Option<time::Time> {
+    use time::macros::format_description;
+    match s.contains('.') {
+        true => time::Time::parse(
+            s,
+            format_description!("[hour]:[minute]:[second].[subsecond]"),
+        ),
+        false => time::Time::parse(s, format_description!("[hour]:[minute]:[second]")),
+    }
+    .ok()
+}
+
+#[cfg(feature = "time")]
+fn parse_primitive_datetime(s: &str) -> Option<time::PrimitiveDateTime> {
+    let (date, time) = s.split_once(' ')?;
+    Some(time::PrimitiveDateTime::new(
+        parse_date(date)?,
+        parse_time(time)?,
+    ))
+}
+
+// the offset is printed last as +hh, +hh:mm or +hh:mm:ss
+#[cfg(feature = "time")]
+fn parse_offset_datetime(s: &str) -> Option<time::OffsetDateTime> {
+    let (datetime, offset) = s.split_at(s.rfind(['+', '-'])?);
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let mut hms = [0i8; 3];
+    for (i, part) in offset[1..].split(':').enumerate() {
+        *hms.get_mut(i)? = sign * part.parse::<i8>().ok()?;
+    }
+    let offset = time::UtcOffset::from_hms(hms[0], hms[1], hms[2]).ok()?;
+    Some(parse_primitive_datetime(datetime)?.assume_offset(offset))
+}
+
+#[cfg(feature = "time")]
+macro_rules! impl_csv_time_produce {
+    ($($t: ty => $parse: ident,)+) => {
         $(
             impl<'r, 'a> Produce<'r, $t> for PostgresCSVSourceParser<'a> {
                 type Error = PostgresSourceError;
@@ -544,9 +4876,8 @@ macro_rules! impl_csv_produce {
                 #[throws(PostgresSourceError)]
                 fn produce(&'r mut self) -> $t {
                     let (ridx, cidx) = self.next_loc()?;
-                    self.rowbuf[ridx][cidx].parse().map_err(|_| {
-                        ConnectorXError::cannot_produce::<$t>(Some(self.rowbuf[ridx][cidx].into()))
-                    })?
+                    let v = &self.rowbuf[ridx][cidx];
+                    $parse(v).ok_or_else(|| self.cannot_produce::<$t>(cidx, v))?
                 }
             }
 
@@ -555,346 +4886,492 @@ macro_rules! impl_csv_produce {
 
                 #[throws(PostgresSourceError)]
                 fn produce(&'r mut self) -> Option<$t> {
-                    let (ridx, cidx) = self.next_loc()?;
-                    match &self.rowbuf[ridx][cidx][..] {
-                        "" => None,
-                        v => Some(v.parse().map_err(|_| {
-                            ConnectorXError::cannot_produce::<$t>(Some(self.rowbuf[ridx][cidx].into()))
-                        })?),
-                    }
+                    self.produce_nullable::<$t>()?
                 }
             }
         )+
     };
 }
 
-impl_csv_produce!(i8, i16, i32, i64, f32, f64, Decimal, Uuid,);
+#[cfg(feature = "time")]
+impl_csv_time_produce!(
+    time::OffsetDateTime => parse_offset_datetime,
+    time::PrimitiveDateTime => parse_primitive_datetime,
+    time::Date => parse_date,
+    time::Time => parse_time,
+);
 
-macro_rules! impl_csv_vec_produce {
-    ($($t: ty,)+) => {
-        $(
-            impl<'r, 'a> Produce<'r, Vec<$t>> for PostgresCSVSourceParser<'a> {
-                type Error = PostgresSourceError;
+// parse [-]hh:mm:ss[.ffffff] into microseconds
+fn parse_interval_time(s: &str) -> Option<i64> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut parts = s.splitn(3, ':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds = parse_interval_seconds(parts.next()?)?;
+    let v = (hours * 60 + minutes) * 60_000_000 + seconds;
+    Some(if negative { -v } else { v })
+}
 
-                #[throws(PostgresSourceError)]
-                fn produce(&mut self) -> Vec<$t> {
-                    let (ridx, cidx) = self.next_loc()?;
-                    let s = &self.rowbuf[ridx][cidx][..];
-                    match s {
-                        "{}" => vec![],
-                        _ if s.len() < 3 => throw!(ConnectorXError::cannot_produce::<$t>(Some(s.into()))),
-                        s => s[1..s.len() - 1]
-                            .split(",")
-                            .map(|v| {
-                                v.parse()
-                                    .map_err(|_| ConnectorXError::cannot_produce::<$t>(Some(s.into())))
-                            })
-                            .collect::<Result<Vec<$t>, ConnectorXError>>()?,
-                    }
-                }
-            }
+// parse ss[.ffffff] into microseconds, keeping the sign of the seconds
+fn parse_interval_seconds(s: &str) -> Option<i64> {
+    let negative = s.starts_with('-');
+    let (secs, frac) = s.split_once('.').unwrap_or((s, ""));
+    if frac.len() > 6 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let secs: i64 = secs.parse().ok()?;
+    let frac: i64 = format!("{:0<6}", frac).parse().ok()?;
+    Some(secs * 1_000_000 + if negative { -frac } else { frac })
+}
 
-            impl<'r, 'a> Produce<'r, Option<Vec<$t>>> for PostgresCSVSourceParser<'a> {
-                type Error = PostgresSourceError;
+// IntervalStyle `postgres` (the default): 1 year 2 mons -3 days +04:05:06.789
+fn parse_postgres_interval(s: &str) -> Option<PostgresInterval> {
+    let mut ret = PostgresInterval::default();
+    let mut tokens = s.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token.contains(':') {
+            ret.microseconds = parse_interval_time(token)?;
+            continue;
+        }
+        let v: i32 = token.parse().ok()?;
+        match tokens.next()? {
+            "year" | "years" => ret.months += v.checked_mul(12)?,
+            "mon" | "mons" => ret.months += v,
+            "day" | "days" => ret.days += v,
+            _ => return None,
+        }
+    }
+    Some(ret)
+}
 
-                #[throws(PostgresSourceError)]
-                fn produce(&mut self) -> Option<Vec<$t>> {
-                    let (ridx, cidx) = self.next_loc()?;
-                    let s = &self.rowbuf[ridx][cidx][..];
-                    match s {
-                        "" => None,
-                        "{}" => Some(vec![]),
-                        _ if s.len() < 3 => throw!(ConnectorXError::cannot_produce::<$t>(Some(s.into()))),
-                        s => Some(
-                            s[1..s.len() - 1]
-                                .split(",")
-                                .map(|v| {
-                                    v.parse()
-                                        .map_err(|_| ConnectorXError::cannot_produce::<$t>(Some(s.into())))
-                                })
-                                .collect::<Result<Vec<$t>, ConnectorXError>>()?,
-                        ),
-                    }
-                }
-            }
-        )+
-    };
+// IntervalStyle `iso_8601`: P1Y2M-3DT4H5M6.789S
+fn parse_iso_8601_interval(s: &str) -> Option<PostgresInterval> {
+    let mut ret = PostgresInterval::default();
+    let s = s.strip_prefix('P')?;
+    let (date, time) = s.split_once('T').unwrap_or((s, ""));
+    let mut rest = date;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let v: i32 = rest[..end].parse().ok()?;
+        match &rest[end..end + 1] {
+            "Y" => ret.months += v.checked_mul(12)?,
+            "M" => ret.months += v,
+            "W" => ret.days += v.checked_mul(7)?,
+            "D" => ret.days += v,
+            _ => return None,
+        }
+        rest = &rest[end + 1..];
+    }
+    let mut rest = time;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let v = &rest[..end];
+        match &rest[end..end + 1] {
+            "H" => ret.microseconds += v.parse::<i64>().ok()? * 3_600_000_000,
+            "M" => ret.microseconds += v.parse::<i64>().ok()? * 60_000_000,
+            "S" => ret.microseconds += parse_interval_seconds(v)?,
+            _ => return None,
+        }
+        rest = &rest[end + 1..];
+    }
+    Some(ret)
+}
+
+// an interval as microseconds, see `PostgresSource::set_interval_month_days`
+#[throws(PostgresSourceError)]
+fn interval_micros(name: &str, val: &PostgresInterval, month_days: Option<i64>) -> i64 {
+    val.to_micros(month_days).ok_or_else(|| {
+        PostgresSourceError::IntervalMicros(name.to_string(), format!("{:?}", val))
+    })?
 }
 
-impl_csv_vec_produce!(i8, i16, i32, i64, f32, f64, Decimal,);
+fn parse_interval(s: &str) -> Option<PostgresInterval> {
+    match s.starts_with('P') {
+        true => parse_iso_8601_interval(s),
+        false => parse_postgres_interval(s),
+    }
+}
 
-impl<'r, 'a> Produce<'r, HashMap<String, Option<String>>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, PostgresInterval> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
+
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> HashMap<String, Option<String>> {
-        unimplemented!("Please use `cursor` protocol for hstore type");
+    fn produce(&'r mut self) -> PostgresInterval {
+        let (ridx, cidx) = self.next_loc()?;
+        let v = &self.rowbuf[ridx][cidx];
+        parse_interval(v).ok_or_else(|| self.cannot_produce::<PostgresInterval>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<HashMap<String, Option<String>>>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<PostgresInterval>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
+
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<HashMap<String, Option<String>>> {
-        unimplemented!("Please use `cursor` protocol for hstore type");
+    fn produce(&'r mut self) -> Option<PostgresInterval> {
+        self.produce_nullable::<PostgresInterval>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, bool> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, (i32, i32, i64)> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> bool {
-        let (ridx, cidx) = self.next_loc()?;
-        let ret = match &self.rowbuf[ridx][cidx][..] {
-            "t" => true,
-            "f" => false,
-            _ => throw!(ConnectorXError::cannot_produce::<bool>(Some(
-                self.rowbuf[ridx][cidx].into()
-            ))),
-        };
-        ret
+    fn produce(&'r mut self) -> (i32, i32, i64) {
+        let val = Produce::<PostgresInterval>::produce(self)?;
+        (val.months, val.days, val.microseconds)
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<bool>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<(i32, i32, i64)>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<bool> {
-        let (ridx, cidx) = self.next_loc()?;
-        let ret = match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            "t" => Some(true),
-            "f" => Some(false),
-            _ => throw!(ConnectorXError::cannot_produce::<bool>(Some(
-                self.rowbuf[ridx][cidx].into()
-            ))),
-        };
-        ret
+    fn produce(&'r mut self) -> Option<(i32, i32, i64)> {
+        self.produce_nullable::<(i32, i32, i64)>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, DateTime<Utc>> for PostgresCSVSourceParser<'a> {
+// COPY prints a geometry or geography as the hex of its EWKB
+#[cfg(feature = "postgis")]
+impl<'r, 'a> Produce<'r, geo_types::Geometry<f64>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> DateTime<Utc> {
+    fn produce(&'r mut self) -> geo_types::Geometry<f64> {
         let (ridx, cidx) = self.next_loc()?;
-        let s: &str = &self.rowbuf[ridx][cidx][..];
-        // postgres csv return example: 1970-01-01 00:00:01+00
-        format!("{}:00", s).parse().map_err(|_| {
-            ConnectorXError::cannot_produce::<DateTime<Utc>>(Some(self.rowbuf[ridx][cidx].into()))
-        })?
+        let v = &self.rowbuf[ridx][cidx];
+        decode(v)
+            .ok()
+            .and_then(|raw| decode_ewkb(&raw).ok())
+            .ok_or_else(|| self.cannot_produce::<geo_types::Geometry<f64>>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<DateTime<Utc>>> for PostgresCSVSourceParser<'a> {
+#[cfg(feature = "postgis")]
+impl<'r, 'a> Produce<'r, Option<geo_types::Geometry<f64>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<DateTime<Utc>> {
-        let (ridx, cidx) = self.next_loc()?;
-        match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            v => {
-                // postgres csv return example: 1970-01-01 00:00:01+00
-                Some(format!("{}:00", v).parse().map_err(|_| {
-                    ConnectorXError::cannot_produce::<DateTime<Utc>>(Some(v.into()))
-                })?)
+    fn produce(&'r mut self) -> Option<geo_types::Geometry<f64>> {
+        self.produce_nullable::<geo_types::Geometry<f64>>()?
+    }
+}
+
+// a multirange literal like {[1,3),["2020-01-01 00:00:00",)} as its ranges, with each bound
+// parsed by `parse`. A bound is quoted if it has special characters, and empty if unbounded.
+fn parse_multirange<T>(s: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<PgRange<T>>> {
+    let inner = s.strip_prefix('{')?.strip_suffix('}')?;
+    let mut ranges = vec![];
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        let lower_inc = match c {
+            '[' => true,
+            '(' => false,
+            ',' if !ranges.is_empty() => continue,
+            _ => return None,
+        };
+        let mut bounds = [String::new(), String::new()];
+        let (mut i, mut quoted) = (0, false);
+        let upper_inc = loop {
+            match chars.next()? {
+                '\\' => bounds[i].push(chars.next()?),
+                '"' => quoted = !quoted,
+                ',' if !quoted && i == 0 => i = 1,
+                ']' if !quoted && i == 1 => break true,
+                ')' if !quoted && i == 1 => break false,
+                c => bounds[i].push(c),
             }
-        }
+        };
+        let [lower, upper] = bounds;
+        let bound = |b: String| match b.is_empty() {
+            true => Some(None),
+            false => parse(&b).map(Some),
+        };
+        ranges.push(PgRange {
+            lower: bound(lower)?,
+            upper: bound(upper)?,
+            lower_inc,
+            upper_inc,
+        });
     }
+    Some(ranges)
 }
 
-impl<'r, 'a> Produce<'r, NaiveDate> for PostgresCSVSourceParser<'a> {
+macro_rules! impl_csv_multirange_produce {
+    ($($t: ty => $parse: expr,)+) => {
+        $(
+            impl<'r, 'a> Produce<'r, Vec<PgRange<$t>>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Vec<PgRange<$t>> {
+                    let (ridx, cidx) = self.next_loc()?;
+                    let v = &self.rowbuf[ridx][cidx];
+                    parse_multirange(v, $parse)
+                        .ok_or_else(|| self.cannot_produce::<Vec<PgRange<$t>>>(cidx, v))?
+                }
+            }
+
+            impl<'r, 'a> Produce<'r, Option<Vec<PgRange<$t>>>> for PostgresCSVSourceParser<'a> {
+                type Error = PostgresSourceError;
+
+                #[throws(PostgresSourceError)]
+                fn produce(&'r mut self) -> Option<Vec<PgRange<$t>>> {
+                    self.produce_nullable::<Vec<PgRange<$t>>>()?
+                }
+            }
+        )+
+    };
+}
+
+impl_csv_multirange_produce!(
+    i32 => |v: &str| v.parse().ok(),
+    i64 => |v: &str| v.parse().ok(),
+    NaiveDate => |v: &str| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok(),
+    NaiveDateTime => parse_naive_datetime,
+    // printed like 2020-01-01 00:00:00+00
+    DateTime<Utc> => |v: &str| format!("{}:00", v).parse().ok(),
+);
+
+fn parse_time_of_day(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f").ok()
+}
+
+// the elements of an array literal like {12:00:00,NULL}, parsed by `parse` unless NULL
+fn parse_nullable_array<T>(s: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<Option<T>>> {
+    split_array(s)?
+        .into_iter()
+        .map(|v| match v {
+            "NULL" => Some(None),
+            v => parse(v).map(Some),
+        })
+        .collect()
+}
+
+impl<'r, 'a> Produce<'r, Vec<NaiveTime>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> NaiveDate {
+    fn produce(&'r mut self) -> Vec<NaiveTime> {
         let (ridx, cidx) = self.next_loc()?;
-        NaiveDate::parse_from_str(&self.rowbuf[ridx][cidx], "%Y-%m-%d").map_err(|_| {
-            ConnectorXError::cannot_produce::<NaiveDate>(Some(self.rowbuf[ridx][cidx].into()))
-        })?
+        let v = &self.rowbuf[ridx][cidx];
+        split_array(v)
+            .and_then(|elems| elems.into_iter().map(parse_time_of_day).collect())
+            .ok_or_else(|| self.cannot_produce::<Vec<NaiveTime>>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<NaiveDate>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<Vec<NaiveTime>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<NaiveDate> {
-        let (ridx, cidx) = self.next_loc()?;
-        match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            v => Some(
-                NaiveDate::parse_from_str(v, "%Y-%m-%d")
-                    .map_err(|_| ConnectorXError::cannot_produce::<NaiveDate>(Some(v.into())))?,
-            ),
-        }
+    fn produce(&'r mut self) -> Option<Vec<NaiveTime>> {
+        self.produce_nullable::<Vec<NaiveTime>>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, NaiveDateTime> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Vec<PostgresInterval>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> NaiveDateTime {
+    fn produce(&'r mut self) -> Vec<PostgresInterval> {
         let (ridx, cidx) = self.next_loc()?;
-        NaiveDateTime::parse_from_str(&self.rowbuf[ridx][cidx], "%Y-%m-%d %H:%M:%S").map_err(
-            |_| {
-                ConnectorXError::cannot_produce::<NaiveDateTime>(Some(
-                    self.rowbuf[ridx][cidx].into(),
-                ))
-            },
-        )?
+        let v = &self.rowbuf[ridx][cidx];
+        split_array(v)
+            .and_then(|elems| elems.into_iter().map(parse_interval).collect())
+            .ok_or_else(|| self.cannot_produce::<Vec<PostgresInterval>>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<NaiveDateTime>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<Vec<PostgresInterval>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<NaiveDateTime> {
-        let (ridx, cidx) = self.next_loc()?;
-        match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            v => Some(
-                NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S").map_err(|_| {
-                    ConnectorXError::cannot_produce::<NaiveDateTime>(Some(v.into()))
-                })?,
-            ),
-        }
+    fn produce(&'r mut self) -> Option<Vec<PostgresInterval>> {
+        self.produce_nullable::<Vec<PostgresInterval>>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, NaiveTime> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, chrono::Duration> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> NaiveTime {
+    fn produce(&'r mut self) -> chrono::Duration {
         let (ridx, cidx) = self.next_loc()?;
-        NaiveTime::parse_from_str(&self.rowbuf[ridx][cidx], "%H:%M:%S").map_err(|_| {
-            ConnectorXError::cannot_produce::<NaiveTime>(Some(self.rowbuf[ridx][cidx].into()))
-        })?
+        let v = &self.rowbuf[ridx][cidx];
+        self.parse_duration(cidx, v)
+            .ok_or_else(|| self.cannot_produce::<chrono::Duration>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<NaiveTime>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<chrono::Duration>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&mut self) -> Option<NaiveTime> {
-        let (ridx, cidx) = self.next_loc()?;
-        match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            v => Some(
-                NaiveTime::parse_from_str(v, "%H:%M:%S")
-                    .map_err(|_| ConnectorXError::cannot_produce::<NaiveTime>(Some(v.into())))?,
-            ),
-        }
+    fn produce(&'r mut self) -> Option<chrono::Duration> {
+        self.produce_nullable::<chrono::Duration>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, &'r str> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Vec<Option<chrono::Duration>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&'r mut self) -> &'r str {
+    fn produce(&'r mut self) -> Vec<Option<chrono::Duration>> {
         let (ridx, cidx) = self.next_loc()?;
-        &self.rowbuf[ridx][cidx]
+        let v = &self.rowbuf[ridx][cidx];
+        parse_nullable_array(v, |elem| self.parse_duration(cidx, elem))
+            .ok_or_else(|| self.cannot_produce::<Vec<Option<chrono::Duration>>>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<&'r str>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<Vec<Option<chrono::Duration>>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&'r mut self) -> Option<&'r str> {
-        let (ridx, cidx) = self.next_loc()?;
-        match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            v => Some(v),
-        }
+    fn produce(&'r mut self) -> Option<Vec<Option<chrono::Duration>>> {
+        self.produce_nullable::<Vec<Option<chrono::Duration>>>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, Vec<u8>> for PostgresCSVSourceParser<'a> {
+// a NULL element has no duration to be read as
+impl<'r, 'a> Produce<'r, Vec<chrono::Duration>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&'r mut self) -> Vec<u8> {
+    fn produce(&'r mut self) -> Vec<chrono::Duration> {
         let (ridx, cidx) = self.next_loc()?;
-        decode(&self.rowbuf[ridx][cidx][2..])? // escape \x in the beginning
+        let v = &self.rowbuf[ridx][cidx];
+        parse_nullable_array(v, |elem| self.parse_duration(cidx, elem))
+            .and_then(|elems| elems.into_iter().collect())
+            .ok_or_else(|| self.cannot_produce::<Vec<chrono::Duration>>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<Vec<u8>>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<Vec<chrono::Duration>>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&'r mut self) -> Option<Vec<u8>> {
-        let (ridx, cidx) = self.next_loc()?;
-        match &self.rowbuf[ridx][cidx] {
-            // escape \x in the beginning, empty if None
-            "" => None,
-            v => Some(decode(&v[2..])?),
-        }
+    fn produce(&'r mut self) -> Option<Vec<chrono::Duration>> {
+        self.produce_nullable::<Vec<chrono::Duration>>()?
     }
 }
 
-impl<'r, 'a> Produce<'r, Value> for PostgresCSVSourceParser<'a> {
+// postgres prints a "char" as the character itself, or as an octal escape `\ooo` for non-ASCII bytes
+fn parse_char(s: &str) -> Option<u8> {
+    match s.as_bytes() {
+        [b] => Some(*b),
+        [b'\\', rest @ ..] if rest.len() == 3 => u8::from_str_radix(&s[1..], 8).ok(),
+        _ => None,
+    }
+}
+
+impl<'r, 'a> Produce<'r, u8> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&'r mut self) -> Value {
+    fn produce(&'r mut self) -> u8 {
         let (ridx, cidx) = self.next_loc()?;
         let v = &self.rowbuf[ridx][cidx];
-        from_str(v).map_err(|_| ConnectorXError::cannot_produce::<Value>(Some(v.into())))?
+        parse_char(v).ok_or_else(|| self.cannot_produce::<u8>(cidx, v))?
     }
 }
 
-impl<'r, 'a> Produce<'r, Option<Value>> for PostgresCSVSourceParser<'a> {
+impl<'r, 'a> Produce<'r, Option<u8>> for PostgresCSVSourceParser<'a> {
     type Error = PostgresSourceError;
 
     #[throws(PostgresSourceError)]
-    fn produce(&'r mut self) -> Option<Value> {
-        let (ridx, cidx) = self.next_loc()?;
-
-        match &self.rowbuf[ridx][cidx][..] {
-            "" => None,
-            v => {
-                from_str(v).map_err(|_| ConnectorXError::cannot_produce::<Value>(Some(v.into())))?
-            }
-        }
+    fn produce(&'r mut self) -> Option<u8> {
+        self.produce_nullable::<u8>()?
     }
 }
 
 pub struct PostgresRawSourceParser<'a> {
     iter: RowIter<'a>,
     rowbuf: Vec<Row>,
+    query: String,
+    names: Vec<String>,
+    schema: Vec<PostgresTypeSystem>,
+    scales: Vec<Option<u32>>,
+    bool_coercion: Option<BoolCoercion>,
+    bytea_encoding: ByteaEncoding,
+    timezone: Tz,
+    interval_month_days: Option<i64>,
     ncols: usize,
+    nrows_read: usize,
     current_col: usize,
     current_row: usize,
+    // the flag of the partition to clear at the end of the result
+    reading: Option<&'a AtomicBool>,
+    // the column of the resume key and where the partition keeps the last one
+    resume: Option<(usize, &'a Cell<Option<i64>>)>,
+    // where to count the rows dropped under `DecodeErrorPolicy::SkipRow`, and how many this
+    // parser dropped
+    skip_rows: Option<DecodeErrorReport>,
+    skipped_rows: usize,
 }
 
 impl<'a> PostgresRawSourceParser<'a> {
-    pub fn new(iter: RowIter<'a>, schema: &[PostgresTypeSystem]) -> Self {
+    pub fn new(
+        iter: RowIter<'a>,
+        query: &str,
+        names: &[String],
+        schema: &[PostgresTypeSystem],
+    ) -> Self {
         Self {
             iter,
             rowbuf: Vec::with_capacity(DB_BUFFER_SIZE),
+            query: query.to_string(),
+            names: names.to_vec(),
+            schema: schema.to_vec(),
+            scales: vec![None; schema.len()],
+            bool_coercion: None,
+            bytea_encoding: ByteaEncoding::Hex,
+            timezone: Tz::UTC,
+            interval_month_days: None,
             ncols: schema.len(),
+            nrows_read: 0,
             current_row: 0,
             current_col: 0,
+            reading: None,
+            resume: None,
+            skip_rows: None,
+            skipped_rows: 0,
         }
     }
 
     #[throws(PostgresSourceError)]
     fn next_loc(&mut self) -> (usize, usize) {
+        if self.ncols == 0 {
+            throw!(PostgresSourceError::NoColumns(self.query.clone()));
+        }
         let ret = (self.current_row, self.current_col);
         self.current_row += (self.current_col + 1) / self.ncols;
         self.current_col = (self.current_col + 1) % self.ncols;
         ret
     }
+
+    // attach the partition query and the number of rows read so far to an error from the stream
+    fn stream_error<E: Into<PostgresSourceError>>(&self, e: E) -> PostgresSourceError {
+        PostgresSourceError::StreamError(
+            self.query.clone(),
+            self.nrows_read + self.rowbuf.len(),
+            Box::new(e.into()),
+        )
+    }
+
+    /// Read column `cidx` of all the buffered rows at once, use `Option<T>` for nullable columns.
+    /// This does not move the cursor used by `produce`, so do not mix the two on one batch.
+    #[throws(PostgresSourceError)]
+    pub fn produce_all<'r, T: FromSql<'r>>(&'r self, cidx: usize) -> Vec<T> {
+        self.rowbuf
+            .iter()
+            .map(|row| row.try_get(cidx))
+            .collect::<Result<Vec<T>, _>>()?
+    }
 }
 
 impl<'a> PartitionParser<'a> for PostgresRawSourceParser<'a> {
@@ -903,19 +5380,29 @@ impl<'a> PartitionParser<'a> for PostgresRawSourceParser<'a> {
 
     #[throws(PostgresSourceError)]
     fn fetch_next(&mut self) -> (usize, bool) {
+        // the previous batch was consumed, so a resumed read can start past it
+        if let (Some((cidx, last_key)), Some(row)) = (self.resume, self.rowbuf.last()) {
+            last_key.set(Some(row.try_get::<_, PostgresResumeKey>(cidx)?.0));
+        }
         if !self.rowbuf.is_empty() {
             self.rowbuf.drain(..);
         }
         for _ in 0..DB_BUFFER_SIZE {
-            if let Some(row) = self.iter.next()? {
+            if let Some(row) = self.iter.next().map_err(|e| self.stream_error(e))? {
                 self.rowbuf.push(row);
             } else {
+                if let Some(reading) = self.reading {
+                    reading.store(false, Ordering::Relaxed);
+                }
                 break;
             }
         }
+        self.nrows_read += self.rowbuf.len();
+        let is_last = self.rowbuf.len() < DB_BUFFER_SIZE;
+        self.skip_undecodable_rows(is_last);
         self.current_row = 0;
         self.current_col = 0;
-        (self.rowbuf.len(), self.rowbuf.len() < DB_BUFFER_SIZE)
+        (self.rowbuf.len(), is_last)
     }
 }
 
@@ -950,27 +5437,377 @@ macro_rules! impl_produce {
 }
 
 impl_produce!(
-    i8,
-    i16,
-    i32,
-    i64,
-    f32,
-    f64,
-    Decimal,
     Vec<i16>,
     Vec<i32>,
     Vec<i64>,
-    Vec<f32>,
-    Vec<f64>,
-    Vec<Decimal>,
-    bool,
-    &'r str,
-    Vec<u8>,
+    Vec<u32>,
+    Vec<String>,
+    Vec<Uuid>,
+    // borrowed from the row buffer, so a large bytea can be streamed out through its `Read`
+    // impl without another copy
+    &'r [u8],
     NaiveTime,
     NaiveDateTime,
-    DateTime<Utc>,
     NaiveDate,
     Uuid,
-    Value,
+    PostgresInterval,
+    Vec<NaiveTime>,
+    Vec<PostgresInterval>,
+    PgSparseVec,
     HashMap<String, Option<String>>,
 );
+
+#[cfg(feature = "time")]
+impl_produce!(
+    time::OffsetDateTime,
+    time::PrimitiveDateTime,
+    time::Date,
+    time::Time,
+);
+
+impl<'r, 'a> Produce<'r, u8> for PostgresRawSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> u8 {
+        let (ridx, cidx) = self.next_loc()?;
+        let row = &self.rowbuf[ridx];
+        let val: i8 = row.try_get(cidx)?;
+        val as u8
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<u8>> for PostgresRawSourceParser<'a> {
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> Option<u8> {
+        let (ridx, cidx) = self.next_loc()?;
+        let row = &self.rowbuf[ridx];
+        let val: Option<i8> = row.try_get(cidx)?;
+        val.map(|v| v as u8)
+    }
+}
+
+// a produced value as json, for `produce_json_row`
+trait ToJson {
+    fn to_json(self) -> Value;
+}
+
+macro_rules! impl_to_json_from {
+    ($($t: ty,)+) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(self) -> Value {
+                    Value::from(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_to_json_from!(bool, i16, i32, i64, u32, f32, f64, String,);
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(self) -> Value {
+        Value::Array(self.into_iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl ToJson for &str {
+    fn to_json(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+// as a string, a json number would be read back as a float by most parsers
+impl ToJson for Decimal {
+    fn to_json(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJson for Vec<u8> {
+    fn to_json(self) -> Value {
+        Value::String(base64::encode(self))
+    }
+}
+
+impl ToJson for NaiveTime {
+    fn to_json(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJson for NaiveDate {
+    fn to_json(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJson for NaiveDateTime {
+    fn to_json(self) -> Value {
+        Value::String(self.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+    }
+}
+
+impl ToJson for DateTime<Utc> {
+    fn to_json(self) -> Value {
+        Value::String(self.to_rfc3339())
+    }
+}
+
+impl ToJson for DateTime<Tz> {
+    fn to_json(self) -> Value {
+        Value::String(self.to_rfc3339())
+    }
+}
+
+impl ToJson for Uuid {
+    fn to_json(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJson for Value {
+    fn to_json(self) -> Value {
+        self
+    }
+}
+
+impl ToJson for PostgresInterval {
+    fn to_json(self) -> Value {
+        serde_json::json!({
+            "months": self.months,
+            "days": self.days,
+            "microseconds": self.microseconds,
+        })
+    }
+}
+
+impl<T: ToJson> ToJson for PgRange<T> {
+    fn to_json(self) -> Value {
+        serde_json::json!({
+            "lower": self.lower.map_or(Value::Null, ToJson::to_json),
+            "upper": self.upper.map_or(Value::Null, ToJson::to_json),
+            "lower_inc": self.lower_inc,
+            "upper_inc": self.upper_inc,
+        })
+    }
+}
+
+impl ToJson for PgSparseVec {
+    fn to_json(self) -> Value {
+        serde_json::json!({
+            "dim": self.dim,
+            "elements": self.elements,
+        })
+    }
+}
+
+impl ToJson for HashMap<String, Option<String>> {
+    fn to_json(self) -> Value {
+        Value::Object(
+            self.into_iter()
+                .map(|(k, v)| (k, v.map_or(Value::Null, Value::String)))
+                .collect(),
+        )
+    }
+}
+
+// the next value of a parser as json, NULL as json null
+#[throws(PostgresSourceError)]
+fn produce_json<'r, P, T>(parser: &'r mut P) -> Value
+where
+    P: Produce<'r, Option<T>, Error = PostgresSourceError>,
+    T: ToJson,
+{
+    parser.produce()?.map_or(Value::Null, ToJson::to_json)
+}
+
+macro_rules! impl_json_row {
+    ($($p: ident,)+) => {
+        $(
+            impl<'a> $p<'a> {
+                /// Read the next row as one json value per column, NULL as json null, for a
+                /// generic export that does not know the columns beforehand. Each column is
+                /// produced as the rust type of its `PostgresTypeSystem` and converted from it:
+                /// numbers as numbers except numerics (strings, to keep their precision), bytea
+                /// as base64, dates and timestamps as ISO 8601 strings, arrays as arrays and
+                /// intervals, ranges and sparse vectors as objects of their fields. Call it at
+                /// the start of a row, not after a `produce` of some of its columns.
+                #[throws(PostgresSourceError)]
+                pub fn produce_json_row(&mut self) -> Vec<Value> {
+                    use PostgresTypeSystem::*;
+                    let mut row = Vec::with_capacity(self.ncols);
+                    for cidx in 0..self.ncols {
+                        let ty = self.schema[cidx];
+                        row.push(match ty {
+                            Bool(_) => produce_json::<_, bool>(self)?,
+                            Bit(_) | BoolArray(_) => produce_json::<_, Vec<bool>>(self)?,
+                            Float4(_) => produce_json::<_, f32>(self)?,
+                            Float8(_) => produce_json::<_, f64>(self)?,
+                            Numeric(_) | Money(_) => produce_json::<_, Decimal>(self)?,
+                            Int2(_) => produce_json::<_, i16>(self)?,
+                            Int4(_) => produce_json::<_, i32>(self)?,
+                            Int8(_) => produce_json::<_, i64>(self)?,
+                            Oid(_) => produce_json::<_, u32>(self)?,
+                            Float4Array(_) | Vector(_) => produce_json::<_, Vec<f32>>(self)?,
+                            Float8Array(_) => produce_json::<_, Vec<f64>>(self)?,
+                            NumericArray(_) | MoneyArray(_) => {
+                                produce_json::<_, Vec<Decimal>>(self)?
+                            }
+                            Int2Array(_) | Int2Vector(_) => produce_json::<_, Vec<i16>>(self)?,
+                            Int4Array(_) => produce_json::<_, Vec<i32>>(self)?,
+                            Int8Array(_) => produce_json::<_, Vec<i64>>(self)?,
+                            TextArray(_) => produce_json::<_, Vec<String>>(self)?,
+                            UUIDArray(_) => produce_json::<_, Vec<Uuid>>(self)?,
+                            OidArray(_) | OidVector(_) => produce_json::<_, Vec<u32>>(self)?,
+                            SparseVec(_) => produce_json::<_, PgSparseVec>(self)?,
+                            Date(_) => produce_json::<_, NaiveDate>(self)?,
+                            // the byte as its character, as every protocol reads it
+                            Char(_) => Produce::<Option<u8>>::produce(self)?
+                                .map_or(Value::Null, |b| Value::String((b as char).to_string())),
+                            BpChar(_) | VarChar(_) | Text(_) | Name(_) | Enum(_) | NodeTree(_)
+                            | Xml(_) | Unsupported(_) => produce_json::<_, &str>(self)?,
+                            Geometry(_) => produce_json::<_, String>(self)?,
+                            ByteA(_) => produce_json::<_, Vec<u8>>(self)?,
+                            Time(_) => produce_json::<_, NaiveTime>(self)?,
+                            Interval(_) => produce_json::<_, PostgresInterval>(self)?,
+                            TimeArray(_) => produce_json::<_, Vec<NaiveTime>>(self)?,
+                            IntervalArray(_) => produce_json::<_, Vec<PostgresInterval>>(self)?,
+                            Timestamp(_) => produce_json::<_, NaiveDateTime>(self)?,
+                            TimestampTz(_) => produce_json::<_, DateTime<Tz>>(self)?,
+                            UUID(_) => produce_json::<_, Uuid>(self)?,
+                            JSON(_) | JSONB(_) => produce_json::<_, Value>(self)?,
+                            RecordArray(_) => produce_json::<_, Vec<Value>>(self)?,
+                            // rejected by `fetch_metadata` for the protocols that cannot read it
+                            HSTORE(_) => {
+                                produce_json::<_, HashMap<String, Option<String>>>(self)?
+                            }
+                            Int4MultiRange(_) => produce_json::<_, Vec<PgRange<i32>>>(self)?,
+                            Int8MultiRange(_) => produce_json::<_, Vec<PgRange<i64>>>(self)?,
+                            DateMultiRange(_) => {
+                                produce_json::<_, Vec<PgRange<NaiveDate>>>(self)?
+                            }
+                            TsMultiRange(_) => {
+                                produce_json::<_, Vec<PgRange<NaiveDateTime>>>(self)?
+                            }
+                            TstzMultiRange(_) => {
+                                produce_json::<_, Vec<PgRange<DateTime<Utc>>>>(self)?
+                            }
+                        });
+                    }
+                    row
+                }
+            }
+        )+
+    };
+}
+
+impl_json_row!(
+    PostgresBinarySourcePartitionParser,
+    PostgresCSVSourceParser,
+    PostgresRawSourceParser,
+);
+
+macro_rules! impl_skip_rows {
+    ($($p: ident,)+) => {
+        $(
+            impl<'a> $p<'a> {
+                /// The number of rows this parser dropped under `DecodeErrorPolicy::SkipRow`.
+                pub fn skipped_rows(&self) -> usize {
+                    self.skipped_rows
+                }
+
+                // drop the buffered rows with a value that cannot be produced as the type of its
+                // column, under `DecodeErrorPolicy::SkipRow`. The rows are already in memory, so
+                // any error producing them is one of decoding.
+                fn skip_undecodable_rows(&mut self, is_last: bool) {
+                    let report = match &self.skip_rows {
+                        Some(report) if self.ncols > 0 => report.clone(),
+                        _ => return,
+                    };
+                    let mut decodes = Vec::with_capacity(self.rowbuf.len());
+                    for ridx in 0..self.rowbuf.len() {
+                        self.current_row = ridx;
+                        self.current_col = 0;
+                        decodes.push(self.produce_json_row().is_ok());
+                    }
+                    let mut decodes = decodes.into_iter();
+                    let nrows = self.rowbuf.len();
+                    self.rowbuf.retain(|_| decodes.next().unwrap_or(true));
+                    let skipped = nrows - self.rowbuf.len();
+                    self.skipped_rows += skipped;
+                    report.skipped_rows.fetch_add(skipped, Ordering::Relaxed);
+                    if is_last && self.skipped_rows > 0 {
+                        warn!(
+                            "skipped {} of the {} rows of {} that could not be decoded",
+                            self.skipped_rows, self.nrows_read, self.query
+                        );
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_skip_rows!(
+    PostgresBinarySourcePartitionParser,
+    PostgresCSVSourceParser,
+    PostgresRawSourceParser,
+);
+
+/// The parser of the protocol `AutoProtocol` picked for a partition.
+pub enum PostgresAutoSourceParser<'a> {
+    Binary(PostgresBinarySourcePartitionParser<'a>),
+    Cursor(PostgresRawSourceParser<'a>),
+}
+
+impl<'a> PartitionParser<'a> for PostgresAutoSourceParser<'a> {
+    type TypeSystem = PostgresTypeSystem;
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn fetch_next(&mut self) -> (usize, bool) {
+        match self {
+            PostgresAutoSourceParser::Binary(parser) => parser.fetch_next()?,
+            PostgresAutoSourceParser::Cursor(parser) => parser.fetch_next()?,
+        }
+    }
+}
+
+impl<'a> PostgresAutoSourceParser<'a> {
+    /// See `PostgresBinarySourcePartitionParser::skipped_rows`.
+    pub fn skipped_rows(&self) -> usize {
+        match self {
+            PostgresAutoSourceParser::Binary(parser) => parser.skipped_rows(),
+            PostgresAutoSourceParser::Cursor(parser) => parser.skipped_rows(),
+        }
+    }
+
+    /// See `PostgresBinarySourcePartitionParser::produce_json_row`.
+    #[throws(PostgresSourceError)]
+    pub fn produce_json_row(&mut self) -> Vec<Value> {
+        match self {
+            PostgresAutoSourceParser::Binary(parser) => parser.produce_json_row()?,
+            PostgresAutoSourceParser::Cursor(parser) => parser.produce_json_row()?,
+        }
+    }
+}
+
+impl<'r, 'a, T> Produce<'r, T> for PostgresAutoSourceParser<'a>
+where
+    PostgresBinarySourcePartitionParser<'a>: Produce<'r, T, Error = PostgresSourceError>,
+    PostgresRawSourceParser<'a>: Produce<'r, T, Error = PostgresSourceError>,
+{
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn produce(&'r mut self) -> T {
+        match self {
+            PostgresAutoSourceParser::Binary(parser) => Produce::<T>::produce(parser)?,
+            PostgresAutoSourceParser::Cursor(parser) => Produce::<T>::produce(parser)?,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Decoding of the PostGIS `geometry` and `geography` types into `geo_types`.
+
+use geo_types::{
+    Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon, Point,
+    Polygon,
+};
+use postgres::types::{FromSql, Type};
+use std::convert::TryInto;
+use std::error::Error;
+
+type DecodeResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+// the flags PostGIS sets in the high bits of the type of an EWKB geometry
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+/// A PostGIS `geometry` or `geography`, decoded from the EWKB it is sent as in the binary format
+/// and printed as (in hex) in the text one. `geo_types` is 2D, so the Z and M of the coordinates
+/// are dropped, as is the SRID.
+pub struct PostgresGeometry(pub Geometry<f64>);
+
+impl<'a> FromSql<'a> for PostgresGeometry {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> DecodeResult<Self> {
+        Ok(PostgresGeometry(decode_ewkb(raw)?))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "geometry" || ty.name() == "geography"
+    }
+}
+
+/// Decode a geometry from its EWKB, or from plain (ISO) WKB, which it extends.
+pub fn decode_ewkb(mut raw: &[u8]) -> DecodeResult<Geometry<f64>> {
+    let geometry = read_geometry(&mut raw)?;
+    if !raw.is_empty() {
+        return Err(format!("{} bytes left after the wkb geometry", raw.len()).into());
+    }
+    Ok(geometry)
+}
+
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> DecodeResult<&'a [u8]> {
+    if buf.len() < n {
+        return Err("invalid message length: wkb too short".into());
+    }
+    let (v, rest) = buf.split_at(n);
+    *buf = rest;
+    Ok(v)
+}
+
+// the byte order of a geometry and the number of values of its coordinates
+struct Header {
+    little_endian: bool,
+    dims: usize,
+}
+
+impl Header {
+    fn read_u32(&self, buf: &mut &[u8]) -> DecodeResult<u32> {
+        let v = take(buf, 4)?.try_into()?;
+        Ok(match self.little_endian {
+            true => u32::from_le_bytes(v),
+            false => u32::from_be_bytes(v),
+        })
+    }
+
+    fn read_f64(&self, buf: &mut &[u8]) -> DecodeResult<f64> {
+        let v = take(buf, 8)?.try_into()?;
+        Ok(match self.little_endian {
+            true => f64::from_le_bytes(v),
+            false => f64::from_be_bytes(v),
+        })
+    }
+
+    fn read_coord(&self, buf: &mut &[u8]) -> DecodeResult<(f64, f64)> {
+        let x = self.read_f64(buf)?;
+        let y = self.read_f64(buf)?;
+        for _ in 2..self.dims {
+            self.read_f64(buf)?;
+        }
+        Ok((x, y))
+    }
+
+    fn read_line_string(&self, buf: &mut &[u8]) -> DecodeResult<LineString<f64>> {
+        let n = self.read_u32(buf)?;
+        (0..n)
+            .map(|_| self.read_coord(buf))
+            .collect::<DecodeResult<Vec<_>>>()
+            .map(LineString::from)
+    }
+
+    fn read_polygon(&self, buf: &mut &[u8]) -> DecodeResult<Polygon<f64>> {
+        let n = self.read_u32(buf)?;
+        let mut rings = (0..n)
+            .map(|_| self.read_line_string(buf))
+            .collect::<DecodeResult<Vec<_>>>()?
+            .into_iter();
+        let exterior = rings
+            .next()
+            .unwrap_or_else(|| LineString::from(Vec::<(f64, f64)>::new()));
+        Ok(Polygon::new(exterior, rings.collect()))
+    }
+
+    // the members of a multi geometry or collection, each with a header of its own
+    fn read_members(&self, buf: &mut &[u8]) -> DecodeResult<Vec<Geometry<f64>>> {
+        let n = self.read_u32(buf)?;
+        (0..n).map(|_| read_geometry(buf)).collect()
+    }
+}
+
+// a geometry as its byte order (0 for big endian, 1 for little endian), its type and its
+// body. The type has the Z, M and SRID flags of EWKB, and the SRID follows it if flagged. ISO
+// WKB adds 1000 to the type for Z, 2000 for M and 3000 for both instead.
+fn read_geometry(buf: &mut &[u8]) -> DecodeResult<Geometry<f64>> {
+    let little_endian = match take(buf, 1)?[0] {
+        0 => false,
+        1 => true,
+        v => return Err(format!("invalid byte order {} in wkb", v).into()),
+    };
+    let mut header = Header {
+        little_endian,
+        dims: 2,
+    };
+    let ty = header.read_u32(buf)?;
+    if ty & EWKB_SRID != 0 {
+        header.read_u32(buf)?;
+    }
+    header.dims += (ty & EWKB_Z != 0) as usize + (ty & EWKB_M != 0) as usize;
+    let ty = ty & !(EWKB_Z | EWKB_M | EWKB_SRID);
+    header.dims += match ty / 1000 {
+        0 => 0,
+        1 | 2 => 1,
+        3 => 2,
+        _ => return Err(format!("invalid geometry type {} in wkb", ty).into()),
+    };
+
+    Ok(match ty % 1000 {
+        1 => Geometry::Point(Point::from(header.read_coord(buf)?)),
+        2 => Geometry::LineString(header.read_line_string(buf)?),
+        3 => Geometry::Polygon(header.read_polygon(buf)?),
+        4 => Geometry::MultiPoint(MultiPoint(
+            header
+                .read_members(buf)?
+                .into_iter()
+                .map(|g| {
+                    g.try_into()
+                        .map_err(|_| "a multipoint member is not a point")
+                })
+                .collect::<Result<_, _>>()?,
+        )),
+        5 => Geometry::MultiLineString(MultiLineString(
+            header
+                .read_members(buf)?
+                .into_iter()
+                .map(|g| {
+                    g.try_into()
+                        .map_err(|_| "a multilinestring member is not a linestring")
+                })
+                .collect::<Result<_, _>>()?,
+        )),
+        6 => Geometry::MultiPolygon(MultiPolygon(
+            header
+                .read_members(buf)?
+                .into_iter()
+                .map(|g| {
+                    g.try_into()
+                        .map_err(|_| "a multipolygon member is not a polygon")
+                })
+                .collect::<Result<_, _>>()?,
+        )),
+        7 => Geometry::GeometryCollection(GeometryCollection(header.read_members(buf)?)),
+        v => return Err(format!("unsupported geometry type {} in wkb", v).into()),
+    })
+}
@@ -0,0 +1,461 @@
+//! Forward the connections of a pool to the database through an SSH bastion, for databases that
+//! are not reachable directly.
+
+use crate::sources::postgres::errors::PostgresSourceError;
+use anyhow::anyhow;
+use fehler::{throw, throws};
+use log::{debug, warn};
+use ssh2::{Channel, CheckResult, KnownHostFileKind, Session};
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const BUFFER_SIZE: usize = 16 * 1024;
+// bounds the blocking calls of the session, so that a bastion gone silent fails them
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+// to name the socket directory of each tunnel of the process
+static NEXT_TUNNEL: AtomicUsize = AtomicUsize::new(0);
+
+/// The settings of a tunnel, read from the url by `rewrite_conn_args` without dialing anything.
+/// `PostgresSource::new_with_pool_config` opens it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshTunnelConfig {
+    /// Address of the bastion (`sshhost`, `sshport`).
+    pub host: String,
+    pub port: u16,
+    /// User to log in to the bastion as (`sshuser`).
+    pub user: String,
+    /// Location of the private key to authenticate with (`sshkey`).
+    pub key: PathBuf,
+    /// The OpenSSH known_hosts file the key of the bastion must be in (`sshknownhosts`),
+    /// `~/.ssh/known_hosts` by default.
+    pub known_hosts: PathBuf,
+    /// The database as the bastion reaches it, the host and port of the url.
+    pub remote_host: String,
+    pub remote_port: u16,
+    /// The directory of the unix socket the pool connects to, which `rewrite_conn_args` points
+    /// the config at. It is created readable by the current user only when the tunnel opens,
+    /// so no other user of the machine can reach the database through it.
+    pub socket_dir: PathBuf,
+}
+
+impl SshTunnelConfig {
+    // a socket directory no other tunnel of the process uses
+    pub(crate) fn new_socket_dir() -> PathBuf {
+        env::temp_dir().join(format!(
+            "connectorx-ssh-{}-{}",
+            process::id(),
+            NEXT_TUNNEL.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    // the session, and its socket
+    #[throws(PostgresSourceError)]
+    fn connect(&self) -> (Session, TcpStream) {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port))?;
+        let socket = tcp.try_clone()?;
+        let mut session = Session::new()?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(SESSION_TIMEOUT.as_millis() as u32);
+        session.handshake()?;
+        self.check_host_key(&session)?;
+        session.userauth_pubkey_file(&self.user, None, &self.key, None)?;
+        if !session.authenticated() {
+            throw!(anyhow!(
+                "ssh authentication to {}@{} failed",
+                self.user,
+                self.host
+            ));
+        }
+        (session, socket)
+    }
+
+    // Fail unless the bastion presents a key of `known_hosts` for its name, so that no one in
+    // between can pose as it and get the credentials of the database. Nothing is added to the
+    // file, a new bastion has to be added to it first, e.g. with `ssh-keyscan`.
+    #[throws(PostgresSourceError)]
+    fn check_host_key(&self, session: &Session) {
+        let (key, _) = session
+            .host_key()
+            .ok_or_else(|| anyhow!("the ssh bastion {} sent no host key", self.host))?;
+        let mut known_hosts = session.known_hosts()?;
+        known_hosts.read_file(&self.known_hosts, KnownHostFileKind::OpenSSH)?;
+        match known_hosts.check_port(&self.host, self.port, key) {
+            CheckResult::Match => {}
+            CheckResult::NotFound => throw!(anyhow!(
+                "the host key of the ssh bastion {} is not in {}",
+                self.host,
+                self.known_hosts.display()
+            )),
+            CheckResult::Mismatch => throw!(anyhow!(
+                "the host key of the ssh bastion {} does not match the one in {}",
+                self.host,
+                self.known_hosts.display()
+            )),
+            CheckResult::Failure => throw!(anyhow!(
+                "could not check the host key of the ssh bastion {}",
+                self.host
+            )),
+        }
+    }
+
+    /// Log in to the bastion and listen on the socket of `socket_dir`, forwarding every
+    /// connection made to it to the database. All of them go through a single SSH session,
+    /// which is opened again for the next connection if the bastion drops it. The tunnel is
+    /// closed when the returned handle is dropped.
+    #[throws(PostgresSourceError)]
+    pub(crate) fn open(&self) -> SshTunnel {
+        let (events, received) = mpsc::channel();
+        let mut pump = Pump {
+            config: self.clone(),
+            session: None,
+            socket: None,
+            generation: 0,
+            resume: None,
+            watching: false,
+            conns: HashMap::new(),
+            next_conn: 0,
+            events: events.clone(),
+        };
+        // fail early on a wrong bastion or key rather than on the first pooled connection
+        pump.connect()?;
+
+        // fails if the directory is there already, rather than use one someone else made
+        DirBuilder::new().mode(0o700).create(&self.socket_dir)?;
+        let socket = self
+            .socket_dir
+            .join(format!(".s.PGSQL.{}", self.remote_port));
+        let listener = match UnixListener::bind(&socket) {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&self.socket_dir);
+                throw!(e);
+            }
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let accept = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::Relaxed) {
+                        let _ = events.send(Event::Stop);
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => {
+                            if events.send(Event::Accepted(stream)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => warn!("ssh tunnel could not accept a connection: {}", e),
+                    }
+                }
+            })
+        };
+        let pump = thread::spawn(move || pump.run(received));
+        debug!(
+            "forwarding {} to {}:{} through {}",
+            socket.display(),
+            self.remote_host,
+            self.remote_port,
+            self.host
+        );
+        SshTunnel {
+            socket_dir: self.socket_dir.clone(),
+            socket,
+            stop,
+            accept: Some(accept),
+            pump: Some(pump),
+        }
+    }
+}
+
+/// An open tunnel, see `SshTunnelConfig::open`. Dropping it stops the listener, closes the
+/// forwarded connections and the session, and removes the socket directory.
+pub(crate) struct SshTunnel {
+    socket_dir: PathBuf,
+    socket: PathBuf,
+    stop: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+    pump: Option<JoinHandle<()>>,
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake the listener up to see `stop` and stop the pump
+        let _ = UnixStream::connect(&self.socket);
+        if let Some(accept) = self.accept.take() {
+            let _ = accept.join();
+        }
+        if let Some(pump) = self.pump.take() {
+            let _ = pump.join();
+        }
+        let _ = fs::remove_dir_all(&self.socket_dir);
+    }
+}
+
+// what the pump of a tunnel is woken up by
+enum Event {
+    Accepted(UnixStream),
+    // bytes a forwarded connection sent, and the end of its input
+    Sent(usize, Vec<u8>),
+    Closed(usize),
+    // the socket of the session of a generation has data, or is closed
+    Readable(usize),
+    Disconnected(usize),
+    Stop,
+}
+
+// The thread that owns the session and moves the bytes of every forwarded connection. It
+// blocks on its events, fed by a thread reading each connection and one watching the socket of
+// the session, so it never polls. The session is non-blocking for reads, so that one channel
+// with nothing to read does not hold up the others, and blocking for the rest.
+struct Pump {
+    config: SshTunnelConfig,
+    session: Option<Session>,
+    // the socket of the session, to close it for the thread watching it too
+    socket: Option<TcpStream>,
+    // of the session, to tell its events from those of the sessions it replaced
+    generation: usize,
+    // lets the watcher of the socket look at it again once the session read what it had, and
+    // whether it is looking. Without a channel to read, the session leaves the socket as it is,
+    // so the watcher waits for the next connection.
+    resume: Option<Sender<()>>,
+    watching: bool,
+    conns: HashMap<usize, (Channel, UnixStream)>,
+    next_conn: usize,
+    events: Sender<Event>,
+}
+
+impl Pump {
+    fn run(mut self, received: Receiver<Event>) {
+        for event in received {
+            match event {
+                Event::Accepted(stream) => {
+                    if let Err(e) = self.accept(stream) {
+                        warn!(
+                            "ssh tunnel to {}:{} could not forward a connection: {}",
+                            self.config.remote_host, self.config.remote_port, e
+                        );
+                    }
+                }
+                Event::Sent(id, data) => {
+                    if let Err(e) = self.send(id, &data) {
+                        warn!(
+                            "ssh tunnel closed a connection it could not write to: {}",
+                            e
+                        );
+                        self.close(id);
+                    }
+                }
+                Event::Closed(id) => self.close(id),
+                Event::Readable(generation) if generation == self.generation => {
+                    self.watching = false
+                }
+                Event::Disconnected(generation) if generation == self.generation => {
+                    warn!(
+                        "ssh session to {} closed, the next connection opens a new one",
+                        self.config.host
+                    );
+                    self.disconnect();
+                    continue;
+                }
+                Event::Readable(_) | Event::Disconnected(_) => continue,
+                Event::Stop => return,
+            }
+            // after any use of the session, since what it took off its socket meanwhile is in
+            // its buffers and would not wake the watcher up
+            self.receive();
+            if !self.watching && !self.conns.is_empty() {
+                if let Some(resume) = &self.resume {
+                    self.watching = resume.send(()).is_ok();
+                }
+            }
+        }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn connect(&mut self) {
+        let (session, socket) = self.config.connect()?;
+        session.set_blocking(false);
+        let watched = socket.try_clone()?;
+        self.generation += 1;
+        let generation = self.generation;
+        let events = self.events.clone();
+        let (resume, resumed) = mpsc::channel();
+        // peek so that the data is left for the session; wait for the pump to read it before
+        // looking again
+        thread::spawn(move || {
+            let mut byte = [0];
+            loop {
+                match watched.peek(&mut byte) {
+                    Ok(n) if n > 0 => {}
+                    _ => {
+                        let _ = events.send(Event::Disconnected(generation));
+                        return;
+                    }
+                }
+                if events.send(Event::Readable(generation)).is_err() || resumed.recv().is_err() {
+                    return;
+                }
+            }
+        });
+        self.session = Some(session);
+        self.socket = Some(socket);
+        self.resume = Some(resume);
+        self.watching = true;
+    }
+
+    // close every connection, so that the pool replaces them, and the session
+    fn disconnect(&mut self) {
+        for id in self.conns.keys().copied().collect::<Vec<_>>() {
+            self.close(id);
+        }
+        self.session = None;
+        if let Some(socket) = self.socket.take() {
+            let _ = socket.shutdown(Shutdown::Both);
+        }
+        self.resume = None;
+        self.watching = false;
+    }
+
+    #[throws(PostgresSourceError)]
+    fn accept(&mut self, stream: UnixStream) {
+        let channel = match self.open_channel() {
+            Ok(channel) => channel,
+            // the bastion may have dropped a session idle since the last connection, which is
+            // only seen now
+            Err(e) if self.conns.is_empty() && self.session.is_some() => {
+                debug!(
+                    "ssh session to {} is gone ({}), reconnecting",
+                    self.config.host, e
+                );
+                self.disconnect();
+                self.open_channel()?
+            }
+            Err(e) => throw!(e),
+        };
+
+        let id = self.next_conn;
+        self.next_conn += 1;
+        let mut reader = stream.try_clone()?;
+        let events = self.events.clone();
+        thread::spawn(move || {
+            let mut buf = vec![0; BUFFER_SIZE];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => {
+                        let _ = events.send(Event::Closed(id));
+                        return;
+                    }
+                    Ok(n) => {
+                        if events.send(Event::Sent(id, buf[..n].to_vec())).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+        self.conns.insert(id, (channel, stream));
+    }
+
+    #[throws(PostgresSourceError)]
+    fn open_channel(&mut self) -> Channel {
+        if self.session.is_none() {
+            self.connect()?;
+        }
+        let session = self.session.as_ref().expect("connected above");
+        session.set_blocking(true);
+        let channel =
+            session.channel_direct_tcpip(&self.config.remote_host, self.config.remote_port, None);
+        session.set_blocking(false);
+        channel?
+    }
+
+    // No flush: on a channel it discards the unread input.
+    #[throws(PostgresSourceError)]
+    fn send(&mut self, id: usize, data: &[u8]) {
+        let (session, (channel, _)) = match (&self.session, self.conns.get_mut(&id)) {
+            (Some(session), Some(conn)) => (session, conn),
+            _ => return,
+        };
+        session.set_blocking(true);
+        let written = channel.write_all(data);
+        session.set_blocking(false);
+        written?;
+    }
+
+    // move what every channel has to its connection, until none has anything left
+    fn receive(&mut self) {
+        let mut buf = vec![0; BUFFER_SIZE];
+        loop {
+            let mut idle = true;
+            let mut closed = vec![];
+            for (&id, (channel, stream)) in self.conns.iter_mut() {
+                match forward(channel, stream, &mut buf) {
+                    Ok(true) => idle = false,
+                    Ok(false) if channel.eof() => closed.push(id),
+                    Ok(false) => {}
+                    Err(e) => {
+                        debug!("ssh tunnel closed a connection: {}", e);
+                        closed.push(id);
+                    }
+                }
+            }
+            for id in closed {
+                self.close(id);
+            }
+            if idle {
+                break;
+            }
+        }
+    }
+
+    fn close(&mut self, id: usize) {
+        if let Some((mut channel, stream)) = self.conns.remove(&id) {
+            // also ends the thread reading the connection
+            let _ = stream.shutdown(Shutdown::Both);
+            if let Some(session) = &self.session {
+                session.set_blocking(true);
+                let _ = channel.close();
+                session.set_blocking(false);
+            }
+        }
+    }
+}
+
+impl Drop for Pump {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+// write what the channel has to read to the connection, whether there was anything
+fn forward(channel: &mut Channel, stream: &mut UnixStream, buf: &mut [u8]) -> io::Result<bool> {
+    let mut forwarded = false;
+    loop {
+        match channel.read(buf) {
+            Ok(0) => return Ok(forwarded),
+            Ok(n) => {
+                stream.write_all(&buf[..n])?;
+                forwarded = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(forwarded),
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -1,39 +1,78 @@
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use postgres::types::Type;
+use log::warn;
+use postgres::types::{FromSql, Type};
 use rust_decimal::Decimal;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::convert::TryInto;
+use std::error::Error;
+use std::str::FromStr;
 use uuid::Uuid;
 
 #[derive(Copy, Clone, Debug)]
 pub enum PostgresTypeSystem {
     Bool(bool),
+    // a bit string, read as its bits or, for a single bit, as a bool
+    Bit(bool),
     Float4(bool),
     Float8(bool),
     Numeric(bool),
+    // a decimal at the number of fraction digits of the currency of `lc_monetary`
+    Money(bool),
     Int2(bool),
     Int4(bool),
     Int8(bool),
+    Oid(bool),
     Float4Array(bool),
     Float8Array(bool),
     NumericArray(bool),
+    // decimals at the number of fraction digits of the currency of `lc_monetary`
+    MoneyArray(bool),
     Int2Array(bool),
     Int4Array(bool),
     Int8Array(bool),
+    BoolArray(bool),
+    TextArray(bool),
+    UUIDArray(bool),
+    OidArray(bool),
+    Int2Vector(bool),
+    OidVector(bool),
+    Vector(bool),
+    SparseVec(bool),
     Date(bool),
     Char(bool),
     BpChar(bool),
     VarChar(bool),
     Text(bool),
+    Name(bool),
     ByteA(bool),
     Time(bool),
+    Interval(bool),
+    TimeArray(bool),
+    IntervalArray(bool),
     Timestamp(bool),
     TimestampTz(bool),
     UUID(bool),
     JSON(bool),
     JSONB(bool),
     Enum(bool),
+    NodeTree(bool),
+    Xml(bool),
     HSTORE(bool),
+    // a PostGIS geometry or geography, read as the hex of its EWKB as the text format prints it,
+    // or decoded into a `geo_types::Geometry` with the `postgis` feature
+    Geometry(bool),
+    // an array of anonymous records, which has no type to read the fields with. It is read as
+    // json (`to_json`), an array of objects with keys f1, f2...
+    RecordArray(bool),
+    // multiranges (postgres 14+), read as their ranges
+    Int4MultiRange(bool),
+    Int8MultiRange(bool),
+    DateMultiRange(bool),
+    TsMultiRange(bool),
+    TstzMultiRange(bool),
+    // a type with no variant of its own, read as the text CSV prints it
+    Unsupported(bool),
 }
 
 impl_typesystem! {
@@ -42,26 +81,42 @@ impl_typesystem! {
         { Int2 => i16 }
         { Int4 => i32 }
         { Int8 => i64 }
+        { Oid => u32 }
         { Float4 => f32 }
         { Float8 => f64 }
-        { Numeric => Decimal }
-        { Int2Array => Vec<i16> }
+        { Numeric | Money => Decimal }
+        { Int2Array | Int2Vector => Vec<i16> }
         { Int4Array => Vec<i32> }
         { Int8Array => Vec<i64> }
-        { Float4Array => Vec<f32> }
+        { Float4Array | Vector => Vec<f32> }
+        { SparseVec => PgSparseVec }
         { Float8Array => Vec<f64> }
-        { NumericArray => Vec<Decimal> }
+        { NumericArray | MoneyArray => Vec<Decimal> }
+        { BoolArray | Bit => Vec<bool> }
+        { TextArray => Vec<String> }
+        { UUIDArray => Vec<Uuid> }
+        { OidArray | OidVector => Vec<u32> }
         { Bool => bool }
         { Char => i8 }
-        { Text | BpChar | VarChar | Enum => &'r str }
+        { Text | BpChar | VarChar | Name | Enum | NodeTree | Xml | Unsupported => &'r str }
         { ByteA => Vec<u8> }
         { Time => NaiveTime }
+        { Interval => PostgresInterval }
+        { TimeArray => Vec<NaiveTime> }
+        { IntervalArray => Vec<PostgresInterval> }
         { Timestamp => NaiveDateTime }
         { TimestampTz => DateTime<Utc> }
         { Date => NaiveDate }
         { UUID => Uuid }
         { JSON | JSONB => Value }
         { HSTORE => HashMap<String, Option<String>> }
+        { Geometry => String }
+        { RecordArray => Vec<Value> }
+        { Int4MultiRange => Vec<PgRange<i32>> }
+        { Int8MultiRange => Vec<PgRange<i64>> }
+        { DateMultiRange => Vec<PgRange<NaiveDate>> }
+        { TsMultiRange => Vec<PgRange<NaiveDateTime>> }
+        { TstzMultiRange => Vec<PgRange<DateTime<Utc>>> }
     }
 }
 
@@ -72,22 +127,38 @@ impl<'a> From<&'a Type> for PostgresTypeSystem {
             "int2" => Int2(true),
             "int4" => Int4(true),
             "int8" => Int8(true),
+            "oid" => Oid(true),
             "float4" => Float4(true),
             "float8" => Float8(true),
             "numeric" => Numeric(true),
+            "money" => Money(true),
             "_int2" => Int2Array(true),
             "_int4" => Int4Array(true),
             "_int8" => Int8Array(true),
             "_float4" => Float4Array(true),
             "_float8" => Float8Array(true),
             "_numeric" => NumericArray(true),
+            "_money" => MoneyArray(true),
+            "_bool" => BoolArray(true),
+            "_text" | "_varchar" | "_bpchar" => TextArray(true),
+            "_uuid" => UUIDArray(true),
+            "_oid" => OidArray(true),
+            "int2vector" => Int2Vector(true),
+            "oidvector" => OidVector(true),
+            "vector" => Vector(true),
+            "sparsevec" => SparseVec(true),
             "bool" => Bool(true),
+            "bit" | "varbit" => Bit(true),
             "char" => Char(true),
             "text" | "citext" | "ltree" | "lquery" | "ltxtquery" => Text(true),
             "bpchar" => BpChar(true),
             "varchar" => VarChar(true),
+            "name" => Name(true),
             "bytea" => ByteA(true),
             "time" => Time(true),
+            "interval" => Interval(true),
+            "_time" => TimeArray(true),
+            "_interval" => IntervalArray(true),
             "timestamp" => Timestamp(true),
             "timestamptz" => TimestampTz(true),
             "date" => Date(true),
@@ -95,14 +166,715 @@ impl<'a> From<&'a Type> for PostgresTypeSystem {
             "json" => JSON(true),
             "jsonb" => JSONB(true),
             "hstore" => HSTORE(true),
+            "geometry" | "geography" => Geometry(true),
+            "_record" => RecordArray(true),
+            "int4multirange" => Int4MultiRange(true),
+            "int8multirange" => Int8MultiRange(true),
+            "datemultirange" => DateMultiRange(true),
+            "tsmultirange" => TsMultiRange(true),
+            "tstzmultirange" => TstzMultiRange(true),
+            "pg_node_tree" => NodeTree(true),
+            "xml" => Xml(true),
+            // an untyped literal, sent in the same format as text
+            "unknown" => {
+                warn!("reading a column of unknown type as text, cast it to set the type");
+                Text(true)
+            }
             _ => match ty.kind() {
                 postgres::types::Kind::Enum(_) => Enum(true),
-                _ => unimplemented!("{}", ty.name()),
+                _ => Unsupported(true),
             },
         }
     }
 }
 
+/// A postgres `interval`. The three fields are kept apart as months and days have no fixed length.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PostgresInterval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+// binary format: microseconds (int8), days (int4), months (int4)
+impl<'a> FromSql<'a> for PostgresInterval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err("invalid message length: interval size mismatch".into());
+        }
+        Ok(PostgresInterval {
+            microseconds: i64::from_be_bytes(raw[0..8].try_into()?),
+            days: i32::from_be_bytes(raw[8..12].try_into()?),
+            months: i32::from_be_bytes(raw[12..16].try_into()?),
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+impl PostgresInterval {
+    /// The interval as a duration, taking a day as 24 hours. One with months has no fixed
+    /// length, and no duration.
+    pub fn to_duration(&self) -> Option<chrono::Duration> {
+        match self.months {
+            0 => Some(
+                chrono::Duration::days(self.days as i64)
+                    + chrono::Duration::microseconds(self.microseconds),
+            ),
+            _ => None,
+        }
+    }
+
+    /// The interval in microseconds, taking a day as 24 hours and a month as `month_days` days.
+    /// One with months has no length unless `month_days` is given, nor one overflowing an i64.
+    pub fn to_micros(&self, month_days: Option<i64>) -> Option<i64> {
+        let days = match (self.months, month_days) {
+            (0, _) => self.days as i64,
+            (months, Some(month_days)) => (months as i64)
+                .checked_mul(month_days)?
+                .checked_add(self.days as i64)?,
+            (_, None) => return None,
+        };
+        days.checked_mul(86_400_000_000)?
+            .checked_add(self.microseconds)
+    }
+}
+
+// a time as the duration since midnight, or an interval without months as a duration (see
+// `PostgresInterval::to_duration`)
+pub struct PostgresDuration(pub chrono::Duration);
+
+impl<'a> FromSql<'a> for PostgresDuration {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::TIME {
+            true => Ok(PostgresDuration(
+                NaiveTime::from_sql(ty, raw)? - NaiveTime::from_hms(0, 0, 0),
+            )),
+            false => Ok(PostgresDuration(
+                PostgresInterval::from_sql(ty, raw)?
+                    .to_duration()
+                    .ok_or("an interval with months has no fixed duration")?,
+            )),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::TIME || *ty == Type::INTERVAL
+    }
+}
+
+// rust_decimal reads a numeric NaN as zero, so it is caught here first. In the binary format
+// the sign word following ndigits and weight is 0xC000 for NaN. Integer columns are accepted
+// too and converted exactly, rather than through a float, and so is money, an int8 of the smallest
+// unit of its currency. Its number of fraction digits follows `lc_monetary` and is not sent, so it
+// is decoded at 2 here and set to the one the source reads in `fetch_metadata` by the parsers.
+pub enum PostgresNumeric {
+    NaN,
+    Decimal(Decimal),
+}
+
+impl<'a> FromSql<'a> for PostgresNumeric {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let v = if *ty == Type::INT2 {
+            Decimal::from(i16::from_sql(ty, raw)?)
+        } else if *ty == Type::INT4 {
+            Decimal::from(i32::from_sql(ty, raw)?)
+        } else if *ty == Type::INT8 {
+            Decimal::from(i64::from_sql(ty, raw)?)
+        } else if *ty == Type::MONEY {
+            Decimal::new(i64::from_sql(&Type::INT8, raw)?, 2)
+        } else if *ty == Type::FLOAT4 {
+            return float_to_numeric(format!("{:e}", f32::from_sql(ty, raw)?));
+        } else if *ty == Type::FLOAT8 {
+            return float_to_numeric(format!("{:e}", f64::from_sql(ty, raw)?));
+        } else if let Some([0xC0, 0x00]) = raw.get(4..6) {
+            return Ok(PostgresNumeric::NaN);
+        } else {
+            Decimal::from_sql(ty, raw)?
+        };
+        Ok(PostgresNumeric::Decimal(v))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        [
+            Type::NUMERIC,
+            Type::INT2,
+            Type::INT4,
+            Type::INT8,
+            Type::MONEY,
+            Type::FLOAT4,
+            Type::FLOAT8,
+        ]
+        .contains(ty)
+    }
+}
+
+// a float as the shortest decimal that reads back as the same float, e.g. 0.1 rather than the
+// 0.1000000000000000055511151231257827 it is stored as. So the decimal is only as precise as the
+// float was, and one beyond the range of a decimal (e.g. 1e30) cannot be read.
+fn float_to_numeric(s: String) -> Result<PostgresNumeric, Box<dyn Error + Sync + Send>> {
+    match s.as_str() {
+        "NaN" => Ok(PostgresNumeric::NaN),
+        "inf" | "-inf" => Err("an infinite float cannot be represented as a decimal".into()),
+        s => Ok(PostgresNumeric::Decimal(Decimal::from_scientific(s)?)),
+    }
+}
+
+// the text form of a numeric, as `numeric_out` prints it, formatted from the binary format: the
+// number of base 10000 digits, the weight of the first one, the sign and the display scale
+pub struct PostgresNumericText(pub String);
+
+impl<'a> FromSql<'a> for PostgresNumericText {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() < 8 {
+            return Err("invalid message length: numeric header missing".into());
+        }
+        let ndigits = i16::from_be_bytes(raw[0..2].try_into()?) as usize;
+        let weight = i16::from_be_bytes(raw[2..4].try_into()?) as isize;
+        let sign = u16::from_be_bytes(raw[4..6].try_into()?);
+        let dscale = u16::from_be_bytes(raw[6..8].try_into()?) as usize;
+        if raw.len() != 8 + ndigits * 2 {
+            return Err("invalid message length: numeric size mismatch".into());
+        }
+        let digits: Vec<i16> = raw[8..]
+            .chunks_exact(2)
+            .map(|d| i16::from_be_bytes([d[0], d[1]]))
+            .collect();
+        let digit = |i: isize| match i {
+            i if i < 0 => 0,
+            i => digits.get(i as usize).copied().unwrap_or(0),
+        };
+
+        let mut s = match sign {
+            0x0000 => String::new(),
+            0x4000 => "-".to_string(),
+            0xC000 => return Ok(PostgresNumericText("NaN".to_string())),
+            0xD000 => return Ok(PostgresNumericText("Infinity".to_string())),
+            0xF000 => return Ok(PostgresNumericText("-Infinity".to_string())),
+            _ => return Err(format!("invalid numeric sign {:#06x}", sign).into()),
+        };
+        match weight < 0 {
+            true => s.push('0'),
+            false => {
+                s.push_str(&digit(0).to_string());
+                for i in 1..=weight {
+                    s.push_str(&format!("{:04}", digit(i)));
+                }
+            }
+        }
+        if dscale > 0 {
+            let mut frac = String::new();
+            let mut i = weight + 1;
+            while frac.len() < dscale {
+                frac.push_str(&format!("{:04}", digit(i)));
+                i += 1;
+            }
+            frac.truncate(dscale);
+            s.push('.');
+            s.push_str(&frac);
+        }
+        Ok(PostgresNumericText(s))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+// a float, or a numeric rounded to the nearest float (ties to even) from its exact text. A
+// numeric beyond the range of the float is read as an infinity, and a NaN as NaN.
+pub struct PostgresFloat<T>(pub T);
+
+impl<'a, T: FromSql<'a> + FromStr> FromSql<'a> for PostgresFloat<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::NUMERIC {
+            true => {
+                let text = PostgresNumericText::from_sql(ty, raw)?.0;
+                match text.parse() {
+                    Ok(v) => Ok(PostgresFloat(v)),
+                    Err(_) => Err(format!("cannot read numeric {} as a float", text).into()),
+                }
+            }
+            false => Ok(PostgresFloat(T::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC || T::accepts(ty)
+    }
+}
+
+// the exact text of a numeric (see `PostgresNumericText`), or the bytes of a bytea to be encoded
+// as text. A PostGIS geometry or geography is the hex of its EWKB, in upper case like the text
+// format prints it.
+pub enum PostgresStringLike {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl<'a> FromSql<'a> for PostgresStringLike {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::BYTEA {
+            true => Ok(PostgresStringLike::Bytes(raw.to_vec())),
+            false if is_geometry(ty) => Ok(PostgresStringLike::Text(hex::encode_upper(raw))),
+            false => Ok(PostgresStringLike::Text(
+                PostgresNumericText::from_sql(ty, raw)?.0,
+            )),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BYTEA || is_geometry(ty) || PostgresNumericText::accepts(ty)
+    }
+}
+
+fn is_geometry(ty: &Type) -> bool {
+    ty.name() == "geometry" || ty.name() == "geography"
+}
+
+// a bytea, or the 16 bytes of a uuid in network order. Both are sent as is in the binary format.
+// A uuid[] is the bytes of its elements one after the other, 16 per element, and cannot have
+// null elements.
+pub struct PostgresBytes(pub Vec<u8>);
+
+impl<'a> FromSql<'a> for PostgresBytes {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::UUID_ARRAY {
+            true => Ok(PostgresBytes(
+                Vec::<Uuid>::from_sql(ty, raw)?
+                    .iter()
+                    .flat_map(|v| *v.as_bytes())
+                    .collect(),
+            )),
+            false => Ok(PostgresBytes(raw.to_vec())),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BYTEA || *ty == Type::UUID || *ty == Type::UUID_ARRAY
+    }
+}
+
+// a text column, or an xml one as its text. An xml is sent as the text itself.
+pub struct PostgresText<'a>(pub &'a str);
+
+impl<'a> FromSql<'a> for PostgresText<'a> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match ty.name() {
+            "xml" => Ok(PostgresText(<&str>::from_sql(&Type::TEXT, raw)?)),
+            _ => Ok(PostgresText(<&str>::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "xml" || <&str>::accepts(ty)
+    }
+}
+
+// a json or jsonb, an hstore as an object of its values, which are strings or null, or an xml
+// as a string of its text
+pub struct PostgresJson(pub Value);
+
+impl<'a> FromSql<'a> for PostgresJson {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match ty.name() {
+            "xml" => Ok(PostgresJson(Value::String(
+                PostgresText::from_sql(ty, raw)?.0.to_string(),
+            ))),
+            "hstore" => {
+                let map = HashMap::<String, Option<String>>::from_sql(ty, raw)?;
+                Ok(PostgresJson(Value::Object(
+                    map.into_iter()
+                        .map(|(k, v)| (k, v.map_or(Value::Null, Value::String)))
+                        .collect(),
+                )))
+            }
+            _ => Ok(PostgresJson(Value::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "hstore" || ty.name() == "xml" || <Value as FromSql>::accepts(ty)
+    }
+}
+
+// microseconds from 1970-01-01 to 2000-01-01, the epoch of postgres timestamps
+const POSTGRES_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+// an int8, or a timestamp or timestamptz as microseconds since the unix epoch. Both are sent as
+// microseconds since 2000-01-01, an infinite one as the largest or smallest int8.
+pub struct PostgresInt8(pub i64);
+
+impl<'a> FromSql<'a> for PostgresInt8 {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let v = i64::from_sql(&Type::INT8, raw)?;
+        match (*ty == Type::INT8, v) {
+            (true, v) => Ok(PostgresInt8(v)),
+            (false, i64::MAX | i64::MIN) => {
+                Err("an infinite timestamp has no epoch microseconds".into())
+            }
+            (false, v) => Ok(PostgresInt8(v + POSTGRES_EPOCH_MICROS)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        [Type::INT8, Type::TIMESTAMP, Type::TIMESTAMPTZ].contains(ty)
+    }
+}
+
+// an int2, int4 or int8 widened to an int8, for the resume key of `PostgresSource::set_resume_key`
+pub struct PostgresResumeKey(pub i64);
+
+impl<'a> FromSql<'a> for PostgresResumeKey {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::INT2 => Ok(PostgresResumeKey(i16::from_sql(ty, raw)?.into())),
+            Type::INT4 => Ok(PostgresResumeKey(i32::from_sql(ty, raw)?.into())),
+            _ => Ok(PostgresResumeKey(i64::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        [Type::INT2, Type::INT4, Type::INT8].contains(ty)
+    }
+}
+
+// an integer that also accepts a bool column, read as 1 or 0
+pub struct PostgresInteger<T>(pub T);
+
+impl<'a, T: FromSql<'a> + From<bool>> FromSql<'a> for PostgresInteger<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::BOOL {
+            true => Ok(PostgresInteger(T::from(bool::from_sql(ty, raw)?))),
+            false => Ok(PostgresInteger(T::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BOOL || T::accepts(ty)
+    }
+}
+
+// days from 1970-01-01 to 2000-01-01, the epoch of postgres dates
+const POSTGRES_EPOCH_DAYS: i32 = 10_957;
+
+// an int4 (or a bool, see `PostgresInteger`), or a date as days since the unix epoch. A date is
+// sent as days since 2000-01-01, an infinite one as the largest or smallest int4.
+pub struct PostgresInt4(pub i32);
+
+impl<'a> FromSql<'a> for PostgresInt4 {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if *ty != Type::DATE {
+            return Ok(PostgresInt4(PostgresInteger::<i32>::from_sql(ty, raw)?.0));
+        }
+        match i32::from_sql(&Type::INT4, raw)? {
+            i32::MAX | i32::MIN => Err("an infinite date has no epoch days".into()),
+            v => Ok(PostgresInt4(v + POSTGRES_EPOCH_DAYS)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::DATE || PostgresInteger::<i32>::accepts(ty)
+    }
+}
+
+/// A timestamptz, or a timestamp taken as UTC under `PostgresSource::set_assume_utc`. Both
+/// are sent as microseconds since 2000-01-01.
+pub struct PostgresTimestampTz(pub DateTime<Utc>);
+
+impl<'a> FromSql<'a> for PostgresTimestampTz {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::TIMESTAMP {
+            true => Ok(PostgresTimestampTz(DateTime::from_utc(
+                NaiveDateTime::from_sql(ty, raw)?,
+                Utc,
+            ))),
+            false => Ok(PostgresTimestampTz(DateTime::<Utc>::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::TIMESTAMP || <DateTime<Utc> as FromSql>::accepts(ty)
+    }
+}
+
+// a float array, or a pgvector `vector`. Its binary format is the dimension (int2), an unused
+// int2 and the elements as float4.
+pub struct PostgresVector<T>(pub Vec<T>);
+
+impl<'a, T: FromSql<'a> + From<f32>> FromSql<'a> for PostgresVector<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if ty.name() != "vector" {
+            return Ok(PostgresVector(Vec::<T>::from_sql(ty, raw)?));
+        }
+        if raw.len() < 4 {
+            return Err("invalid message length: vector header missing".into());
+        }
+        let dim = u16::from_be_bytes(raw[0..2].try_into()?) as usize;
+        let body = &raw[4..];
+        if body.len() != dim * 4 {
+            return Err("invalid message length: vector size mismatch".into());
+        }
+        Ok(PostgresVector(
+            body.chunks_exact(4)
+                .map(|v| Ok(T::from(f32::from_be_bytes(v.try_into()?))))
+                .collect::<Result<Vec<_>, Box<dyn Error + Sync + Send>>>()?,
+        ))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "vector" || Vec::<T>::accepts(ty)
+    }
+}
+
+/// A pgvector `sparsevec`: its dimension and its nonzero elements as (index, value) pairs.
+/// The indices are 1-based, as in its text form (e.g. `{1:0.5,3:0.2}/5`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PgSparseVec {
+    pub dim: i32,
+    pub elements: Vec<(i32, f32)>,
+}
+
+// its binary format is the dimension, the number of elements and an unused int4, followed by
+// the 0-based indices (int4) and then the values (float4)
+impl<'a> FromSql<'a> for PgSparseVec {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() < 12 {
+            return Err("invalid message length: sparsevec header missing".into());
+        }
+        let dim = i32::from_be_bytes(raw[0..4].try_into()?);
+        let nnz = i32::from_be_bytes(raw[4..8].try_into()?) as usize;
+        let body = &raw[12..];
+        if body.len() != nnz * 8 {
+            return Err("invalid message length: sparsevec size mismatch".into());
+        }
+        let (indices, values) = body.split_at(nnz * 4);
+        let elements = indices
+            .chunks_exact(4)
+            .zip(values.chunks_exact(4))
+            .map(|(i, v)| {
+                Ok((
+                    i32::from_be_bytes(i.try_into()?) + 1,
+                    f32::from_be_bytes(v.try_into()?),
+                ))
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error + Sync + Send>>>()?;
+        Ok(PgSparseVec { dim, elements })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "sparsevec"
+    }
+}
+
+/// A range of a multirange, e.g. `[1,3)`. An unbounded side has no bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgRange<T> {
+    pub lower: Option<T>,
+    pub upper: Option<T>,
+    pub lower_inc: bool,
+    pub upper_inc: bool,
+}
+
+// the type of the bounds of a multirange
+fn multirange_element(ty: &Type) -> Option<Type> {
+    match ty.name() {
+        "int4multirange" => Some(Type::INT4),
+        "int8multirange" => Some(Type::INT8),
+        "datemultirange" => Some(Type::DATE),
+        "tsmultirange" => Some(Type::TIMESTAMP),
+        "tstzmultirange" => Some(Type::TIMESTAMPTZ),
+        _ => None,
+    }
+}
+
+fn read_i32(buf: &mut &[u8]) -> Result<i32, Box<dyn Error + Sync + Send>> {
+    if buf.len() < 4 {
+        return Err("invalid message length: multirange too short".into());
+    }
+    let (v, rest) = buf.split_at(4);
+    *buf = rest;
+    Ok(i32::from_be_bytes(v.try_into()?))
+}
+
+// the flags byte leading a range
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LB_INC: u8 = 0x02;
+const RANGE_UB_INC: u8 = 0x04;
+const RANGE_LB_INF: u8 = 0x08;
+const RANGE_UB_INF: u8 = 0x10;
+
+// a bound of a range as its length (int4) and the element, absent if it is infinite
+fn read_bound<'a, T: FromSql<'a>>(
+    element: &Type,
+    buf: &mut &'a [u8],
+    infinite: bool,
+) -> Result<Option<T>, Box<dyn Error + Sync + Send>> {
+    if infinite {
+        return Ok(None);
+    }
+    let len = read_i32(buf)? as usize;
+    if buf.len() < len {
+        return Err("invalid message length: range bound size mismatch".into());
+    }
+    let (v, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(Some(T::from_sql(element, v)?))
+}
+
+// a multirange as its ranges. It is sent as the number of ranges (int4), then each range as its
+// length (int4), a flags byte and its lower and upper bound. A multirange never holds an empty
+// range.
+pub struct PostgresMultiRange<T>(pub Vec<PgRange<T>>);
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for PostgresMultiRange<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let element = multirange_element(ty).ok_or("not a multirange")?;
+        let mut buf = raw;
+        let n = read_i32(&mut buf)?;
+        let mut ranges = Vec::with_capacity(n.max(0) as usize);
+        for _ in 0..n {
+            let len = read_i32(&mut buf)? as usize;
+            if buf.len() < len {
+                return Err("invalid message length: range size mismatch".into());
+            }
+            let (mut range, rest) = buf.split_at(len);
+            buf = rest;
+            let (&flags, bounds) = range
+                .split_first()
+                .ok_or("invalid message length: range flags missing")?;
+            range = bounds;
+            if flags & RANGE_EMPTY != 0 {
+                return Err("an empty range in a multirange".into());
+            }
+            ranges.push(PgRange {
+                lower: read_bound(&element, &mut range, flags & RANGE_LB_INF != 0)?,
+                upper: read_bound(&element, &mut range, flags & RANGE_UB_INF != 0)?,
+                lower_inc: flags & RANGE_LB_INC != 0,
+                upper_inc: flags & RANGE_UB_INC != 0,
+            });
+            if !range.is_empty() {
+                return Err("invalid message length: range size mismatch".into());
+            }
+        }
+        if !buf.is_empty() {
+            return Err("invalid message length: multirange size mismatch".into());
+        }
+        Ok(PostgresMultiRange(ranges))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        multirange_element(ty).map_or(false, |element| T::accepts(&element))
+    }
+}
+
+// a bool array, or a bit string as its bits. A bit string is sent as its length in bits (int4)
+// followed by the bits, most significant first and padded to whole bytes.
+pub struct PostgresBits(pub Vec<bool>);
+
+impl<'a> FromSql<'a> for PostgresBits {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if *ty != Type::BIT && *ty != Type::VARBIT {
+            return Ok(PostgresBits(Vec::<bool>::from_sql(ty, raw)?));
+        }
+        if raw.len() < 4 {
+            return Err("invalid message length: bit string length missing".into());
+        }
+        let len = u32::from_be_bytes(raw[0..4].try_into()?) as usize;
+        let body = &raw[4..];
+        if body.len() != (len + 7) / 8 {
+            return Err("invalid message length: bit string size mismatch".into());
+        }
+        Ok(PostgresBits(
+            (0..len)
+                .map(|i| body[i / 8] & (0x80 >> (i % 8)) != 0)
+                .collect(),
+        ))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::BIT || *ty == Type::VARBIT || Vec::<bool>::accepts(ty)
+    }
+}
+
+// an oid, or a bit string packed into an integer, its first bit the most significant. The width
+// is that of an oid, or the length of the bit string, of which the value keeps the last 64 bits.
+pub struct PostgresUnsigned {
+    pub value: u64,
+    pub width: usize,
+}
+
+impl PostgresUnsigned {
+    pub fn from_bits(bits: &[bool]) -> Self {
+        PostgresUnsigned {
+            value: bits.iter().fold(0, |v, &b| v << 1 | b as u64),
+            width: bits.len(),
+        }
+    }
+}
+
+impl<'a> FromSql<'a> for PostgresUnsigned {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty == Type::OID {
+            true => Ok(PostgresUnsigned {
+                value: u32::from_sql(ty, raw)? as u64,
+                width: 32,
+            }),
+            false => Ok(PostgresUnsigned::from_bits(
+                &PostgresBits::from_sql(ty, raw)?.0,
+            )),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::OID || *ty == Type::BIT || *ty == Type::VARBIT
+    }
+}
+
+// a bool, a bit string of a single bit, or an integer or text column to be read as a bool
+pub enum PostgresBoolLike<'a> {
+    Bool(bool),
+    Int(i64),
+    Text(&'a str),
+}
+
+impl<'a> FromSql<'a> for PostgresBoolLike<'a> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(if *ty == Type::BOOL {
+            PostgresBoolLike::Bool(bool::from_sql(ty, raw)?)
+        } else if *ty == Type::BIT || *ty == Type::VARBIT {
+            let bits = PostgresBits::from_sql(ty, raw)?.0;
+            match bits[..] {
+                [v] => PostgresBoolLike::Bool(v),
+                _ => return Err(format!("{} bits are not a bool", bits.len()).into()),
+            }
+        } else if *ty == Type::INT2 {
+            PostgresBoolLike::Int(i16::from_sql(ty, raw)? as i64)
+        } else if *ty == Type::INT4 {
+            PostgresBoolLike::Int(i32::from_sql(ty, raw)? as i64)
+        } else if *ty == Type::INT8 {
+            PostgresBoolLike::Int(i64::from_sql(ty, raw)?)
+        } else {
+            PostgresBoolLike::Text(<&str>::from_sql(ty, raw)?)
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        [
+            Type::BOOL,
+            Type::BIT,
+            Type::VARBIT,
+            Type::INT2,
+            Type::INT4,
+            Type::INT8,
+        ]
+        .contains(ty)
+            || <&str>::accepts(ty)
+    }
+}
+
 pub struct PostgresTypePairs<'a>(pub &'a Type, pub &'a PostgresTypeSystem);
 
 // Link (postgres::Type, connectorx::PostgresTypes) back to the one defiend by the postgres crate.
@@ -111,7 +883,9 @@ impl<'a> From<PostgresTypePairs<'a>> for Type {
         use PostgresTypeSystem::*;
         match ty.1 {
             Enum(_) => Type::TEXT,
+            NodeTree(_) => Type::TEXT, // sent as its serialized text, which `&str` only reads as TEXT
             HSTORE(_) => Type::TEXT, // hstore is not supported in binary protocol (since no corresponding inner TYPE)
+            RecordArray(_) => Type::JSON, // converted by `fetch_metadata`
             _ => ty.0.clone(),
         }
     }
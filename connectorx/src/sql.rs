@@ -4,11 +4,12 @@ use crate::sources::oracle::OracleDialect;
 use fehler::{throw, throws};
 use log::{debug, trace, warn};
 use sqlparser::ast::{
-    BinaryOperator, Expr, Function, FunctionArg, Ident, ObjectName, Query, Select, SelectItem,
-    SetExpr, Statement, TableAlias, TableFactor, TableWithJoins, Value,
+    BinaryOperator, Expr, Function, FunctionArg, Ident, ObjectName, OrderByExpr, Query, Select,
+    SelectItem, SetExpr, Statement, TableAlias, TableFactor, TableWithJoins, Value,
 };
 use sqlparser::dialect::Dialect;
-use sqlparser::parser::Parser;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer};
 #[cfg(feature = "src_oracle")]
 use std::any::Any;
 
@@ -171,6 +172,369 @@ impl QueryExt for Query {
     }
 }
 
+/// Split a semicolon-separated batch into its statements, each of which must be a query.
+#[throws(ConnectorXError)]
+pub fn split_statements<T: Dialect>(sql: &str, dialect: &T) -> Vec<CXQuery<String>> {
+    Parser::parse_sql(dialect, sql)?
+        .iter()
+        .map(|stmt| match stmt.as_query() {
+            Some(_) => Ok(CXQuery::Naked(format!("{}", stmt))),
+            None => Err(ConnectorXError::SqlQueryNotSupported(stmt.to_string())),
+        })
+        .collect::<Result<Vec<_>, _>>()?
+}
+
+/// The tables in the FROM clause of `sql` and the columns its projection names explicitly
+/// (by identifier or alias), or `None` if `sql` is not a single plain SELECT.
+pub fn projection_info<T: Dialect>(sql: &str, dialect: &T) -> Option<(Vec<String>, Vec<Ident>)> {
+    let ast = Parser::parse_sql(dialect, sql).ok()?;
+    if ast.len() != 1 {
+        return None;
+    }
+    let mut query = ast[0].as_query()?.clone();
+    let select = query.as_select_mut()?;
+
+    let tables = select
+        .from
+        .iter()
+        .flat_map(|twj| std::iter::once(&twj.relation).chain(twj.joins.iter().map(|j| &j.relation)))
+        .filter_map(|relation| match relation {
+            // not a table function, though one without arguments parses the same as a table
+            TableFactor::Table { name, args, .. } if args.is_empty() => Some(name.to_string()),
+            _ => None,
+        })
+        .collect();
+    let columns = select
+        .projection
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(ident.clone()),
+            SelectItem::UnnamedExpr(Expr::CompoundIdentifier(idents)) => idents.last().cloned(),
+            SelectItem::ExprWithAlias { alias, .. } => Some(alias.clone()),
+            _ => None,
+        })
+        .collect();
+    Some((tables, columns))
+}
+
+// whether `expr` calls a function, which may be an aggregate or a window function
+fn has_function(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(_) => true,
+        Expr::BinaryOp { left, right, .. } => has_function(left) || has_function(right),
+        Expr::UnaryOp { expr, .. } | Expr::Nested(expr) | Expr::Cast { expr, .. } => {
+            has_function(expr)
+        }
+        _ => false,
+    }
+}
+
+/// Add `predicate` to the WHERE clause of the single SELECT `sql`. The predicate is parsed as one
+/// expression, so unlike concatenating strings it cannot close the statement or start another.
+/// A query whose WHERE does not run last (aggregation, DISTINCT, a limit, a set operation or a
+/// function in the projection) or whose output names differ from those of its tables (an alias
+/// in the projection) is wrapped into a subquery first, so that the predicate filters its result.
+/// The predicate should therefore only refer to columns by their output names.
+#[throws(ConnectorXError)]
+pub fn filter_query<T: Dialect>(sql: &str, predicate: &str, dialect: &T) -> String {
+    trace!("Incoming query: {}, predicate: {}", sql, predicate);
+    const FILTER_TMP_TAB_NAME: &str = "CXTMPTAB_FILTER";
+
+    let ast = Parser::parse_sql(dialect, sql)?;
+    if ast.len() != 1 {
+        throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string()));
+    }
+    let mut query = ast[0]
+        .as_query()
+        .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?
+        .clone();
+
+    let tokens = Tokenizer::new(dialect, predicate)
+        .tokenize()
+        .map_err(ParserError::from)?;
+    let mut parser = Parser::new(tokens, dialect);
+    let predicate_expr = parser.parse_expr()?;
+    if parser.peek_token() != Token::EOF {
+        throw!(ConnectorXError::SqlQueryNotSupported(predicate.to_string()));
+    }
+    let predicate_expr = Expr::Nested(Box::new(predicate_expr));
+
+    let limited = query.limit.is_some() || query.offset.is_some() || query.fetch.is_some();
+    let filtered = match query.as_select_mut() {
+        Some(select)
+            if !limited
+                && select.top.is_none()
+                && !select.distinct
+                && select.group_by.is_empty()
+                && select.having.is_none()
+                && !select.projection.iter().any(|item| match item {
+                    SelectItem::UnnamedExpr(expr) => has_function(expr),
+                    SelectItem::ExprWithAlias { .. } => true,
+                    _ => false,
+                }) =>
+        {
+            select.selection = Some(match select.selection.take() {
+                Some(selection) => Expr::BinaryOp {
+                    left: Box::new(Expr::Nested(Box::new(selection))),
+                    op: BinaryOperator::And,
+                    right: Box::new(predicate_expr),
+                },
+                None => predicate_expr,
+            });
+            Statement::Query(Box::new(query))
+        }
+        _ => wrap_query(
+            &mut query,
+            vec![SelectItem::Wildcard],
+            Some(predicate_expr),
+            FILTER_TMP_TAB_NAME,
+        ),
+    };
+    let tsql = format!("{}", filtered);
+
+    // COPY takes exactly one query, make sure that is still what we produce
+    let check = Parser::parse_sql(dialect, &tsql)?;
+    if check.len() != 1 || check[0].as_query().is_none() {
+        throw!(ConnectorXError::SqlQueryNotSupported(tsql));
+    }
+
+    debug!("Transformed filter query: {}", tsql);
+    tsql
+}
+
+// the single table a plain SELECT reads, looking through a single derived table (e.g. the
+// partition queries wrapping the origin query) down to it
+fn single_table(query: &mut Query) -> Option<&mut TableWithJoins> {
+    if query.with.is_some() {
+        return None; // the table may be a CTE
+    }
+    let select = query.as_select_mut()?;
+    if select.from.len() != 1 || !select.from[0].joins.is_empty() {
+        return None;
+    }
+    match &select.from[0].relation {
+        TableFactor::Table { args, .. } if args.is_empty() => Some(&mut select.from[0]),
+        TableFactor::Derived { lateral: false, .. } => match &mut select.from[0].relation {
+            TableFactor::Derived { subquery, .. } => single_table(subquery),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Read `percent` of the pages of the table under `sql` with `TABLESAMPLE SYSTEM`, which is the
+/// syntax of PostgreSQL. `sql` must be a plain SELECT of a single table, possibly wrapped into
+/// subqueries, otherwise `None` is returned. The sample is `REPEATABLE` with `seed`, so that the
+/// count query and the partition queries sampled alike see the same rows.
+#[throws(ConnectorXError)]
+pub fn tablesample_query<T: Dialect>(
+    sql: &str,
+    percent: f64,
+    seed: i64,
+    dialect: &T,
+) -> Option<String> {
+    trace!("Incoming query: {}, sample: {}%", sql, percent);
+    const SAMPLE_TMP_TAB_NAME: &str = "CXTMPTAB_SAMPLE";
+
+    let mut ast = Parser::parse_sql(dialect, sql)?;
+    if ast.len() != 1 {
+        throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string()));
+    }
+    let query = match &mut ast[0] {
+        Statement::Query(q) => q,
+        _ => throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string())),
+    };
+    let table = match single_table(query) {
+        Some(table) => table,
+        None => return None,
+    };
+
+    // the AST has no room for TABLESAMPLE, so put a placeholder in and replace it once printed
+    let placeholder = TableFactor::Table {
+        name: ObjectName(vec![Ident {
+            value: SAMPLE_TMP_TAB_NAME.to_string(),
+            quote_style: None,
+        }]),
+        alias: None,
+        args: vec![],
+        with_hints: vec![],
+    };
+    let sampled = match std::mem::replace(&mut table.relation, placeholder) {
+        TableFactor::Table {
+            name,
+            alias: Some(alias),
+            ..
+        } => format!("{} AS {}", name, alias),
+        TableFactor::Table { name, .. } => name.to_string(),
+        _ => unreachable!("single_table only returns tables"),
+    };
+    let tsql = format!("{}", ast[0]).replacen(
+        SAMPLE_TMP_TAB_NAME,
+        &format!(
+            "{} TABLESAMPLE SYSTEM ({}) REPEATABLE ({})",
+            sampled, percent, seed
+        ),
+        1,
+    );
+
+    debug!("Transformed sample query: {}", tsql);
+    Some(tsql)
+}
+
+/// The table and the columns `sql` reads if it is a plain scan of a single table: a SELECT of
+/// `*` (the columns are then `None`) or of distinct bare column names, from the table alone
+/// without an alias, and with no other clause. Such a query can be copied as `COPY table
+/// (columns)` rather than as `COPY (sql)`.
+pub fn plain_table_scan<T: Dialect>(
+    sql: &str,
+    dialect: &T,
+) -> Option<(String, Option<Vec<String>>)> {
+    let ast = Parser::parse_sql(dialect, sql).ok()?;
+    if ast.len() != 1 {
+        return None;
+    }
+    let mut query = ast[0].as_query()?.clone();
+    if query.with.is_some()
+        || !query.order_by.is_empty()
+        || query.limit.is_some()
+        || query.offset.is_some()
+        || query.fetch.is_some()
+    {
+        return None;
+    }
+    let select = query.as_select_mut()?;
+    if select.distinct
+        || select.top.is_some()
+        || select.from.len() != 1
+        || !select.from[0].joins.is_empty()
+        || !select.lateral_views.is_empty()
+        || select.selection.is_some()
+        || !select.group_by.is_empty()
+        || !select.cluster_by.is_empty()
+        || !select.distribute_by.is_empty()
+        || !select.sort_by.is_empty()
+        || select.having.is_some()
+    {
+        return None;
+    }
+    let table = match &select.from[0].relation {
+        TableFactor::Table {
+            name,
+            alias: None,
+            args,
+            with_hints,
+        } if args.is_empty() && with_hints.is_empty() => name.to_string(),
+        _ => return None,
+    };
+    let columns = match &select.projection[..] {
+        [SelectItem::Wildcard] => None,
+        items => {
+            let columns = items
+                .iter()
+                .map(|item| match item {
+                    SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(ident.to_string()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            if (1..columns.len()).any(|i| columns[..i].contains(&columns[i])) {
+                return None; // COPY takes each column once
+            }
+            Some(columns)
+        }
+    };
+    Some((table, columns))
+}
+
+/// The options of an EXPLAIN that matter to read its result.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExplainOptions {
+    /// Whether the explained statement is run, by `ANALYZE`.
+    pub analyze: bool,
+    /// The output format upper cased, `None` for the default text.
+    pub format: Option<String>,
+}
+
+impl ExplainOptions {
+    // an option of the parenthesized list as its name and value, e.g. ["FORMAT", "JSON"]
+    fn set(&mut self, option: &[String]) {
+        let value = option.get(1).map(String::as_str);
+        match option.first().map(String::as_str) {
+            Some("ANALYZE" | "ANALYSE") => {
+                self.analyze = !matches!(value, Some("FALSE" | "OFF" | "0"))
+            }
+            Some("FORMAT") => self.format = value.filter(|f| *f != "TEXT").map(str::to_string),
+            _ => {}
+        }
+    }
+}
+
+fn is_keyword(token: &Token, keyword: &str) -> bool {
+    matches!(token, Token::Word(w) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(keyword))
+}
+
+/// The options of `sql` if it is an EXPLAIN, which can be neither wrapped in a subquery nor
+/// copied, or `None` otherwise. They are read from the tokens of `sql`, as the parser does not
+/// take the parenthesized form (`EXPLAIN (ANALYZE, FORMAT JSON) ...`).
+pub fn explain_options<T: Dialect>(sql: &str, dialect: &T) -> Option<ExplainOptions> {
+    let mut tokens = Tokenizer::new(dialect, sql)
+        .tokenize()
+        .ok()?
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)));
+    if !is_keyword(&tokens.next()?, "explain") {
+        return None;
+    }
+    let mut options = ExplainOptions::default();
+    match tokens.next()? {
+        Token::LParen => {
+            let mut option = vec![];
+            for token in tokens {
+                match token {
+                    Token::Comma | Token::RParen => {
+                        options.set(&option);
+                        option.clear();
+                        if token == Token::RParen {
+                            break;
+                        }
+                    }
+                    token => option.push(token.to_string().to_uppercase()),
+                }
+            }
+        }
+        // the unparenthesized form, `EXPLAIN ANALYZE VERBOSE ...`
+        mut token => loop {
+            if is_keyword(&token, "analyze") || is_keyword(&token, "analyse") {
+                options.analyze = true;
+            } else if !is_keyword(&token, "verbose") {
+                break;
+            }
+            token = tokens.next()?;
+        },
+    }
+    Some(options)
+}
+
+/// Whether `sql` is a single query ordered by `col` ascending first, so that its rows come in
+/// increasing order of `col` (strictly so if `col` is unique).
+pub fn ordered_by<T: Dialect>(sql: &str, col: &str, dialect: &T) -> bool {
+    let ast = match Parser::parse_sql(dialect, sql) {
+        Ok(ast) if ast.len() == 1 => ast,
+        _ => return false,
+    };
+    let query = match ast[0].as_query() {
+        Some(query) => query,
+        None => return false,
+    };
+    match query.order_by.first() {
+        Some(OrderByExpr { expr, asc, .. }) if *asc != Some(false) => match expr {
+            Expr::Identifier(ident) => ident.value == col,
+            Expr::CompoundIdentifier(idents) => idents.last().map_or(false, |i| i.value == col),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 #[throws(ConnectorXError)]
 pub fn count_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<String> {
     trace!("Incoming query: {}", sql);
@@ -228,11 +592,17 @@ pub fn count_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<St
                         .as_query()
                         .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?
                         .clone();
-                    let select = query
-                        .as_select_mut()
-                        .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?;
-                    select.projection = projection;
-                    Statement::Query(Box::new(query))
+                    if query.limit.is_some() || query.offset.is_some() || query.fetch.is_some() {
+                        // count the rows left by the limit, not the ones it applies to
+                        wrap_query(&mut query, projection, None, table_alias)
+                    } else {
+                        let select = query.as_select_mut().ok_or_else(|| {
+                            ConnectorXError::SqlQueryNotSupported(sql.to_string())
+                        })?;
+                        select.projection = projection;
+                        query.order_by = vec![]; // the partition may be ordered on its key
+                        Statement::Query(Box::new(query))
+                    }
                 }
             };
             format!("{}", ast_count)
@@ -312,15 +682,88 @@ pub fn limit1_query_oracle(sql: &CXQuery<String>) -> CXQuery<String> {
     // CXQuery::Wrapped(tsql)
 }
 
+/// Quote `name` as a delimited identifier of `dialect`, doubling the closing quote within it, so
+/// that the partition helpers below match a column whose name is mixed case, a reserved word or
+/// has special characters exactly as written. Dialects that fold unquoted names (Postgres to
+/// lower case, Oracle to upper case) no longer do so for a quoted one.
+pub fn quote_ident<T: Dialect>(name: &str, dialect: &T) -> String {
+    let (open, close) = match ['"', '`', '[']
+        .iter()
+        .copied()
+        .find(|&c| dialect.is_delimited_identifier_start(c))
+    {
+        Some('`') => ('`', '`'),
+        Some('[') => ('[', ']'),
+        _ => ('"', '"'),
+    };
+    format!(
+        "{}{}{}",
+        open,
+        name.replace(close, &close.to_string().repeat(2)),
+        close
+    )
+}
+
+// the partition column is written into the generated queries as is, so it must be a single,
+// possibly qualified, identifier rather than any SQL: plain words or quoted names (the tokenizer
+// does not take a doubled quote within a quoted name) separated by periods
+#[throws(ConnectorXError)]
+fn check_partition_col(col: &str) {
+    let invalid = || ConnectorXError::InvalidPartitionColumn(col.to_string());
+    let mut rest = col;
+    loop {
+        let len = match rest.chars().next().ok_or_else(invalid)? {
+            open @ ('"' | '`' | '[') => {
+                let close = if open == '[' { ']' } else { open };
+                let mut end = None;
+                let mut chars = rest.char_indices().skip(1).peekable();
+                while let Some((i, c)) = chars.next() {
+                    if c != close {
+                        continue;
+                    }
+                    match chars.peek() {
+                        Some(&(_, c)) if c == close => {
+                            chars.next();
+                        }
+                        _ => {
+                            end = Some(i + c.len_utf8());
+                            break;
+                        }
+                    }
+                }
+                end.ok_or_else(invalid)?
+            }
+            c if c.is_alphabetic() || c == '_' => rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(rest.len()),
+            _ => throw!(invalid()),
+        };
+        rest = &rest[len..];
+        match rest.strip_prefix('.') {
+            Some(next) => rest = next,
+            None if rest.is_empty() => break,
+            None => throw!(invalid()),
+        }
+    }
+}
+
+/// Restrict `sql` to the rows where `lower <= col < upper`.
+///
+/// Rows come back in whatever order the database finds them, usually the physical order of the
+/// table. Set `order` to append an `ORDER BY` on `col`, for destinations that write faster when
+/// rows arrive sorted by the key. The database then has to sort every partition before returning
+/// its first row, which is cheap with an index on `col` and a full sort without one.
 #[throws(ConnectorXError)]
 pub fn single_col_partition_query<T: Dialect>(
     sql: &str,
     col: &str,
     lower: i64,
     upper: i64,
+    order: bool,
     dialect: &T,
 ) -> String {
     trace!("Incoming query: {}", sql);
+    check_partition_col(col)?;
     const PART_TMP_TAB_NAME: &str = "CXTMPTAB_PART";
 
     #[allow(unused_mut)]
@@ -340,7 +783,7 @@ pub fn single_col_partition_query<T: Dialect>(
     // HACK: Some dialect (e.g. Oracle) does not support "AS" for alias
     #[cfg(feature = "src_oracle")]
     if dialect.type_id() == (OracleDialect {}.type_id()) {
-        return format!("SELECT * FROM ({}) CXTMPTAB_PART WHERE CXTMPTAB_PART.{} >= {} AND CXTMPTAB_PART.{} < {}", sql, col, lower, col, upper);
+        return format!("SELECT * FROM ({}) CXTMPTAB_PART WHERE CXTMPTAB_PART.{} >= {} AND CXTMPTAB_PART.{} < {}{}", sql, col, lower, col, upper, order_by_suffix(col, order));
         // table_alias = "";
         // cid = Box::new(Expr::Identifier(Ident {
         //     value: col.to_string(),
@@ -364,7 +807,7 @@ pub fn single_col_partition_query<T: Dialect>(
                 .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?
                 .clone();
 
-            let ast_part: Statement;
+            let mut ast_part: Statement;
 
             let lb = Expr::BinaryOp {
                 left: Box::new(Expr::Value(Value::Number(lower.to_string(), false))),
@@ -373,7 +816,7 @@ pub fn single_col_partition_query<T: Dialect>(
             };
 
             let ub = Expr::BinaryOp {
-                left: cid,
+                left: cid.clone(),
                 op: BinaryOperator::Lt,
                 right: Box::new(Expr::Value(Value::Number(upper.to_string(), false))),
             };
@@ -397,11 +840,18 @@ pub fn single_col_partition_query<T: Dialect>(
                 Some(selection),
                 table_alias,
             );
+            if let (true, Statement::Query(q)) = (order, &mut ast_part) {
+                q.order_by = vec![OrderByExpr {
+                    expr: *cid,
+                    asc: None,
+                    nulls_first: None,
+                }];
+            }
             format!("{}", ast_part)
         }
         Err(e) => {
             warn!("parser error: {:?}, manually compose query string", e);
-            format!("SELECT * FROM ({}) AS CXTMPTAB_PART WHERE CXTMPTAB_PART.{} >= {} AND CXTMPTAB_PART.{} < {}", sql, col, lower, col, upper)
+            format!("SELECT * FROM ({}) AS CXTMPTAB_PART WHERE CXTMPTAB_PART.{} >= {} AND CXTMPTAB_PART.{} < {}{}", sql, col, lower, col, upper, order_by_suffix(col, order))
         }
     };
 
@@ -409,9 +859,66 @@ pub fn single_col_partition_query<T: Dialect>(
     tsql
 }
 
+fn order_by_suffix(col: &str, order: bool) -> String {
+    match order {
+        true => format!(" ORDER BY CXTMPTAB_PART.{}", col),
+        false => String::new(),
+    }
+}
+
+/// Restrict `sql` to partition `part` of `num`, like `single_col_partition_query`, but with the
+/// range of `col` computed by a CTE of the partition query itself (Postgres only), rather than
+/// beforehand by `get_partition_range_query`. The range is split the same way.
+///
+/// Precomputed bounds cost one scan for the range, but a row written between that scan and the
+/// partition queries can fall outside the range and be missed, e.g. a new key above the max.
+/// Here the range is evaluated against the same snapshot as the rows of the partition, so the
+/// first and last partitions reach the min and max the partition sees. This costs a scan for
+/// the range per partition (cheap with an index on `col`), and as every partition takes a
+/// snapshot of its own, a write that moves the min or max between them still shifts the
+/// boundaries of the later partitions.
+#[throws(ConnectorXError)]
+pub fn single_col_partition_query_in_range(
+    sql: &str,
+    col: &str,
+    part: usize,
+    num: usize,
+    order: bool,
+) -> String {
+    trace!("Incoming query: {}", sql);
+    check_partition_col(col)?;
+    let lower = match part {
+        0 => format!("CXTMPTAB_PART.{} >= CXTMPTAB_BOUNDS.min", col),
+        _ => format!(
+            "CXTMPTAB_PART.{} >= CXTMPTAB_BOUNDS.min + {} * CXTMPTAB_BOUNDS.size",
+            col, part
+        ),
+    };
+    let upper = match part + 1 >= num {
+        true => format!("CXTMPTAB_PART.{} <= CXTMPTAB_BOUNDS.max", col),
+        false => format!(
+            "CXTMPTAB_PART.{} < CXTMPTAB_BOUNDS.min + {} * CXTMPTAB_BOUNDS.size",
+            col,
+            part + 1
+        ),
+    };
+    let tsql = format!(
+        "WITH CXTMPTAB_BOUNDS AS (SELECT min, max, (max - min + 1) / {num} AS size FROM (SELECT MIN(CXTMPTAB_RANGE.{col})::int8 AS min, MAX(CXTMPTAB_RANGE.{col})::int8 AS max FROM ({sql}) AS CXTMPTAB_RANGE) AS CXTMPTAB_MINMAX) SELECT CXTMPTAB_PART.* FROM ({sql}) AS CXTMPTAB_PART, CXTMPTAB_BOUNDS WHERE {lower} AND {upper}{order}",
+        num = num,
+        col = col,
+        sql = sql,
+        lower = lower,
+        upper = upper,
+        order = order_by_suffix(col, order),
+    );
+    debug!("Transformed single column partition query: {}", tsql);
+    tsql
+}
+
 #[throws(ConnectorXError)]
 pub fn get_partition_range_query<T: Dialect>(sql: &str, col: &str, dialect: &T) -> String {
     trace!("Incoming query: {}", sql);
+    check_partition_col(col)?;
     const RANGE_TMP_TAB_NAME: &str = "CXTMPTAB_RANGE";
 
     #[allow(unused_mut)]
@@ -497,6 +1004,7 @@ pub fn get_partition_range_query_sep<T: Dialect>(
     dialect: &T,
 ) -> (String, String) {
     trace!("Incoming query: {}", sql);
+    check_partition_col(col)?;
     const RANGE_TMP_TAB_NAME: &str = "CXTMPTAB_RANGE";
 
     let (sql_min, sql_max) = match Parser::parse_sql(dialect, sql) {
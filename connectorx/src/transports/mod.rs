@@ -54,7 +54,10 @@ pub use oracle_arrow::{OracleArrowTransport, OracleArrowTransportError};
 #[cfg(all(feature = "src_oracle", feature = "dst_arrow2"))]
 pub use oracle_arrow2::{OracleArrow2Transport, OracleArrow2TransportError};
 #[cfg(all(feature = "src_postgres", feature = "dst_arrow"))]
-pub use postgres_arrow::{PostgresArrowTransport, PostgresArrowTransportError};
+pub use postgres_arrow::{
+    arrow_schema, fill_record_batch, write_ipc_stream, PostgresArrowTransport,
+    PostgresArrowTransportError,
+};
 #[cfg(all(feature = "src_postgres", feature = "dst_arrow2"))]
 pub use postgres_arrow2::{PostgresArrow2Transport, PostgresArrow2TransportError};
 #[cfg(all(feature = "src_sqlite", feature = "dst_arrow"))]
@@ -4,7 +4,7 @@ use crate::destinations::arrow2::{
     typesystem::Arrow2TypeSystem, Arrow2Destination, Arrow2DestinationError,
 };
 use crate::sources::postgres::{
-    BinaryProtocol, CSVProtocol, CursorProtocol, PostgresSource, PostgresSourceError,
+    AutoProtocol, BinaryProtocol, CSVProtocol, CursorProtocol, PostgresSource, PostgresSourceError,
     PostgresTypeSystem,
 };
 use crate::typesystem::TypeConversion;
@@ -44,14 +44,21 @@ macro_rules! impl_postgres_transport {
                 { Float4[f32]                       => Float32[f32]                | conversion auto }
                 { Float8[f64]                       => Float64[f64]                | conversion auto }
                 { Numeric[Decimal]                  => Float64[f64]                | conversion option }
+                { Money[Decimal]                    => Float64[f64]                | conversion none }
                 { Int2[i16]                         => Int32[i32]                  | conversion auto }
                 { Int4[i32]                         => Int32[i32]                  | conversion auto }
                 { Int8[i64]                         => Int64[i64]                  | conversion auto }
+                { Oid[u32]                          => UInt32[u32]                 | conversion auto }
                 { Bool[bool]                        => Boolean[bool]               | conversion auto  }
+                { Bit[Vec<bool>]                    => LargeUtf8[String]           | conversion option }
                 { Text[&'r str]                     => LargeUtf8[String]           | conversion owned }
                 { BpChar[&'r str]                   => LargeUtf8[String]           | conversion none }
                 { VarChar[&'r str]                  => LargeUtf8[String]           | conversion none }
+                { Name[&'r str]                     => LargeUtf8[String]           | conversion none }
+                { Unsupported[&'r str]              => LargeUtf8[String]           | conversion none }
                 { Enum[&'r str]                     => LargeUtf8[String]           | conversion none }
+                { NodeTree[&'r str]                 => LargeUtf8[String]           | conversion none }
+                { Xml[&'r str]                      => LargeUtf8[String]           | conversion none }
                 { Timestamp[NaiveDateTime]          => Date64[NaiveDateTime]       | conversion auto }
                 { Date[NaiveDate]                   => Date32[NaiveDate]           | conversion auto }
                 { Time[NaiveTime]                   => Time64[NaiveTime]           | conversion auto }
@@ -59,14 +66,21 @@ macro_rules! impl_postgres_transport {
                 { UUID[Uuid]                        => LargeUtf8[String]           | conversion option }
                 { Char[&'r str]                     => LargeUtf8[String]           | conversion none }
                 { ByteA[Vec<u8>]                    => LargeBinary[Vec<u8>]        | conversion auto }
+                { Geometry[String]                  => LargeUtf8[String]           | conversion owned }
                 { JSON[Value]                       => LargeUtf8[String]           | conversion option }
                 { JSONB[Value]                      => LargeUtf8[String]           | conversion none }
+                { RecordArray[Vec<Value>]           => LargeUtf8[String]           | conversion option }
                 { Int2Array[Vec<i16>]               => Int64Array[Vec<i64>]        | conversion auto_vec }
                 { Int4Array[Vec<i32>]               => Int64Array[Vec<i64>]        | conversion auto_vec }
                 { Int8Array[Vec<i64>]               => Int64Array[Vec<i64>]        | conversion auto }
+                { Int2Vector[Vec<i16>]              => Int64Array[Vec<i64>]        | conversion none }
+                { OidVector[Vec<u32>]               => Int64Array[Vec<i64>]        | conversion auto_vec }
+                { OidArray[Vec<u32>]                => Int64Array[Vec<i64>]        | conversion none }
                 { Float4Array[Vec<f32>]             => Float64Array[Vec<f64>]      | conversion auto_vec }
                 { Float8Array[Vec<f64>]             => Float64Array[Vec<f64>]      | conversion auto }
+                { Vector[Vec<f32>]                  => Float64Array[Vec<f64>]      | conversion none }
                 { NumericArray[Vec<Decimal>]        => Float64Array[Vec<f64>]      | conversion option }
+                { MoneyArray[Vec<Decimal>]          => Float64Array[Vec<f64>]      | conversion none }
             }
         );
     }
@@ -78,6 +92,8 @@ impl_postgres_transport!(CSVProtocol, NoTls);
 impl_postgres_transport!(CSVProtocol, MakeTlsConnector);
 impl_postgres_transport!(CursorProtocol, NoTls);
 impl_postgres_transport!(CursorProtocol, MakeTlsConnector);
+impl_postgres_transport!(AutoProtocol, NoTls);
+impl_postgres_transport!(AutoProtocol, MakeTlsConnector);
 
 impl<P, C> TypeConversion<Uuid, String> for PostgresArrow2Transport<P, C> {
     fn convert(val: Uuid) -> String {
@@ -85,6 +101,12 @@ impl<P, C> TypeConversion<Uuid, String> for PostgresArrow2Transport<P, C> {
     }
 }
 
+impl<P, C> TypeConversion<Vec<bool>, String> for PostgresArrow2Transport<P, C> {
+    fn convert(val: Vec<bool>) -> String {
+        val.iter().map(|&b| if b { '1' } else { '0' }).collect()
+    }
+}
+
 impl<P, C> TypeConversion<Decimal, f64> for PostgresArrow2Transport<P, C> {
     fn convert(val: Decimal) -> f64 {
         val.to_f64()
@@ -108,3 +130,9 @@ impl<P, C> TypeConversion<Value, String> for PostgresArrow2Transport<P, C> {
         val.to_string()
     }
 }
+
+impl<P, C> TypeConversion<Vec<Value>, String> for PostgresArrow2Transport<P, C> {
+    fn convert(val: Vec<Value>) -> String {
+        Value::Array(val).to_string()
+    }
+}
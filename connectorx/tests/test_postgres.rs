@@ -1,18 +1,38 @@
 use arrow::{
-    array::{BooleanArray, Float64Array, Int64Array, StringArray},
+    array::{
+        BooleanArray, Date32Array, Float64Array, Int64Array, StringArray, TimestampMicrosecondArray,
+    },
+    ipc::reader::StreamReader,
     record_batch::RecordBatch,
 };
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::{America::New_York, Tz};
 use connectorx::{
     destinations::arrow::ArrowDestination,
     prelude::*,
-    sources::postgres::{rewrite_tls_args, BinaryProtocol, CSVProtocol, PostgresSource},
+    sources::postgres::{
+        rewrite_conn_args, rewrite_tls_args, AutoProtocol, BinaryProtocol, BoolCoercion,
+        ByteaEncoding, CSVProtocol, CursorProtocol, DecodeErrorPolicy, PgRange, PgSparseVec,
+        PostgresAutoSourceParser, PostgresInterval, PostgresPoolConfig, PostgresSample,
+        PostgresSource, PostgresSourceError, PostgresTypeSystem,
+    },
     sources::PartitionParser,
-    sql::CXQuery,
-    transports::PostgresArrowTransport,
+    sql::{
+        explain_options, filter_query, plain_table_scan, quote_ident, single_col_partition_query,
+        single_col_partition_query_in_range, CXQuery, ExplainOptions,
+    },
+    transports::{arrow_schema, fill_record_batch, write_ipc_stream, PostgresArrowTransport},
 };
 use postgres::NoTls;
+use rust_decimal::Decimal;
+use sqlparser::dialect::PostgreSqlDialect;
+use std::collections::HashMap;
 use std::env;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use url::Url;
+use uuid::Uuid;
 
 #[test]
 fn load_and_parse() {
@@ -123,184 +143,4828 @@ fn load_and_parse_csv() {
 }
 
 #[test]
-fn test_postgres() {
+fn load_and_parse_csv_header() {
     let _ = env_logger::builder().is_test(true).try_init();
 
     let dburl = env::var("POSTGRES_URL").unwrap();
 
-    let queries = [
-        CXQuery::naked("select * from test_table where test_int < 2"),
-        CXQuery::naked("select * from test_table where test_int >= 2"),
-    ];
     let url = Url::parse(dburl.as_str()).unwrap();
     let (config, _tls) = rewrite_tls_args(&url).unwrap();
-    let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
-    let mut destination = ArrowDestination::new();
-    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
-        builder,
-        &mut destination,
-        &queries,
-        Some(String::from("select * from test_table")),
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    source.set_csv_header(true);
+    source.set_queries(&[
+        CXQuery::naked("select test_int, test_str from test_table where test_int < 2"),
+        CXQuery::naked(
+            "select test_int, test_str as test_text from test_table where test_int >= 2",
+        ),
+    ]);
+    source.fetch_metadata().unwrap();
+
+    let mut partitions = source.partition().unwrap();
+    assert!(partitions.len() == 2);
+
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(2, n);
+    let v: i32 = parser.produce().unwrap();
+    assert_eq!(1, v);
+
+    let mut partition = partitions.remove(0);
+    assert!(partition.parser().is_err());
+}
+
+#[test]
+fn load_and_parse_csv_null() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_csv_null(Some("<it's null>".to_string()));
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values ('a'::text, 1), ('', null), (null, 3)) as t(test_str, test_int)",
+    )]);
+    source.fetch_metadata().unwrap();
+
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let mut rows = vec![];
+    for _ in 0..n {
+        let s: Option<&str> = parser.produce().unwrap();
+        let s = s.map(ToString::to_string);
+        let i: Option<i32> = parser.produce().unwrap();
+        rows.push((s, i));
+    }
+    assert_eq!(
+        vec![
+            (Some("a".to_string()), Some(1)),
+            (Some("".to_string()), None),
+            (None, Some(3)),
+        ],
+        rows
     );
+}
 
-    dispatcher.run().expect("run dispatcher");
+#[test]
+fn load_and_parse_char_as_u8() {
+    let _ = env_logger::builder().is_test(true).try_init();
 
-    let result = destination.arrow().unwrap();
-    verify_arrow_results(result);
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select test_int16, test_char::\"char\" from test_types",
+    )];
+    let expected = vec![Some(b'a'), Some(b'b'), None, Some(b'd')];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<u8>> = (0..n)
+        .map(|_| {
+            let _: i16 = parser.produce().unwrap();
+            parser.produce().unwrap()
+        })
+        .collect();
+    assert_eq!(expected, rows);
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<u8>> = (0..n)
+        .map(|_| {
+            let _: i16 = parser.produce().unwrap();
+            parser.produce().unwrap()
+        })
+        .collect();
+    assert_eq!(expected, rows);
 }
 
 #[test]
-fn test_postgres_csv() {
+fn load_and_parse_csv_money_and_numeric_arrays() {
     let _ = env_logger::builder().is_test(true).try_init();
 
     let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values (array['-1.5', '1234.56']::money[], array['1.5e3', '-2.5E-2']::numeric[]), (array[null, '1']::money[], null)) as t(test_moneyarray, test_narray)",
+    )]);
+    source.fetch_metadata().unwrap();
 
-    let queries = [
-        CXQuery::naked("select * from test_table where test_int < 2"),
-        CXQuery::naked("select * from test_table where test_int >= 2"),
-    ];
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(2, n);
+
+    let money: Option<Vec<Decimal>> = parser.produce().unwrap();
+    assert_eq!(
+        Some(vec![Decimal::new(-150, 2), Decimal::new(123456, 2)]),
+        money
+    );
+    let numeric: Option<Vec<Decimal>> = parser.produce().unwrap();
+    assert_eq!(
+        Some(vec![Decimal::new(1500, 0), Decimal::new(-25, 3)]),
+        numeric
+    );
+
+    // NULL elements cannot be represented in a Vec<Decimal>
+    assert!(Produce::<Option<Vec<Decimal>>>::produce(&mut parser).is_err());
+}
+
+#[test]
+fn load_and_parse_csv_money_array_locale() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (mut config, _tls) = rewrite_tls_args(&url).unwrap();
+    // a locale with `.` as the thousands separator and `,` as the decimal point
+    config.options("-c lc_monetary=de_DE.UTF-8");
+    if config.connect(NoTls).is_err() {
+        eprintln!("skipping, the server has no de_DE.UTF-8 locale");
+        return;
+    }
+    // casting from numeric, as the text input of money follows lc_monetary as well
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values (array[-1234567.89, 0.5]::numeric[]::money[], 1234.5::numeric::money), (null, null)) as t(test_moneyarray, test_money)",
+    )]);
+    source.fetch_metadata().unwrap();
+
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(2, n);
+    let money: Option<Vec<Decimal>> = parser.produce().unwrap();
+    assert_eq!(
+        Some(vec![Decimal::new(-123456789, 2), Decimal::new(50, 2)]),
+        money
+    );
+    let money: Option<Decimal> = parser.produce().unwrap();
+    assert_eq!(Some(Decimal::new(123450, 2)), money);
+    let money: Option<Vec<Decimal>> = parser.produce().unwrap();
+    assert_eq!(None, money);
+    let money: Option<Decimal> = parser.produce().unwrap();
+    assert_eq!(None, money);
+}
+
+#[test]
+fn load_and_parse_money_yen() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (mut config, _tls) = rewrite_tls_args(&url).unwrap();
+    // a currency without fraction digits
+    config.options("-c lc_monetary=ja_JP.UTF-8");
+    if config.connect(NoTls).is_err() {
+        eprintln!("skipping, the server has no ja_JP.UTF-8 locale");
+        return;
+    }
+    let queries = [CXQuery::naked(
+        "select * from (values (1234::numeric::money, array[-5]::numeric[]::money[])) as t(test_money, test_moneyarray)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            assert_eq!(1, n);
+            let money: Decimal = parser.produce().unwrap();
+            assert_eq!(
+                (1234, 0),
+                (money.mantissa(), money.scale()),
+                "{}",
+                stringify!($proto)
+            );
+            let money: Vec<Decimal> = parser.produce().unwrap();
+            assert_eq!(
+                vec![(-5, 0)],
+                money
+                    .iter()
+                    .map(|v| (v.mantissa(), v.scale()))
+                    .collect::<Vec<_>>(),
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_money_array() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
     let url = Url::parse(dburl.as_str()).unwrap();
     let (config, _tls) = rewrite_tls_args(&url).unwrap();
-    let builder = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 2).unwrap();
-    let mut dst = ArrowDestination::new();
-    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<CSVProtocol, NoTls>>::new(
-        builder, &mut dst, &queries, None,
+    let queries = [CXQuery::naked(
+        "select * from (values ('-0.01'::money, array['-1.5', '0', '1234.56']::money[]), (null, null)) as t(test_money, test_moneyarray)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(Option<Decimal>, Option<Vec<Decimal>>)> = (0..n)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(
+                vec![
+                    (
+                        Some(Decimal::new(-1, 2)),
+                        Some(vec![
+                            Decimal::new(-150, 2),
+                            Decimal::new(0, 2),
+                            Decimal::new(123456, 2)
+                        ])
+                    ),
+                    (None, None),
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_numeric_as_string() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let big = "12345678901234567890123.4567890123456789012345678";
+    let tiny = "-0.0000000000000000000000001";
+    let queries = [CXQuery::naked(format!(
+        "select test_numeric::numeric(50,25) from (values ('{}'), ('{}'), (null)) as t(test_numeric)",
+        big, tiny
+    ))];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<String>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(
+                vec![Some(big.to_string()), Some(tiny.to_string()), None],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn test_csv_unsupported_type() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, array[test_bool] as test_boolarray from test_table",
+    )]);
+
+    let err = source.fetch_metadata().unwrap_err();
+    assert!(err.to_string().contains("\"test_boolarray\""));
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn load_and_parse_time_crate() {
+    use time::macros::{date, datetime, time};
+    use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    #[derive(Debug, PartialEq)]
+    struct Row(
+        Option<Date>,
+        Option<PrimitiveDateTime>,
+        Option<OffsetDateTime>,
+        Option<Time>,
     );
 
-    dispatcher.run().expect("run dispatcher");
-    let result = dst.arrow().unwrap();
-    verify_arrow_results(result);
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select test_date, test_timestamp, test_timestamptz, test_time from test_types",
+    )];
+    let expected = vec![
+        Row(
+            Some(date!(1970 - 01 - 01)),
+            Some(datetime!(1970-01-01 00:00:01)),
+            Some(datetime!(1970-01-01 00:00:01 UTC)),
+            Some(time!(08:12:40)),
+        ),
+        Row(
+            Some(date!(2000 - 02 - 28)),
+            Some(datetime!(2000-02-28 12:00:10)),
+            Some(datetime!(2000-02-28 12:00:10 -4)),
+            None,
+        ),
+        Row(
+            Some(date!(2038 - 01 - 18)),
+            Some(datetime!(2038-01-18 23:59:59)),
+            Some(datetime!(2038-01-18 23:59:59 +8)),
+            Some(time!(23:00:10)),
+        ),
+        Row(None, None, None, Some(time!(18:30:00))),
+    ];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Row> = (0..n)
+        .map(|_| {
+            Row(
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(expected, rows);
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Row> = (0..n)
+        .map(|_| {
+            Row(
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(expected, rows);
 }
 
 #[test]
-fn test_postgres_agg() {
+fn load_and_parse_csv_interval() {
     let _ = env_logger::builder().is_test(true).try_init();
 
     let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (0, interval '1 year 2 months 3 days'), (1, '2 weeks ago'), (2, '1 day -02:03:04.5'), (3, '-1 year +3 hours'), (4, '00:00:00'), (5, null)) as t(id, test_interval)",
+    )];
+    let expected = vec![
+        Some(PostgresInterval {
+            months: 14,
+            days: 3,
+            microseconds: 0,
+        }),
+        Some(PostgresInterval {
+            months: 0,
+            days: -14,
+            microseconds: 0,
+        }),
+        Some(PostgresInterval {
+            months: 0,
+            days: 1,
+            microseconds: -7_384_500_000,
+        }),
+        Some(PostgresInterval {
+            months: -12,
+            days: 0,
+            microseconds: 10_800_000_000,
+        }),
+        Some(PostgresInterval::default()),
+        None,
+    ];
+
+    for style in ["postgres", "iso_8601"] {
+        let mut config = config.clone();
+        config.options(&format!("-c IntervalStyle={}", style));
+        let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+        source.set_queries(&queries);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let rows: Vec<Option<PostgresInterval>> = (0..n)
+            .map(|_| {
+                let _: i32 = parser.produce().unwrap();
+                parser.produce().unwrap()
+            })
+            .collect();
+        assert_eq!(expected, rows, "IntervalStyle {}", style);
+    }
+}
+
+#[test]
+fn load_and_parse_csv_datestyle() {
+    let _ = env_logger::builder().is_test(true).try_init();
 
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (mut config, _tls) = rewrite_tls_args(&url).unwrap();
+    config.options("-c DateStyle=German");
     let queries = [CXQuery::naked(
-        "SELECT test_bool, SUM(test_float) FROM test_table GROUP BY test_bool",
+        "select test_date, test_timestamp from test_types order by test_int16",
     )];
 
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<(Option<NaiveDate>, Option<NaiveDateTime>)> = (0..n)
+        .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                Some(NaiveDate::from_ymd(1970, 1, 1)),
+                Some(NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 1))
+            ),
+            (
+                Some(NaiveDate::from_ymd(2000, 2, 28)),
+                Some(NaiveDate::from_ymd(2000, 2, 28).and_hms(12, 0, 10))
+            ),
+            (
+                Some(NaiveDate::from_ymd(2038, 1, 18)),
+                Some(NaiveDate::from_ymd(2038, 1, 18).and_hms(23, 59, 59))
+            ),
+            (None, None),
+        ],
+        rows
+    );
+}
+
+#[test]
+fn load_and_parse_csv_timestamp_offset() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
     let url = Url::parse(dburl.as_str()).unwrap();
     let (config, _tls) = rewrite_tls_args(&url).unwrap();
-    let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
-    let mut destination = ArrowDestination::new();
-    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
-        builder,
-        &mut destination,
-        &queries,
-        Some(format!(
-            "SELECT test_bool, SUM(test_float) FROM test_table GROUP BY test_bool"
-        )),
+    let queries = [CXQuery::naked(
+        "select * from (values ('2000-02-28 12:00:10+00'), ('2038-01-18 23:59:59-08:30'), ('1970-01-01 00:00:01'), (null)) as t(test_timestamp)",
+    )];
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<NaiveDateTime>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(
+        vec![
+            Some(NaiveDate::from_ymd(2000, 2, 28).and_hms(12, 0, 10)),
+            Some(NaiveDate::from_ymd(2038, 1, 18).and_hms(23, 59, 59)),
+            Some(NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 1)),
+            None,
+        ],
+        rows
     );
+}
 
-    dispatcher.run().expect("run dispatcher");
+#[test]
+fn load_and_parse_bool_as_int() {
+    let _ = env_logger::builder().is_test(true).try_init();
 
-    let mut result = destination.arrow().unwrap();
-    assert!(result.len() == 1);
-    let rb = result.pop().unwrap();
-    assert!(rb.columns().len() == 2);
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select test_bool, test_bool from test_table order by test_int",
+    )];
 
-    assert!(rb
-        .column(0)
-        .as_any()
-        .downcast_ref::<BooleanArray>()
-        .unwrap()
-        .eq(&BooleanArray::from(vec![None, Some(false), Some(true)])));
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(Option<i8>, Option<i32>)> = (0..n)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(
+                vec![
+                    (None, None),
+                    (Some(1), Some(1)),
+                    (Some(0), Some(0)),
+                    (Some(0), Some(0)),
+                    (None, None),
+                    (Some(1), Some(1)),
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
 
-    assert!(rb
-        .column(1)
-        .as_any()
-        .downcast_ref::<Float64Array>()
-        .unwrap()
-        .eq(&Float64Array::from(vec![
-            Some(10.9),
-            Some(5.2),
-            Some(-10.0),
-        ])));
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
 }
 
-pub fn verify_arrow_results(result: Vec<RecordBatch>) {
-    assert!(result.len() == 2);
+#[test]
+fn load_and_parse_int_as_decimal() {
+    let _ = env_logger::builder().is_test(true).try_init();
 
-    for r in result {
-        match r.num_rows() {
-            2 => {
-                assert!(r
-                    .column(0)
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .unwrap()
-                    .eq(&Int64Array::from(vec![1, 0])));
-                assert!(r
-                    .column(1)
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .unwrap()
-                    .eq(&Int64Array::from(vec![3, 5])));
-                assert!(r
-                    .column(2)
-                    .as_any()
-                    .downcast_ref::<StringArray>()
-                    .unwrap()
-                    .eq(&StringArray::from(vec!["str1", "a"])));
-                assert!(r
-                    .column(3)
-                    .as_any()
-                    .downcast_ref::<Float64Array>()
-                    .unwrap()
-                    .eq(&Float64Array::from(vec![None, Some(3.1)])));
-                assert!(r
-                    .column(4)
-                    .as_any()
-                    .downcast_ref::<BooleanArray>()
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (-2::int2, 1314::int4, 9223372036854775807::int8), (null, null, null)) as t(test_int2, test_int4, test_int8)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Vec<Option<Decimal>>> = (0..n)
+                .map(|_| (0..3).map(|_| parser.produce().unwrap()).collect())
+                .collect();
+            assert_eq!(
+                vec![
+                    vec![
+                        Some(Decimal::from(-2)),
+                        Some(Decimal::from(1314)),
+                        Some(Decimal::from(i64::MAX)),
+                    ],
+                    vec![None, None, None],
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_scaled_numeric() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (12.5::numeric(18,4), 1::numeric), (-0.0001::numeric(18,4), 2::numeric), (null::numeric(18,4), 3::numeric)) as t(test_scaled, test_unscaled)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.set_declared_scales(true);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut rows: Vec<Option<(i64, u32)>> = vec![];
+            for _ in 0..n {
+                rows.push(parser.produce().unwrap());
+                let unscaled: Result<Option<(i64, u32)>, _> = parser.produce();
+                assert!(matches!(
+                    unscaled,
+                    Err(PostgresSourceError::UndeclaredScale(_))
+                ));
+            }
+            assert_eq!(
+                vec![Some((125000, 4)), Some((-1, 4)), None],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_auto_protocol() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS hstore")
+        .unwrap();
+
+    let mut source = PostgresSource::<AutoProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, test_str from test_table where test_int = 1314",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    assert!(matches!(parser, PostgresAutoSourceParser::Binary(_)));
+    parser.fetch_next().unwrap();
+    let v: i32 = parser.produce().unwrap();
+    assert_eq!(1314, v);
+    let s: Option<&str> = parser.produce().unwrap();
+    assert_eq!(None, s);
+
+    // hstore has no binary representation connectorx can parse
+    let mut source = PostgresSource::<AutoProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select 1 as test_int, 'a=>1'::hstore as test_hstore",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    assert!(matches!(parser, PostgresAutoSourceParser::Cursor(_)));
+    parser.fetch_next().unwrap();
+    let v: i32 = parser.produce().unwrap();
+    assert_eq!(1, v);
+    let h: HashMap<String, Option<String>> = parser.produce().unwrap();
+    assert_eq!(Some(&Some("1".to_string())), h.get("a"));
+}
+
+#[test]
+fn load_and_parse_hstore_hybrid() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS hstore")
+        .unwrap();
+    let query = "select * from (values (1, 'a=>1'::hstore, 'x'), (2, null, 'y'), (3, 'b=>NULL', 'z')) as t(test_id, test_hstore, test_str)";
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    assert!(matches!(
+        source.fetch_metadata(),
+        Err(PostgresSourceError::UnsupportedType(..))
+    ));
+
+    // the only connection of the pool is taken by the partition, the second query opens its own
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_hybrid_key(Some("test_id".to_string()));
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<(i32, Option<HashMap<String, Option<String>>>, String)> = (0..n)
+        .map(|_| {
+            (
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                1,
+                Some(HashMap::from([("a".to_string(), Some("1".to_string()))])),
+                "x".to_string()
+            ),
+            (2, None, "y".to_string()),
+            (
+                3,
+                Some(HashMap::from([("b".to_string(), None)])),
+                "z".to_string()
+            ),
+        ],
+        rows
+    );
+}
+
+// whether `name` can be created on the server, for the tests of the types of an extension
+fn has_extension(client: &mut postgres::Client, name: &str) -> bool {
+    client
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = $1)",
+            &[&name],
+        )
+        .unwrap()
+        .get(0)
+}
+
+#[test]
+fn load_and_parse_pgvector() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    if !has_extension(&mut client, "vector") {
+        eprintln!("skipping, the server has no vector extension");
+        return;
+    }
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS vector")
+        .unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('[0.5,-1,2.25]'::vector), (null)) as t(test_vector)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let v: Option<Vec<f32>> = parser.produce().unwrap();
+            assert_eq!(Some(vec![0.5, -1., 2.25]), v, "{}", stringify!($proto));
+            let v: Option<Vec<f64>> = parser.produce().unwrap();
+            assert_eq!(None, v, "{}", stringify!($proto));
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select '[1,2]'::halfvec as test_halfvec")]);
+    assert!(matches!(
+        source.fetch_metadata(),
+        Err(PostgresSourceError::UnsupportedExtensionType(_, _))
+    ));
+}
+
+#[test]
+fn load_and_parse_sparsevec() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    if !has_extension(&mut client, "vector") {
+        eprintln!("skipping, the server has no vector extension");
+        return;
+    }
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS vector")
+        .unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('{1:0.5,3:-2.25}/5'::sparsevec), ('{}/3'::sparsevec), (null)) as t(test_sparsevec)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [PostgresTypeSystem::SparseVec(true)]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let v: Option<PgSparseVec> = parser.produce().unwrap();
+            assert_eq!(
+                Some(PgSparseVec {
+                    dim: 5,
+                    elements: vec![(1, 0.5), (3, -2.25)]
+                }),
+                v,
+                "{}",
+                stringify!($proto)
+            );
+            let v: Vec<(i32, f32)> = parser.produce().unwrap();
+            assert!(v.is_empty(), "{}", stringify!($proto));
+            let v: Option<Vec<(i32, f32)>> = parser.produce().unwrap();
+            assert_eq!(None, v, "{}", stringify!($proto));
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[cfg(feature = "postgis")]
+#[test]
+fn load_and_parse_postgis_geometry() {
+    use geo_types::{line_string, point, polygon, Geometry, MultiPoint};
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS postgis")
+        .unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('SRID=4326;POINT(1 2)'::geometry), ('POLYGON((0 0,1 0,1 1,0 0))'::geometry), ('MULTIPOINT(1 2 3,4 5 6)'::geometry), ('LINESTRING(0 0,2 1)'::geography), (null)) as t(test_geom)",
+    )];
+    let expected: Vec<Option<Geometry<f64>>> = vec![
+        Some(point!(x: 1., y: 2.).into()),
+        Some(polygon![(x: 0., y: 0.), (x: 1., y: 0.), (x: 1., y: 1.), (x: 0., y: 0.)].into()),
+        Some(MultiPoint(vec![point!(x: 1., y: 2.), point!(x: 4., y: 5.)]).into()),
+        Some(line_string![(x: 0., y: 0.), (x: 2., y: 1.)].into()),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [PostgresTypeSystem::Geometry(true)]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            {
+                let mut parser = partition.parser().unwrap();
+                let (n, _) = parser.fetch_next().unwrap();
+                let rows: Vec<Option<Geometry<f64>>> =
+                    (0..n).map(|_| parser.produce().unwrap()).collect();
+                assert_eq!(expected, rows, "{}", stringify!($proto));
+            }
+
+            // the raw EWKB, with the SRID of the point
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let hex: String = parser.produce().unwrap();
+            assert_eq!(
+                "0101000020E6100000000000000000F03F0000000000000040",
+                hex,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_flatten_composites() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TYPE IF EXISTS cx_outer; DROP TYPE IF EXISTS cx_pair;
+            CREATE TYPE cx_pair AS (x int4, y text);
+            CREATE TYPE cx_outer AS (p cx_pair, z float8);",
+        )
+        .unwrap();
+    let queries = [CXQuery::naked(
+        "select test_int, row(row(test_int, test_str)::cx_pair, test_float)::cx_outer as test_outer from test_table where test_int < 2 order by test_int",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_flatten_composites(true);
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            assert_eq!(
+                vec![
+                    "test_int",
+                    "test_outer.p.x",
+                    "test_outer.p.y",
+                    "test_outer.z"
+                ],
+                source.names()
+            );
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut rows: Vec<(i32, Option<i32>, Option<String>, Option<f64>)> = vec![];
+            for _ in 0..n {
+                let id: i32 = parser.produce().unwrap();
+                let x: Option<i32> = parser.produce().unwrap();
+                let y: Option<&str> = parser.produce().unwrap();
+                let z: Option<f64> = parser.produce().unwrap();
+                rows.push((id, x, y.map(ToString::to_string), z));
+            }
+            assert_eq!(
+                vec![
+                    (0, Some(0), Some("a".to_string()), Some(3.1)),
+                    (1, Some(1), Some("str1".to_string()), None),
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_bytea_reader() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (decode(repeat('ab', 1000000), 'hex')), (null)) as t(test_blob)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+
+            let mut blob: &[u8] = parser.produce().unwrap();
+            let mut sink = std::io::sink();
+            let copied = std::io::copy(&mut blob, &mut sink).unwrap();
+            assert_eq!(1000000, copied, "{}", stringify!($proto));
+            let blob: Option<&[u8]> = parser.produce().unwrap();
+            assert_eq!(None, blob, "{}", stringify!($proto));
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_bytea_slice_matches_vec() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select v as test_slice, v as test_vec from (values ('\\x00ff10'::bytea), (''::bytea), (decode(repeat('ab', 100000), 'hex')), (null)) as t(v)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            assert_eq!(4, n);
+            for _ in 0..n {
+                let slice: Option<Vec<u8>> = Produce::<Option<&[u8]>>::produce(&mut parser)
                     .unwrap()
-                    .eq(&BooleanArray::from(vec![Some(true), None])));
+                    .map(<[u8]>::to_vec);
+                let vec: Option<Vec<u8>> = parser.produce().unwrap();
+                assert_eq!(vec, slice, "{}", stringify!($proto));
+            }
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_node_tree() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute("CREATE TABLE IF NOT EXISTS cx_checked (x int4 CHECK (x > 0))")
+        .unwrap();
+    let queries = [CXQuery::naked(
+        "select conbin from pg_constraint where conrelid = 'cx_checked'::regclass and contype = 'c'",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let tree: Option<&str> = parser.produce().unwrap();
+            assert!(
+                tree.unwrap().starts_with("{OPEXPR"),
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+}
+
+#[test]
+fn load_and_parse_catalog_vectors() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('1 3 2'::int2vector, '23 25'::oidvector), ('', '')) as t(test_int2vector, test_oidvector)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(Vec<i16>, Vec<u32>)> = (0..n)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(
+                vec![(vec![1, 3, 2], vec![23, 25]), (vec![], vec![])],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_bool_coercion() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (1, 'Y'::text), (0, 'n'), (null, null), (2, 'maybe')) as t(test_flag, test_yn)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_bool_coercion(Some(BoolCoercion::default()));
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let rows: Vec<(Option<bool>, Option<bool>)> = (0..3)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(
+                vec![(Some(true), Some(true)), (Some(false), Some(false)), (None, None)],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+            let unknown: Result<Option<bool>, _> = parser.produce();
+            assert!(
+                matches!(unknown, Err(PostgresSourceError::NotABool(ref name, ref value)) if name == "test_flag" && value == "2"),
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_bool_across_protocols() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (true, B'1', 1::int2, 'yes'::varchar, 1::numeric), (false, B'0', 0::int2, 'no'::varchar, 0::numeric), (null, null, null, null, 2::numeric)) as t(test_bool, test_bit, test_int, test_text, test_numeric)",
+    )];
+
+    macro_rules! read {
+        ($proto: ty, $coercion: expr) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_bool_coercion($coercion);
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            // a value that cannot be produced is read as `None`, so failures are compared too
+            let mut columns: Vec<Vec<Option<Option<bool>>>> = vec![vec![]; 5];
+            for _ in 0..3 {
+                for column in columns.iter_mut() {
+                    column.push(parser.produce().ok());
+                }
+            }
+            columns
+        }};
+    }
+
+    for coercion in [None, Some(BoolCoercion::default())] {
+        let binary = read!(BinaryProtocol, coercion.clone());
+        assert_eq!(read!(CSVProtocol, coercion.clone()), binary);
+        assert_eq!(read!(CursorProtocol, coercion.clone()), binary);
+
+        let expected = vec![Some(Some(true)), Some(Some(false)), Some(None)];
+        assert_eq!(expected, binary[0]);
+        assert_eq!(expected, binary[1]);
+        if coercion.is_some() {
+            assert_eq!(expected, binary[2]);
+            assert_eq!(expected, binary[3]);
+        } else {
+            assert_eq!(vec![None, None, Some(None)], binary[2]);
+            assert_eq!(vec![None, None, Some(None)], binary[3]);
+        }
+        // a numeric column is never coerced
+        assert_eq!(vec![None, None, None], binary[4]);
+    }
+}
+
+#[test]
+fn load_and_parse_decode_error_policy() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_decode_error_policy(DecodeErrorPolicy::Null);
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values ('1'::text, '1.5'::text), ('x', 'NaN'), (null, 'abc')) as t(test_int, test_num)",
+    )]);
+    source.fetch_metadata().unwrap();
+    let report = source.decode_errors();
+
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let rows: Vec<(Option<i64>, Option<Decimal>)> = (0..3)
+        .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+        .collect();
+    assert_eq!(
+        vec![
+            (Some(1), Some(Decimal::new(15, 1))),
+            (None, None),
+            (None, None)
+        ],
+        rows
+    );
+
+    let counts = report.counts();
+    assert_eq!(Some(&1), counts.get("test_int"));
+    assert_eq!(Some(&2), counts.get("test_num"));
+}
+
+#[test]
+fn load_and_skip_undecodable_rows() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    // a numeric NaN has no decimal, a "char" decodes on every protocol
+    let query = "select * from (values (1, 1.5::numeric, 'a'::\"char\"), (2, 'NaN'::numeric, 'b'), (3, null, null), (4, 'NaN'::numeric, 'd')) as t(test_int, test_num, test_char)";
+    let expected = vec![(1, Some(Decimal::new(15, 1)), Some(b'a')), (3, None, None)];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_decode_error_policy(DecodeErrorPolicy::SkipRow);
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let report = source.decode_errors();
+
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, is_last) = parser.fetch_next().unwrap();
+            assert!(is_last);
+            let rows: Vec<(i32, Option<Decimal>, Option<u8>)> = (0..n)
+                .map(|_| {
+                    (
+                        parser.produce().unwrap(),
+                        parser.produce().unwrap(),
+                        parser.produce().unwrap(),
+                    )
+                })
+                .collect();
+            assert_eq!(expected, rows);
+            assert_eq!(2, parser.skipped_rows());
+            assert_eq!(2, report.skipped_rows());
+
+            // the rows dropped are not known before the read
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_decode_error_policy(DecodeErrorPolicy::SkipRow);
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.set_origin_query(Some(query.to_string()));
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.result_rows(),
+                Err(PostgresSourceError::SkipRowCounted(_))
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            assert!(matches!(
+                partition.result_rows(),
+                Err(PostgresSourceError::SkipRowCounted(_))
+            ));
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_decode_fallback() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_decode_error_policy(DecodeErrorPolicy::Null);
+    source
+        .set_decode_fallback(PostgresTypeSystem::Numeric(true), "0".to_string())
+        .unwrap();
+    source
+        .set_decode_fallback(PostgresTypeSystem::Date(true), "1970-01-01".to_string())
+        .unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values ('1.5'::text, '2020-01-02'::text, '1'::text), ('abc', 'not a date', 'x'), ('NaN', null, null)) as t(test_num, test_date, test_int)",
+    )]);
+    source.fetch_metadata().unwrap();
+    let report = source.decode_errors();
+
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let rows: Vec<(Option<Decimal>, Option<NaiveDate>, Option<i64>)> = (0..3)
+        .map(|_| {
+            (
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                Some(Decimal::new(15, 1)),
+                Some(NaiveDate::from_ymd(2020, 1, 2)),
+                Some(1)
+            ),
+            (
+                Some(Decimal::new(0, 0)),
+                Some(NaiveDate::from_ymd(1970, 1, 1)),
+                None
+            ),
+            (Some(Decimal::new(0, 0)), None, None)
+        ],
+        rows
+    );
+
+    // the int has no fallback, so the policy reads it as NULL
+    let fallbacks = report.fallback_counts();
+    assert_eq!(Some(&2), fallbacks.get("test_num"));
+    assert_eq!(Some(&1), fallbacks.get("test_date"));
+    assert_eq!(None, fallbacks.get("test_int"));
+    assert_eq!(Some(&1), report.counts().get("test_int"));
+}
+
+#[test]
+fn load_and_parse_date_from_timestamp() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('2020-01-01'::text), ('2020-01-01 00:00:00')) as t(test_date)",
+    )];
+
+    for strict in [false, true] {
+        let mut source =
+            PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_strict_dates(strict);
+        source.set_queries(&queries);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        parser.fetch_next().unwrap();
+
+        let date = NaiveDate::from_ymd(2020, 1, 1);
+        let first: NaiveDate = parser.produce().unwrap();
+        assert_eq!(date, first);
+        let second: Result<NaiveDate, _> = parser.produce();
+        match strict {
+            false => assert_eq!(date, second.unwrap()),
+            true => assert!(second.is_err()),
+        }
+    }
+}
+
+#[test]
+fn load_and_parse_sample() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let sample = PostgresSample {
+        percent: 100.0,
+        rows: 2,
+        seed: 0,
+    };
+
+    let count = |queries: &[&str]| -> Result<Vec<usize>, PostgresSourceError> {
+        let mut source =
+            PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 2).unwrap();
+        source.set_sample(Some(sample));
+        source.set_queries(&queries.iter().map(CXQuery::naked).collect::<Vec<_>>());
+        source.fetch_metadata()?;
+        let mut counts = vec![];
+        for mut partition in source.partition()? {
+            let mut parser = partition.parser()?;
+            counts.push(parser.fetch_next()?.0);
+        }
+        Ok(counts)
+    };
+
+    // all the pages of a single table, also through the partition queries wrapping it
+    assert_eq!(
+        vec![6],
+        count(&["select test_int from test_table t"]).unwrap()
+    );
+    assert_eq!(
+        vec![2, 4],
+        count(&[
+            "select * from (select * from test_table) as CXTMPTAB_PART where test_int < 2",
+            "select * from (select * from test_table) as CXTMPTAB_PART where test_int >= 2",
+        ])
+        .unwrap()
+    );
+
+    // anything else falls back to picking rows at random, which cannot be partitioned
+    let values = "select * from (values (1), (2), (3)) as t(v)";
+    assert_eq!(vec![2], count(&[values]).unwrap());
+    assert!(matches!(
+        count(&[values, values]),
+        Err(PostgresSourceError::UnsampleablePartitions(_))
+    ));
+}
+
+#[test]
+fn load_and_parse_pg_attribute() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select attname, atttypid, attnum, attlen, attnotnull, array[attrelid, atttypid] as oids \
+         from pg_attribute where attrelid = 'test_table'::regclass and attnum > 0 order by attnum",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut rows = vec![];
+            for _ in 0..n {
+                let name: &str = parser.produce().unwrap();
+                let name = name.to_string();
+                let typid: u32 = parser.produce().unwrap();
+                let num: i16 = parser.produce().unwrap();
+                let len: i16 = parser.produce().unwrap();
+                let notnull: bool = parser.produce().unwrap();
+                let oids: Vec<u32> = parser.produce().unwrap();
+                assert_eq!(typid, oids[1], "{}", stringify!($proto));
+                rows.push((name, typid, num, len, notnull));
+            }
+            assert_eq!(
+                vec![
+                    ("test_int".to_string(), 23, 1, 4, true),
+                    ("test_nullint".to_string(), 23, 2, 4, false),
+                    ("test_str".to_string(), 25, 3, -1, false),
+                    ("test_float".to_string(), 701, 4, 8, false),
+                    ("test_bool".to_string(), 16, 5, 1, false),
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_values_literals() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('a', 1), ('b', null)) as v(test_str, test_int)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(String, Option<i32>)> = (0..n)
+                .map(|_| {
+                    (
+                        Produce::<&str>::produce(&mut parser).unwrap().to_string(),
+                        parser.produce().unwrap(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                vec![("a".to_string(), Some(1)), ("b".to_string(), None)],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_numeric_nan() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (1.5::numeric), ('NaN'::numeric)) as t(test_nan)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source = PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            assert_eq!(2, n);
+            assert_eq!(
+                Some(Decimal::new(15, 1)),
+                Produce::<Option<Decimal>>::produce(&mut parser).unwrap()
+            );
+            let err = Produce::<Option<Decimal>>::produce(&mut parser).unwrap_err();
+            assert!(
+                matches!(&err, PostgresSourceError::NumericNaN(name) if name == "test_nan"),
+                "{}",
+                err
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CursorProtocol);
+    check!(CSVProtocol);
+}
+
+#[test]
+fn load_and_parse_foreign_table() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    // loop test_table back into the same database through postgres_fdw
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(&format!(
+            "CREATE EXTENSION IF NOT EXISTS postgres_fdw;
+            DROP SCHEMA IF EXISTS cx_fdw CASCADE;
+            DROP SERVER IF EXISTS cx_loopback CASCADE;
+            DO $$ BEGIN
+                EXECUTE format('CREATE SERVER cx_loopback FOREIGN DATA WRAPPER postgres_fdw OPTIONS (host %L, port %L, dbname %L)',
+                    'localhost', current_setting('port'), current_database());
+            END $$;
+            CREATE USER MAPPING FOR CURRENT_USER SERVER cx_loopback OPTIONS (user '{}', password '{}');
+            CREATE SCHEMA cx_fdw;
+            IMPORT FOREIGN SCHEMA public LIMIT TO (test_table) FROM SERVER cx_loopback INTO cx_fdw;",
+            url.username(),
+            url.password().unwrap_or_default(),
+        ))
+        .unwrap();
+
+    // COPY of a query plans it like any other select, so the foreign scan runs for
+    // the COPY protocols as well and none of them has to fall back to the cursor.
+    let queries = [CXQuery::naked(
+        "select test_int, test_nullint from cx_fdw.test_table order by test_int",
+    )];
+    let expected = vec![
+        (0, Some(5)),
+        (1, Some(3)),
+        (2, None),
+        (3, Some(7)),
+        (4, Some(9)),
+        (1314, Some(2)),
+    ];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(i32, Option<i32>)> = (0..n)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(expected, rows, "{}", stringify!($proto));
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+
+    client
+        .batch_execute("DROP SCHEMA cx_fdw CASCADE; DROP SERVER cx_loopback CASCADE;")
+        .unwrap();
+}
+
+#[test]
+fn load_and_parse_stream_error() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    // the division fails on the server once 999 rows have been sent
+    let queries = [CXQuery::naked(
+        "select 1 / (1000 - g) as test_div from generate_series(1, 2000) as g",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let err = loop {
+                match parser.fetch_next() {
+                    Ok((n, false)) => {
+                        for _ in 0..n {
+                            let _: i32 = parser.produce().unwrap();
+                        }
+                    }
+                    Ok((_, true)) => panic!("{} read past the error", stringify!($proto)),
+                    Err(e) => break e,
+                }
+            };
+            match err {
+                PostgresSourceError::StreamError(query, nrows, _) => {
+                    assert_eq!(queries[0].to_string(), query);
+                    assert_eq!(999, nrows, "{}", stringify!($proto));
+                }
+                e => panic!("{} unexpected error {}", stringify!($proto), e),
+            }
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_exclude_generated() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS test_generated;
+            CREATE TABLE test_generated(
+                test_int INTEGER NOT NULL,
+                test_double INTEGER GENERATED ALWAYS AS (test_int * 2) STORED,
+                test_str TEXT
+            );
+            INSERT INTO test_generated(test_int, test_str) VALUES (1, 'a'), (2, 'b');",
+        )
+        .unwrap();
+
+    let load = |query: &str| {
+        let mut source =
+            PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_exclude_generated(true);
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let names = source.names();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        parser.fetch_next().unwrap();
+        (names, parser.produce_all::<i32>(0).unwrap())
+    };
+
+    // the generated column is dropped from `*`
+    assert_eq!(
+        (
+            vec!["test_int".to_string(), "test_str".to_string()],
+            vec![1, 2]
+        ),
+        load("select * from test_generated order by test_int")
+    );
+    // but kept when named explicitly
+    assert_eq!(
+        (
+            vec!["test_double".to_string(), "test_str".to_string()],
+            vec![2, 4]
+        ),
+        load("select test_double, test_str from test_generated order by test_int")
+    );
+
+    client.batch_execute("DROP TABLE test_generated;").unwrap();
+}
+
+#[test]
+fn load_and_parse_batch() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let sources = source
+        .split_batch(
+            "select test_int from test_table where test_int < 2 order by test_int; \
+             select test_str, test_float from test_table where test_int = 0",
+        )
+        .unwrap();
+    assert_eq!(2, sources.len());
+
+    let mut sources = sources.into_iter();
+    let mut first = sources.next().unwrap();
+    first.fetch_metadata().unwrap();
+    assert_eq!(vec!["test_int".to_string()], first.names());
+    let mut partition = first.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    assert_eq!(vec![0, 1], parser.produce_all::<i32>(0).unwrap());
+
+    let mut second = sources.next().unwrap();
+    second.fetch_metadata().unwrap();
+    assert_eq!(
+        vec!["test_str".to_string(), "test_float".to_string()],
+        second.names()
+    );
+    let mut partition = second.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    assert_eq!(vec!["a"], parser.produce_all::<&str>(0).unwrap());
+    assert_eq!(vec![3.1], parser.produce_all::<f64>(1).unwrap());
+
+    assert!(source
+        .split_batch("select 1; set search_path = public")
+        .is_err());
+}
+
+#[test]
+fn load_and_parse_materialized() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 4).unwrap();
+    source
+        .materialize(
+            "select t1.test_int, count(*)::int as test_count from test_table t1 join test_table t2 on t1.test_int >= t2.test_int group by t1.test_int",
+            3,
+        )
+        .unwrap();
+    source.fetch_metadata().unwrap();
+    assert_eq!(
+        vec!["test_int".to_string(), "test_count".to_string()],
+        source.names()
+    );
+
+    let mut rows: Vec<(i32, i32)> = vec![];
+    for mut partition in source.partition().unwrap() {
+        let mut parser = partition.parser().unwrap();
+        loop {
+            let (n, is_last) = parser.fetch_next().unwrap();
+            for _ in 0..n {
+                rows.push((parser.produce().unwrap(), parser.produce().unwrap()));
+            }
+            if is_last {
+                break;
+            }
+        }
+    }
+    rows.sort();
+    assert_eq!(
+        vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (1314, 6)],
+        rows
+    );
+
+    // the table is dropped along with the partitions
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    let remaining: i64 = client
+        .query_one(
+            "select count(*) from pg_class where relname like 'cx_materialized_%'",
+            &[],
+        )
+        .unwrap()
+        .get(0);
+    assert_eq!(0, remaining);
+
+    // a table left by a killed process is dropped once old enough
+    client
+        .batch_execute("create unlogged table cx_materialized_1_1 (test_int int)")
+        .unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    assert_eq!(
+        vec!["cx_materialized_1_1".to_string()],
+        source.drop_materialized(Duration::from_secs(3600)).unwrap()
+    );
+}
+
+#[test]
+fn load_and_parse_lazy_nrows() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked("select * from test_table")];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let partition = source.partition().unwrap().remove(0);
+    assert_eq!(0, partition.nrows());
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_lazy_nrows(true);
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let partition = source.partition().unwrap().remove(0);
+    assert_eq!(6, partition.nrows());
+    assert_eq!(6, partition.nrows());
+}
+
+#[test]
+fn load_and_parse_filtered() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let load = |sql: &str| -> Vec<i64> {
+        let mut source =
+            PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_queries(&[CXQuery::naked(sql)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        (0..n).map(|_| parser.produce().unwrap()).collect()
+    };
+
+    // appended to the existing WHERE
+    let sql = filter_query(
+        "select test_int::int8 from test_table where test_int < 4 or test_int = 1314 order by test_int",
+        "test_int > 1 or test_int is null",
+        &PostgreSqlDialect {},
+    )
+    .unwrap();
+    assert_eq!(vec![2, 3, 1314], load(&sql));
+
+    // an alias is only visible in the result
+    let sql = filter_query(
+        "select test_int::int8 as test_alias from test_table where test_int < 4",
+        "test_alias > 2",
+        &PostgreSqlDialect {},
+    )
+    .unwrap();
+    assert_eq!(vec![3], load(&sql));
+
+    // filters the result of the aggregation
+    let sql = filter_query(
+        "select count(*) as cnt from test_table group by test_bool",
+        "cnt > 2",
+        &PostgreSqlDialect {},
+    )
+    .unwrap();
+    assert_eq!(Vec::<i64>::new(), load(&sql));
+    let sql = filter_query(
+        "select count(*) as cnt from test_table",
+        "cnt > 2",
+        &PostgreSqlDialect {},
+    )
+    .unwrap();
+    assert_eq!(vec![6], load(&sql));
+
+    // a predicate cannot end the statement
+    assert!(filter_query(
+        "select * from test_table",
+        "true; drop table test_table",
+        &PostgreSqlDialect {},
+    )
+    .is_err());
+}
+
+#[test]
+fn load_and_parse_conn_wait() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let waits = Arc::new(Mutex::new(vec![]));
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let waits_cb = waits.clone();
+    source.set_conn_wait_callback(Arc::new(move |query, wait| {
+        waits_cb
+            .lock()
+            .unwrap()
+            .push((query.as_str().to_string(), wait))
+    }));
+    source.set_queries(&[
+        CXQuery::naked("select * from test_table where test_int < 2"),
+        CXQuery::naked("select * from test_table where test_int >= 2"),
+    ]);
+    source.fetch_metadata().unwrap();
+    let partitions = source.partition().unwrap();
+
+    let waits = waits.lock().unwrap();
+    assert_eq!(
+        vec![
+            "select * from test_table where test_int < 2",
+            "select * from test_table where test_int >= 2"
+        ],
+        waits.iter().map(|(q, _)| q.as_str()).collect::<Vec<_>>()
+    );
+    for (partition, (_, wait)) in partitions.iter().zip(waits.iter()) {
+        assert_eq!(*wait, partition.conn_wait());
+    }
+}
+
+#[test]
+fn load_and_parse_warm_up() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    source.set_warm_up(true);
+    source.set_queries(&[
+        CXQuery::naked("select test_int from test_table where test_int < 2"),
+        CXQuery::naked("select test_int from test_table where test_int >= 2"),
+    ]);
+    source.fetch_metadata().unwrap();
+    let mut nrows = 0;
+    for mut partition in source.partition().unwrap() {
+        let mut parser = partition.parser().unwrap();
+        loop {
+            let (n, is_last) = parser.fetch_next().unwrap();
+            nrows += n;
+            if is_last {
+                break;
+            }
+        }
+    }
+    assert_eq!(6, nrows);
+}
+
+#[test]
+fn load_and_parse_no_columns() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select from test_table")]);
+
+    assert!(matches!(
+        source.fetch_metadata(),
+        Err(PostgresSourceError::NoColumns(_))
+    ));
+}
+
+#[test]
+fn load_and_parse_produce_all() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select test_int, test_nullint from test_table",
+    )];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    assert_eq!(
+        vec![1, 2, 0, 3, 4, 1314],
+        parser.produce_all::<i32>(0).unwrap()
+    );
+    assert_eq!(
+        vec![Some(3), None, Some(5), Some(7), Some(9), Some(2)],
+        parser.produce_all::<Option<i32>>(1).unwrap()
+    );
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    assert_eq!(
+        vec![Some(3), None, Some(5), Some(7), Some(9), Some(2)],
+        parser.produce_all::<i32>(1).unwrap()
+    );
+}
+
+#[test]
+fn load_and_parse_binary_uuid_array() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values (array['86b494cc-96b2-11eb-9298-3e22fbb9fe9d', '86b49b84-96b2-11eb-9298-3e22fbb9fe9d']::uuid[]), ('{}'), (null)) as t(test_uuidarray)",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<Vec<Uuid>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+
+    assert_eq!(
+        vec![
+            Some(vec![
+                Uuid::parse_str("86b494cc-96b2-11eb-9298-3e22fbb9fe9d").unwrap(),
+                Uuid::parse_str("86b49b84-96b2-11eb-9298-3e22fbb9fe9d").unwrap(),
+            ]),
+            Some(vec![]),
+            None,
+        ],
+        rows
+    );
+}
+
+#[test]
+fn load_and_fill_record_batch() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, test_nullint, test_str, test_float, date '2000-02-28' + test_int % 2 as test_date from test_table order by test_int",
+    )]);
+    source.fetch_metadata().unwrap();
+    let names = source.names();
+    let schema = source.schema();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+
+    let rb = fill_record_batch(&parser, &names, &schema).unwrap();
+    assert_eq!(
+        vec![
+            "test_int",
+            "test_nullint",
+            "test_str",
+            "test_float",
+            "test_date"
+        ],
+        rb.schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>()
+    );
+    assert!(rb
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .eq(&Int64Array::from(vec![0, 1, 2, 3, 4, 1314])));
+    assert!(rb
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .eq(&Int64Array::from(vec![
+            Some(5),
+            Some(3),
+            None,
+            Some(7),
+            Some(9),
+            Some(2)
+        ])));
+    assert!(rb
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap()
+        .eq(&StringArray::from(vec![
+            Some("a"),
+            Some("str1"),
+            Some("str2"),
+            Some("b"),
+            Some("c"),
+            None
+        ])));
+    assert!(rb
+        .column(3)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap()
+        .eq(&Float64Array::from(vec![
+            Some(3.1),
+            None,
+            Some(2.2),
+            Some(3.),
+            Some(7.8),
+            Some(-10.)
+        ])));
+    assert!(rb
+        .column(4)
+        .as_any()
+        .downcast_ref::<Date32Array>()
+        .unwrap()
+        .eq(&Date32Array::from(vec![
+            11015, 11016, 11015, 11016, 11015, 11015
+        ])));
+}
+
+#[test]
+fn load_and_fill_record_batch_timestamptz_range() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    // both out of the range of nanoseconds since the epoch
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values ('0001-01-01 00:00:00+00'::timestamptz), ('3000-01-01 00:00:00.000001+00'::timestamptz), (null)) as t(test_timestamptz)",
+    )]);
+    source.fetch_metadata().unwrap();
+    let names = source.names();
+    let schema = source.schema();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+
+    let rb = fill_record_batch(&parser, &names, &schema).unwrap();
+    assert!(rb
+        .column(0)
+        .as_any()
+        .downcast_ref::<TimestampMicrosecondArray>()
+        .unwrap()
+        .eq(&TimestampMicrosecondArray::from_opt_vec(
+            vec![
+                Some(-62_135_596_800_000_000),
+                Some(32_503_680_000_000_001),
+                None
+            ],
+            Some("UTC".to_string())
+        )));
+}
+
+#[test]
+fn load_and_write_ipc_stream() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, test_str from test_table order by test_int",
+    )]);
+    source.fetch_metadata().unwrap();
+    let names = source.names();
+    let schema = source.schema();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let bytes = write_ipc_stream(&mut parser, &names, &schema, vec![]).unwrap();
+
+    let reader = StreamReader::try_new(Cursor::new(bytes), None).unwrap();
+    assert_eq!(arrow_schema(&names, &schema).unwrap(), *reader.schema());
+    let mut ints = vec![];
+    for batch in reader {
+        let batch = batch.unwrap();
+        let column = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        ints.extend(column.iter().map(Option::unwrap));
+    }
+    assert_eq!(vec![0, 1, 2, 3, 4, 1314], ints);
+}
+
+#[test]
+fn load_and_parse_cursor_arrays() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    #[derive(Debug, PartialEq)]
+    struct Row(Option<Vec<String>>, Option<Vec<bool>>, Option<Vec<Uuid>>);
+
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values (array['a', 'b,c', '\"d\"']::text[], array[true, false], array['86b494cc-96b2-11eb-9298-3e22fbb9fe9d']::uuid[]), (null, '{}', null)) as t(test_strarray, test_boolarray, test_uuidarray)",
+    )]);
+    source.fetch_metadata().unwrap();
+
+    let mut partitions = source.partition().unwrap();
+    assert!(partitions.len() == 1);
+    let mut partition = partitions.remove(0);
+    partition.result_rows().expect("run query");
+
+    let mut parser = partition.parser().unwrap();
+
+    let mut rows: Vec<Row> = Vec::new();
+    loop {
+        let (n, is_last) = parser.fetch_next().unwrap();
+        for _i in 0..n {
+            rows.push(Row(
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+                parser.produce().unwrap(),
+            ));
+        }
+        if is_last {
+            break;
+        }
+    }
+
+    assert_eq!(
+        vec![
+            Row(
+                Some(vec!["a".into(), "b,c".into(), "\"d\"".into()]),
+                Some(vec![true, false]),
+                Some(vec![Uuid::parse_str(
+                    "86b494cc-96b2-11eb-9298-3e22fbb9fe9d"
+                )
+                .unwrap()]),
+            ),
+            Row(None, Some(vec![]), None),
+        ],
+        rows
+    );
+}
+
+#[test]
+fn test_postgres() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+
+    let queries = [
+        CXQuery::naked("select * from test_table where test_int < 2"),
+        CXQuery::naked("select * from test_table where test_int >= 2"),
+    ];
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let mut destination = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
+        builder,
+        &mut destination,
+        &queries,
+        Some(String::from("select * from test_table")),
+    );
+
+    dispatcher.run().expect("run dispatcher");
+
+    let result = destination.arrow().unwrap();
+    verify_arrow_results(result);
+}
+
+#[test]
+fn test_postgres_csv() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+
+    let queries = [
+        CXQuery::naked("select * from test_table where test_int < 2"),
+        CXQuery::naked("select * from test_table where test_int >= 2"),
+    ];
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let builder = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let mut dst = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<CSVProtocol, NoTls>>::new(
+        builder, &mut dst, &queries, None,
+    );
+
+    dispatcher.run().expect("run dispatcher");
+    let result = dst.arrow().unwrap();
+    verify_arrow_results(result);
+}
+
+#[test]
+fn test_postgres_reset() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+
+    let queries = [
+        CXQuery::naked("select * from test_table where test_int < 2"),
+        CXQuery::naked("select * from test_table where test_int >= 2"),
+    ];
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+
+    for _ in 0..2 {
+        let mut dst = ArrowDestination::new();
+        let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
+            builder.reset(),
+            &mut dst,
+            &queries,
+            None,
+        );
+
+        dispatcher.run().expect("run dispatcher");
+        let result = dst.arrow().unwrap();
+        verify_arrow_results(result);
+    }
+}
+
+#[test]
+fn test_postgres_more_queries_than_connections() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let queries: Vec<_> = (0..5)
+        .map(|i| {
+            CXQuery::naked(format!(
+                "select * from test_table where test_int % 5 = {}",
+                i
+            ))
+        })
+        .collect();
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let source = PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 2).unwrap();
+            let mut dst = ArrowDestination::new();
+            let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<$proto, NoTls>>::new(
+                source, &mut dst, &queries, None,
+            );
+            dispatcher.run().expect("run dispatcher");
+
+            let result = dst.arrow().unwrap();
+            let nrows: usize = result.iter().map(|batch| batch.num_rows()).sum();
+            assert_eq!(6, nrows);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn test_postgres_more_queries_than_connections_in_sequence() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let queries: Vec<_> = (0..5)
+        .map(|i| {
+            CXQuery::naked(format!(
+                "select test_int from test_table where test_int % 5 = {}",
+                i
+            ))
+        })
+        .collect();
+    let pool_config = PostgresPoolConfig {
+        conn_wait_timeout: Duration::from_secs(1),
+        ..Default::default()
+    };
+    let new_source = || {
+        let mut source = PostgresSource::<BinaryProtocol, NoTls>::new_with_pool_config(
+            config.clone(),
+            NoTls,
+            2,
+            pool_config.clone(),
+        )
+        .unwrap();
+        source.set_queries(&queries);
+        source.fetch_metadata().unwrap();
+        source
+    };
+
+    // a partition gives its connection back as it is dropped, for the next one to read
+    let mut nrows = 0;
+    for mut partition in new_source().partition().unwrap() {
+        let mut parser = partition.parser().unwrap();
+        loop {
+            let (n, is_last) = parser.fetch_next().unwrap();
+            for _ in 0..n {
+                let _: Option<i32> = parser.produce().unwrap();
+            }
+            nrows += n;
+            if is_last {
+                break;
+            }
+        }
+    }
+    assert_eq!(6, nrows);
+
+    // while partitions holding all the connections are kept, the next one fails rather than
+    // waiting forever
+    let mut partitions = new_source().partition().unwrap();
+    for partition in &mut partitions[..2] {
+        partition.parser().unwrap();
+    }
+    assert!(matches!(
+        partitions[2].parser(),
+        Err(PostgresSourceError::ConnectionWait(2, _))
+    ));
+}
+
+#[test]
+fn test_postgres_stale_connection() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut pool_config = config.clone();
+    pool_config.application_name("cx_stale_connection");
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(pool_config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select * from test_table")]);
+    source.fetch_metadata().unwrap();
+
+    // kill the idle connection sitting in the pool
+    let mut client = config.connect(NoTls).unwrap();
+    client
+        .execute(
+            "select pg_terminate_backend(pid) from pg_stat_activity where application_name = 'cx_stale_connection'",
+            &[],
+        )
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(6, n);
+}
+
+#[test]
+fn test_postgres_keepalive_config() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let pool_config = PostgresPoolConfig {
+        keepalives_idle: Some(Duration::from_secs(10)),
+        connect_timeout: Some(Duration::from_secs(5)),
+        ..Default::default()
+    };
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new_with_pool_config(
+        config,
+        NoTls,
+        1,
+        pool_config,
+    )
+    .unwrap();
+    source.set_queries(&[CXQuery::naked("select * from test_table")]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(6, n);
+}
+
+#[test]
+fn test_postgres_password_provider() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (mut config, _tls) = rewrite_tls_args(&url).unwrap();
+    let password = config.get_password().unwrap_or_default().to_vec();
+    // only the provider knows the password
+    config.password("expired");
+    let calls = Arc::new(Mutex::new(0));
+    let pool_config = PostgresPoolConfig {
+        password_provider: Some({
+            let calls = calls.clone();
+            Arc::new(move || {
+                *calls.lock().unwrap() += 1;
+                Ok(String::from_utf8(password.clone())?)
+            })
+        }),
+        ..Default::default()
+    };
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new_with_pool_config(
+        config,
+        NoTls,
+        2,
+        pool_config,
+    )
+    .unwrap();
+    source.set_queries(&[
+        CXQuery::naked("select * from test_table where test_int < 2"),
+        CXQuery::naked("select * from test_table where test_int >= 2"),
+    ]);
+    source.fetch_metadata().unwrap();
+    let mut nrows = 0;
+    for mut partition in source.partition().unwrap() {
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        nrows += n;
+    }
+    assert_eq!(6, nrows);
+    // once for each connection the pool opened
+    assert_eq!(2, *calls.lock().unwrap());
+}
+
+#[test]
+fn test_postgres_agg() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+
+    let queries = [CXQuery::naked(
+        "SELECT test_bool, SUM(test_float) FROM test_table GROUP BY test_bool",
+    )];
+
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    let mut destination = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
+        builder,
+        &mut destination,
+        &queries,
+        Some(format!(
+            "SELECT test_bool, SUM(test_float) FROM test_table GROUP BY test_bool"
+        )),
+    );
+
+    dispatcher.run().expect("run dispatcher");
+
+    let mut result = destination.arrow().unwrap();
+    assert!(result.len() == 1);
+    let rb = result.pop().unwrap();
+    assert!(rb.columns().len() == 2);
+
+    assert!(rb
+        .column(0)
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .unwrap()
+        .eq(&BooleanArray::from(vec![None, Some(false), Some(true)])));
+
+    assert!(rb
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap()
+        .eq(&Float64Array::from(vec![
+            Some(10.9),
+            Some(5.2),
+            Some(-10.0),
+        ])));
+}
+
+pub fn verify_arrow_results(result: Vec<RecordBatch>) {
+    assert!(result.len() == 2);
+
+    for r in result {
+        match r.num_rows() {
+            2 => {
+                assert!(r
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .eq(&Int64Array::from(vec![1, 0])));
+                assert!(r
+                    .column(1)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .eq(&Int64Array::from(vec![3, 5])));
+                assert!(r
+                    .column(2)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap()
+                    .eq(&StringArray::from(vec!["str1", "a"])));
+                assert!(r
+                    .column(3)
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .unwrap()
+                    .eq(&Float64Array::from(vec![None, Some(3.1)])));
+                assert!(r
+                    .column(4)
+                    .as_any()
+                    .downcast_ref::<BooleanArray>()
+                    .unwrap()
+                    .eq(&BooleanArray::from(vec![Some(true), None])));
+            }
+            4 => {
+                assert!(r
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .eq(&Int64Array::from(vec![2, 3, 4, 1314])));
+                assert!(r
+                    .column(1)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .eq(&Int64Array::from(vec![None, Some(7), Some(9), Some(2)])));
+                assert!(r
+                    .column(2)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap()
+                    .eq(&StringArray::from(vec![
+                        Some("str2"),
+                        Some("b"),
+                        Some("c"),
+                        None
+                    ])));
+                assert!(r
+                    .column(3)
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .unwrap()
+                    .eq(&Float64Array::from(vec![2.2, 3., 7.8, -10.])));
+                assert!(r
+                    .column(4)
+                    .as_any()
+                    .downcast_ref::<BooleanArray>()
+                    .unwrap()
+                    .eq(&BooleanArray::from(vec![
+                        Some(false),
+                        Some(false),
+                        None,
+                        Some(true)
+                    ])));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn test_postgres_ssh_tunnel_args() {
+    // the tunnel needs a user and a key
+    let url = Url::parse("postgresql://postgres@db.internal:5432/db?sshhost=bastion").unwrap();
+    assert!(rewrite_conn_args(&url).is_err());
+
+    // a config that skips the tunnel is refused, whatever else is set
+    let url = Url::parse(
+        "postgresql://postgres@db.internal:5432/db?sshhost=bastion.invalid&sshuser=me&sshkey=/nonexistent",
+    )
+    .unwrap();
+    assert!(rewrite_tls_args(&url).is_err());
+
+    // nothing is dialed until the source opens the tunnel, so an unknown bastion is fine here
+    #[cfg(all(feature = "ssh_tunnel", unix))]
+    {
+        let (config, _tls, pool_config) = rewrite_conn_args(&url).unwrap();
+        let tunnel = pool_config.ssh_tunnel.unwrap();
+        assert_eq!("db.internal", tunnel.remote_host);
+        assert_eq!(5432, tunnel.remote_port);
+        assert_eq!(
+            &[postgres::config::Host::Unix(tunnel.socket_dir.clone())],
+            config.get_hosts()
+        );
+        assert_eq!(&[5432], config.get_ports());
+    }
+}
+
+#[test]
+fn load_and_parse_ordered_partition() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let query = single_col_partition_query(
+        "select test_int from test_table",
+        "test_int",
+        0,
+        10000,
+        true,
+        &PostgreSqlDialect {},
+    )
+    .unwrap();
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::Wrapped(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    partition.result_rows().unwrap();
+    assert_eq!(6, partition.nrows());
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(vec![0, 1, 2, 3, 4, 1314], rows);
+}
+
+#[test]
+fn load_and_parse_quoted_partition_column() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    assert_eq!(
+        r#""Test ""Int""""#,
+        quote_ident(r#"Test "Int""#, &PostgreSqlDialect {})
+    );
+    let sql = r#"select test_int as "Test Int" from test_table"#;
+    let col = quote_ident("Test Int", &PostgreSqlDialect {});
+    let query =
+        single_col_partition_query(sql, &col, 0, 10000, true, &PostgreSqlDialect {}).unwrap();
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::Wrapped(query)]);
+    source.fetch_metadata().unwrap();
+    assert_eq!(vec!["Test Int".to_string()], source.names());
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(vec![0, 1, 2, 3, 4, 1314], rows);
+
+    // anything but an identifier is rejected rather than written into the query
+    let injected = "test_int >= 0 OR 1 = 1 OR test_int";
+    assert!(matches!(
+        single_col_partition_query(sql, injected, 0, 10, false, &PostgreSqlDialect {}),
+        Err(ConnectorXError::InvalidPartitionColumn(ref col)) if col == injected
+    ));
+    assert!(single_col_partition_query_in_range(sql, injected, 0, 2, false).is_err());
+}
+
+#[test]
+fn load_and_parse_partition_range_in_query() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries: Vec<_> = (0..3)
+        .map(|i| {
+            CXQuery::Wrapped(
+                single_col_partition_query_in_range(
+                    "select test_int from test_table",
+                    "test_int",
+                    i,
+                    3,
+                    true,
+                )
+                .unwrap(),
+            )
+        })
+        .collect();
+
+    // the range is 0 to 1314, split at 438 and 876
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 3).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut rows = vec![];
+    for mut partition in source.partition().unwrap() {
+        partition.result_rows().unwrap();
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let part: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+        rows.push(part);
+    }
+    assert_eq!(vec![vec![0, 1, 2, 3, 4], vec![], vec![1314]], rows);
+}
+
+#[test]
+fn load_and_parse_uuid_bytes() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let uuid = Uuid::parse_str("86b494cc-96b2-11eb-9298-3e22fbb9fe9d").unwrap();
+    let queries = [CXQuery::naked(format!(
+        "select v as test_bytes, v as test_array from (values ('{}'::uuid), (null)) as t(v)",
+        uuid
+    ))];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut rows: Vec<(Option<Vec<u8>>, Option<[u8; 16]>)> = vec![];
+            for _ in 0..n {
+                rows.push((parser.produce().unwrap(), parser.produce().unwrap()));
+            }
+            assert_eq!(
+                vec![
+                    (Some(uuid.as_bytes().to_vec()), Some(*uuid.as_bytes())),
+                    (None, None)
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn test_csv_cannot_produce_column() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select 1 as user_id, 'abc' as user_age")]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let _: i32 = parser.produce().unwrap();
+    let err = Produce::<i32>::produce(&mut parser).unwrap_err();
+    assert!(
+        err.to_string().ends_with("column 'user_age': abc."),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn load_and_parse_hstore_as_json() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS hstore")
+        .unwrap();
+
+    let mut source = PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select * from (values ('a=>1, b=>NULL'::hstore, '{\"c\": 2}'::json), (null, null)) as t(test_hstore, test_json)",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<serde_json::Value>> =
+        (0..n * 2).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(
+        vec![
+            Some(serde_json::json!({"a": "1", "b": null})),
+            Some(serde_json::json!({"c": 2})),
+            None,
+            None,
+        ],
+        rows
+    );
+}
+
+#[test]
+fn load_and_parse_bpchar_array() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (array['ab', 'abcd', 'a\"b', 'c,d']::char(4)[]), ('{}'), (null)) as t(test_bpchararray)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<Vec<String>>> =
+                (0..n).map(|_| parser.produce().unwrap()).collect();
+            // padded like a scalar char(4)
+            assert_eq!(
+                vec![
+                    Some(vec![
+                        "ab  ".to_string(),
+                        "abcd".to_string(),
+                        "a\"b ".to_string(),
+                        "c,d ".to_string(),
+                    ]),
+                    Some(vec![]),
+                    None
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_explain() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "EXPLAIN (ANALYZE, FORMAT JSON) select * from test_table",
+    )];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    assert!(matches!(
+        source.fetch_metadata(),
+        Err(PostgresSourceError::NotCopyable(_, "binary"))
+    ));
+
+    let mut source = PostgresSource::<AutoProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    partition.result_rows().unwrap();
+    assert_eq!(1, partition.nrows());
+    let mut parser = partition.parser().unwrap();
+    assert!(matches!(parser, PostgresAutoSourceParser::Cursor(_)));
+    parser.fetch_next().unwrap();
+    let plan: serde_json::Value = parser.produce().unwrap();
+    assert_eq!(Some("Seq Scan"), plan[0]["Plan"]["Node Type"].as_str());
+    assert!(plan[0]["Execution Time"].is_number());
+
+    // counting the lines of a text plan would run the analyzed statement twice
+    let mut source =
+        PostgresSource::<CursorProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "explain analyze delete from test_table where test_int = 0",
+    )]);
+    assert!(matches!(
+        source.fetch_metadata(),
+        Err(PostgresSourceError::UncountableExplain(_))
+    ));
+
+    let mut source = PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("explain select * from test_table")]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    partition.result_rows().unwrap();
+    assert!(partition.nrows() > 0);
+
+    let dialect = PostgreSqlDialect {};
+    assert_eq!(None, explain_options("select 1", &dialect));
+    assert_eq!(
+        Some(ExplainOptions {
+            analyze: true,
+            format: None
+        }),
+        explain_options("EXPLAIN ANALYZE VERBOSE select 1", &dialect)
+    );
+    assert_eq!(
+        Some(ExplainOptions {
+            analyze: false,
+            format: Some("YAML".to_string())
+        }),
+        explain_options(
+            "/* plan */ explain (analyze off, format yaml) select 1",
+            &dialect
+        )
+    );
+    assert_eq!(
+        Some(ExplainOptions::default()),
+        explain_options("explain (analyze false, format text) select 1", &dialect)
+    );
+}
+
+#[test]
+fn load_and_parse_float_array_as_decimal() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values (array[0.1, -1e-7, 123456789.125, 1e20]::float8[]), ('{}'), (null)) as t(test_floatarray)",
+    )];
+    let nan = [CXQuery::naked("select array[1, 'NaN']::float8[]")];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<Vec<Decimal>>> =
+                (0..n).map(|_| parser.produce().unwrap()).collect();
+            // the shortest decimals that read back as the same floats
+            assert_eq!(
+                vec![
+                    Some(vec![
+                        "0.1".parse::<Decimal>().unwrap(),
+                        "-0.0000001".parse::<Decimal>().unwrap(),
+                        "123456789.125".parse::<Decimal>().unwrap(),
+                        "100000000000000000000".parse::<Decimal>().unwrap(),
+                    ]),
+                    Some(vec![]),
+                    None
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&nan);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            assert!(matches!(
+                Produce::<Vec<Decimal>>::produce(&mut parser),
+                Err(PostgresSourceError::NumericNaN(_))
+            ));
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_timestamp_as_epoch_micros() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select v::timestamp as test_timestamp, (v || '+00')::timestamptz as test_timestamptz \
+         from (values ('1970-01-01 00:00:01.5'), ('2000-01-01 00:00:00'), ('1969-12-31 23:59:59.999999'), (null)) as t(v)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut rows: Vec<(Option<i64>, Option<i64>)> = vec![];
+            for _ in 0..n {
+                rows.push((parser.produce().unwrap(), parser.produce().unwrap()));
+            }
+            assert_eq!(
+                vec![
+                    (Some(1_500_000), Some(1_500_000)),
+                    (Some(946_684_800_000_000), Some(946_684_800_000_000)),
+                    (Some(-1), Some(-1)),
+                    (None, None),
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_date_as_epoch_days() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('1970-01-02'::date), ('2000-01-01'), ('1969-12-31'), (null)) as t(test_date)",
+    )];
+
+    macro_rules! check {
+        ($proto: ty) => {{
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&queries);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<i32>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(
+                vec![Some(1), Some(10_957), Some(-1), None],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+        }};
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_max_rows() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    source.set_max_rows(Some(3));
+    source.set_queries(&[
+        CXQuery::naked("select test_int from test_table where test_int < 2"),
+        CXQuery::naked("select test_int from test_table where test_int >= 2"),
+    ]);
+    source.fetch_metadata().unwrap();
+
+    let mut counts = vec![];
+    for mut partition in source.partition().unwrap() {
+        partition.result_rows().unwrap();
+        let nrows = partition.nrows();
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        assert_eq!(nrows, n);
+        counts.push(n);
+    }
+    // the partitions have 2 and 4 rows
+    assert_eq!(vec![2, 3], counts);
+}
+
+#[test]
+fn load_and_parse_unsupported_type() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let queries = [CXQuery::naked(
+        "select * from (values ('1.2.3.4'::inet, '(1,2)'::point), (null, null)) as t(test_inet, test_point)",
+    )];
+
+    // named in the error of the protocols reading binary values
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    let err = source.fetch_metadata().unwrap_err();
+    assert!(
+        matches!(&err, PostgresSourceError::UnsupportedType(name, ty, "binary") if name == "test_inet" && ty == "inet"),
+        "{}",
+        err
+    );
+
+    // and passed through as text by CSV
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<String>> = (0..n * 2)
+        .map(|_| {
+            Produce::<Option<&str>>::produce(&mut parser)
+                .unwrap()
+                .map(str::to_string)
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            Some("1.2.3.4".to_string()),
+            Some("(1,2)".to_string()),
+            None,
+            None
+        ],
+        rows
+    );
+}
+
+#[test]
+fn load_and_parse_json_path() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values (1, '{\"a\": {\"b\": \"x\"}}'::jsonb), (2, '{\"a\": {\"b\": 3}}'::jsonb), (3, '{\"c\": 1}'::jsonb)) as t(test_int, test_jsonb)";
+    let mut json_paths = HashMap::new();
+    json_paths.insert(
+        "test_jsonb".to_string(),
+        vec!["a".to_string(), "b".to_string()],
+    );
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.set_json_paths(json_paths.clone());
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[..],
+        [
+            PostgresTypeSystem::Int4(true),
+            PostgresTypeSystem::Text(true)
+        ]
+    ));
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let mut rows = vec![];
+    for _ in 0..n {
+        let id: i32 = parser.produce().unwrap();
+        let value = Produce::<Option<&str>>::produce(&mut parser)
+            .unwrap()
+            .map(str::to_string);
+        rows.push((id, value));
+    }
+    assert_eq!(
+        vec![
+            (1, Some("x".to_string())),
+            (2, Some("3".to_string())),
+            (3, None)
+        ],
+        rows
+    );
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.set_json_paths(json_paths);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(3, n);
+    let _: i32 = parser.produce().unwrap();
+    let value = Produce::<Option<&str>>::produce(&mut parser)
+        .unwrap()
+        .map(str::to_string);
+    assert_eq!(Some("x".to_string()), value);
+
+    let mut json_paths = HashMap::new();
+    json_paths.insert("test_int".to_string(), vec!["a".to_string()]);
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.set_json_paths(json_paths);
+    assert!(source.fetch_metadata().is_err());
+}
+
+#[test]
+fn drop_partition_mid_read() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    // a single connection, which every source below gets back from the pool
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+
+    let mut binary = source.reset();
+    binary.set_queries(&[CXQuery::naked(
+        "select i from generate_series(1, 10000000) i",
+    )]);
+    binary.fetch_metadata().unwrap();
+    let mut partition = binary.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, is_last) = parser.fetch_next().unwrap();
+    assert!(n > 0 && !is_last);
+    drop(parser);
+    drop(partition);
+
+    let mut again = source.reset();
+    again.set_queries(&[CXQuery::naked(
+        "select test_int from test_table order by test_int",
+    )]);
+    again.fetch_metadata().unwrap();
+    let mut partition = again.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(vec![0, 1, 2, 3, 4, 1314], rows);
+}
+
+#[test]
+fn load_and_parse_bit() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values (B'1', B'1010'::varbit), (B'0', B''::varbit), (null, null)) as t(test_bit, test_varbit)";
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[..],
+        [PostgresTypeSystem::Bit(true), PostgresTypeSystem::Bit(true)]
+    ));
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(3, n);
+    let mut rows = vec![];
+    for _ in 0..n {
+        let bit: Option<bool> = parser.produce().unwrap();
+        let bits: Option<Vec<bool>> = parser.produce().unwrap();
+        rows.push((bit, bits));
+    }
+    assert_eq!(
+        vec![
+            (Some(true), Some(vec![true, false, true, false])),
+            (Some(false), Some(vec![])),
+            (None, None),
+        ],
+        rows
+    );
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let bit: Option<bool> = parser.produce().unwrap();
+    let bits: Option<Vec<bool>> = parser.produce().unwrap();
+    assert_eq!(
+        (Some(true), Some(vec![true, false, true, false])),
+        (bit, bits)
+    );
+
+    // only a single bit reads as a bool
+    let mut source = PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let bit: Option<bool> = parser.produce().unwrap();
+    assert_eq!(Some(true), bit);
+    assert!(Produce::<Option<bool>>::produce(&mut parser).is_err());
+}
+
+#[test]
+fn load_and_parse_bit_mask() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    // 40 bits, 2^39 + 5
+    let query = "select * from (values (B'00101001'::bit(8), B'1000000000000000000000000000000000000101'::varbit), (null, B''::varbit), (B'11111111', null)) as t(test_flags, test_mask)";
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut rows = vec![];
+            {
+                let mut parser = partition.parser().unwrap();
+                let (n, _) = parser.fetch_next().unwrap();
+                for _ in 0..n {
+                    let flags: Option<u32> = parser.produce().unwrap();
+                    let mask: Option<u64> = parser.produce().unwrap();
+                    rows.push((flags, mask));
+                }
+            }
+            assert_eq!(
+                vec![
+                    (Some(0b0010_1001), Some((1 << 39) + 5)),
+                    (None, Some(0)),
+                    (Some(0xff), None),
+                ],
+                rows,
+                "{}",
+                stringify!($proto)
+            );
+
+            // 40 bits do not fit a u32
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let _: Option<u32> = parser.produce().unwrap();
+            assert!(
+                matches!(
+                    Produce::<Option<u32>>::produce(&mut parser),
+                    Err(PostgresSourceError::BitStringTooWide(ref name, 40, 32)) if name == "test_mask"
+                ),
+                "{}",
+                stringify!($proto)
+            );
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_table_partitions() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS cx_parted;
+            CREATE TABLE cx_parted (id int4, region text) PARTITION BY LIST (region);
+            CREATE TABLE cx_parted_eu PARTITION OF cx_parted FOR VALUES IN ('eu');
+            CREATE TABLE cx_parted_us PARTITION OF cx_parted FOR VALUES IN ('us')
+                PARTITION BY RANGE (id);
+            CREATE TABLE cx_parted_us_low PARTITION OF cx_parted_us FOR VALUES FROM (0) TO (10);
+            CREATE TABLE cx_parted_us_high PARTITION OF cx_parted_us FOR VALUES FROM (10) TO (100);
+            INSERT INTO cx_parted VALUES (1, 'eu'), (2, 'eu'), (3, 'us'), (20, 'us'), (30, 'us');",
+        )
+        .unwrap();
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 3).unwrap();
+    source.partition_by_children("cx_parted", "id").unwrap();
+    source.fetch_metadata().unwrap();
+
+    let mut ids = vec![];
+    for mut partition in source.partition().unwrap() {
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let mut rows: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+        rows.sort();
+        ids.push(rows);
+    }
+    // one query per leaf, in the order of their names
+    assert_eq!(vec![vec![1, 2], vec![20, 30], vec![3]], ids);
+}
+
+#[test]
+fn load_and_parse_bytea_as_string() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query =
+        "select * from (values ('\\x68656c6c6f'::bytea), (''::bytea), (null)) as t(test_bytea)";
+    let expected = |encoding| match encoding {
+        ByteaEncoding::Hex => vec![
+            Some("\\x68656c6c6f".to_string()),
+            Some("\\x".to_string()),
+            None,
+        ],
+        ByteaEncoding::Base64 => vec![Some("aGVsbG8=".to_string()), Some("".to_string()), None],
+    };
+
+    for encoding in [ByteaEncoding::Hex, ByteaEncoding::Base64] {
+        let mut source =
+            PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_bytea_encoding(encoding);
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let rows: Vec<Option<String>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+        assert_eq!(expected(encoding), rows);
+
+        let mut source =
+            PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_bytea_encoding(encoding);
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let rows: Vec<Option<String>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+        assert_eq!(expected(encoding), rows);
+    }
+}
+
+#[test]
+fn load_and_parse_bytea_as_boxed_slice() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query =
+        "select * from (values ('\\x68656c6c6f'::bytea), (''::bytea), (null)) as t(test_bytea)";
+    let expected: Vec<Option<Box<[u8]>>> = vec![
+        Some(Box::from(&b"hello"[..])),
+        Some(Box::from(&b""[..])),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<Box<[u8]>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_uuid_array_as_bytes() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let uuids = [
+        Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").unwrap(),
+        Uuid::parse_str("00112233-4455-6677-8899-aabbccddeeff").unwrap(),
+    ];
+    let query = format!(
+        "select * from (values (array['{}', '{}']::uuid[]), ('{{}}'), (null)) as t(test_uuids)",
+        uuids[0], uuids[1]
+    );
+    let expected: Vec<Option<Vec<[u8; 16]>>> = vec![
+        Some(uuids.iter().map(|v| *v.as_bytes()).collect()),
+        Some(vec![]),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query.as_str())]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<Vec<[u8; 16]>>> =
+                (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows);
+
+            // the same bytes one after the other
+            let concatenated = expected.iter().map(|v| v.as_ref().map(|v| v.concat()));
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query.as_str())]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<Vec<u8>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(concatenated.collect::<Vec<_>>(), rows);
+            assert_eq!(32, rows[0].as_ref().unwrap().len());
+        };
+    }
+
+    // the csv protocol cannot read a uuid[]
+    check!(BinaryProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_timestamptz_output_timezone() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('2021-07-01 12:00:00+00'::timestamptz), ('2021-12-01 12:00:00+00'::timestamptz), (null)) as t(test_timestamptz)";
+    // EDT in july, EST in december
+    let expected = vec![
+        Some(New_York.ymd(2021, 7, 1).and_hms(8, 0, 0)),
+        Some(New_York.ymd(2021, 12, 1).and_hms(7, 0, 0)),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_output_timezone(New_York);
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<DateTime<Tz>>> =
+                (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows);
+            assert_eq!(
+                Some("2021-07-01T08:00:00-04:00"),
+                rows[0].as_ref().map(|v| v.to_rfc3339()).as_deref()
+            );
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_timestamp_assume_utc() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('2020-01-02 03:04:05.678'::timestamp), (null)) as t(test_timestamp)";
+    let expected = vec![
+        Some(DateTime::<Utc>::from_utc(
+            NaiveDate::from_ymd(2020, 1, 2).and_hms_milli(3, 4, 5, 678),
+            Utc,
+        )),
+        None,
+    ];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_assume_utc(true);
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[..],
+        [PostgresTypeSystem::TimestampTz(true)]
+    ));
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<DateTime<Utc>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_assume_utc(true);
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<DateTime<Utc>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+
+    // the naive produce by default
+    let mut source = PostgresSource::<CursorProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[..],
+        [PostgresTypeSystem::Timestamp(true)]
+    ));
+}
+
+#[test]
+fn load_and_parse_table_functions() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "CREATE OR REPLACE FUNCTION cx_setof_record() RETURNS SETOF record
+                LANGUAGE sql AS $$ VALUES (1, 'a'::text), (2, NULL) $$;
+            CREATE OR REPLACE FUNCTION cx_out_params(n int4, OUT id int4, OUT label text)
+                RETURNS SETOF record
+                LANGUAGE sql AS $$ SELECT i, 'label ' || i FROM generate_series(1, n) i $$;",
+        )
+        .unwrap();
+
+    let queries = [
+        "select g as id, null::text as label from generate_series(1, 2) g",
+        "select * from cx_setof_record() as t(id int4, label text)",
+        "select * from cx_out_params(2)",
+    ];
+    let expected = [
+        vec![(1, None), (2, None)],
+        vec![(1, Some("a".to_string())), (2, None)],
+        vec![
+            (1, Some("label 1".to_string())),
+            (2, Some("label 2".to_string())),
+        ],
+    ];
+
+    for (query, expected) in queries.iter().zip(expected.iter()) {
+        let mut source =
+            PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        // looks for generated columns of the tables read, which a table function is not
+        source.set_exclude_generated(true);
+        source.set_queries(&[CXQuery::naked(*query)]);
+        source.fetch_metadata().unwrap();
+        assert!(matches!(
+            source.schema()[..],
+            [PostgresTypeSystem::Int4(_), PostgresTypeSystem::Text(_)]
+        ));
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let mut rows = vec![];
+        for _ in 0..n {
+            let id: i32 = parser.produce().unwrap();
+            let label = Produce::<Option<&str>>::produce(&mut parser)
+                .unwrap()
+                .map(str::to_string);
+            rows.push((id, label));
+        }
+        assert_eq!(expected, &rows);
+
+        let mut source =
+            PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_queries(&[CXQuery::naked(*query)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let mut rows = vec![];
+        for _ in 0..n {
+            let id: i32 = parser.produce().unwrap();
+            let label = Produce::<Option<&str>>::produce(&mut parser)
+                .unwrap()
+                .map(str::to_string);
+            rows.push((id, label));
+        }
+        assert_eq!(expected, &rows);
+
+        let mut source =
+            PostgresSource::<CursorProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_queries(&[CXQuery::naked(*query)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let mut rows = vec![];
+        for _ in 0..n {
+            let id: i32 = parser.produce().unwrap();
+            let label = Produce::<Option<&str>>::produce(&mut parser)
+                .unwrap()
+                .map(str::to_string);
+            rows.push((id, label));
+        }
+        assert_eq!(expected, &rows);
+    }
+}
+
+#[test]
+fn load_and_parse_uuid_as_u128() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('00000000-0000-0000-0000-000000000101'::uuid), ('ffffffff-ffff-ffff-ffff-ffffffffffff'::uuid), (null)) as t(test_uuid)";
+    let expected = vec![Some(0x101), Some(u128::MAX), None];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<u128>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<u128>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+}
+
+#[test]
+fn load_and_parse_int4multirange() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('{[1,3),[5,7)}'::int4multirange), ('{(,0)}'), (null)) as t(test_multirange)";
+    let range = |lower: Option<i32>, upper| PgRange {
+        lower,
+        upper,
+        lower_inc: lower.is_some(),
+        upper_inc: false,
+    };
+    let expected = vec![
+        Some(vec![range(Some(1), Some(3)), range(Some(5), Some(7))]),
+        Some(vec![range(None, Some(0))]),
+        None,
+    ];
+
+    let mut source =
+        PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[..],
+        [PostgresTypeSystem::Int4MultiRange(true)]
+    ));
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<Vec<PgRange<i32>>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+
+    let mut source =
+        PostgresSource::<CursorProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<Vec<PgRange<i32>>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+
+    let mut source = PostgresSource::<CSVProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<Option<Vec<PgRange<i32>>>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(expected, rows);
+}
+
+#[test]
+fn load_and_parse_interval_as_micros() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('1 day 00:00:01.5'::interval), ('-2 hours'::interval), ('1 year 1 mon'::interval), (null)) as t(test_interval)";
+    let expected = vec![
+        Some(86_401_500_000),
+        Some(-7_200_000_000),
+        Some(13 * 30 * 86_400_000_000),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            // months have no length unless one is set
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            assert_eq!(expected[0], parser.produce().unwrap());
+            assert_eq!(expected[1], parser.produce().unwrap());
+            assert!(matches!(
+                Produce::<Option<i64>>::produce(&mut parser),
+                Err(PostgresSourceError::IntervalMicros(..))
+            ));
+
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_interval_month_days(Some(30));
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<i64>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_time_micros() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('23:59:59.999999'::time(6)), ('00:00:00.000001'), ('12:34:56'), (null)) as t(test_time)";
+    let expected = vec![
+        Some(NaiveTime::from_hms_micro(23, 59, 59, 999_999)),
+        Some(NaiveTime::from_hms_micro(0, 0, 0, 1)),
+        Some(NaiveTime::from_hms(12, 34, 56)),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<NaiveTime>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows, "{}", stringify!($proto));
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_interval_parts() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values ('1 year 2 mons -3 days 04:05:06.789'::interval), ('-1 mon 35 days -00:00:00.000001'::interval), ('00:00:00'::interval), (null)) as t(test_interval)";
+    let expected = vec![
+        Some((14, -3, 14_706_789_000)),
+        Some((-1, 35, -1)),
+        Some((0, 0, 0)),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<Option<(i32, i32, i64)>> =
+                (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows, "{}", stringify!($proto));
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_duration_arrays() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select * from (values (array['00:30:00', '12:00:00.5']::time[], array['1 day 02:00:00', null]::interval[]), (null, null)) as t(test_times, test_intervals)";
+    let expected = vec![
+        (
+            Some(vec![
+                chrono::Duration::minutes(30),
+                chrono::Duration::hours(12) + chrono::Duration::milliseconds(500),
+            ]),
+            Some(vec![Some(chrono::Duration::hours(26)), None]),
+        ),
+        (None, None),
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [
+                    PostgresTypeSystem::TimeArray(true),
+                    PostgresTypeSystem::IntervalArray(true)
+                ]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(
+                Option<Vec<chrono::Duration>>,
+                Option<Vec<Option<chrono::Duration>>>,
+            )> = (0..n)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(expected, rows);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_numeric_as_float() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select v as test_f64, v as test_f32 from (values ('1.5'::numeric), ('0.1'), ('123456789.123456789'), (null)) as t(v)";
+    let expected = vec![
+        (Some(1.5), Some(1.5)),
+        (Some(0.1), Some(0.1)),
+        (Some(123456789.12345679), Some(123456790.0)),
+        (None, None),
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<(Option<f64>, Option<f32>)> = (0..n)
+                .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+                .collect();
+            assert_eq!(expected, rows);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_large_object_chunks() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    let data: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+    let loid: u32 = client
+        .query_one("SELECT lo_from_bytea(0, $1)", &[&data])
+        .unwrap()
+        .get(0);
+    // a write past the end leaves a hole of missing pages, read as zeros
+    client
+        .execute("SELECT lo_put($1, 10000, '\\xff')", &[&loid])
+        .unwrap();
+    let expected: Vec<u8> = client
+        .query_one("SELECT lo_get($1)", &[&loid])
+        .unwrap()
+        .get(0);
+    assert_eq!(10001, expected.len());
+
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    let chunks: Vec<Vec<u8>> = source
+        .large_object_chunks(loid, 2)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        vec![4096, 5905],
+        chunks.iter().map(Vec::len).collect::<Vec<_>>()
+    );
+    assert_eq!(expected, chunks.concat());
+
+    client.execute("SELECT lo_unlink($1)", &[&loid]).unwrap();
+    assert!(source.large_object_chunks(loid, 2).is_err());
+}
+
+#[test]
+fn load_and_parse_xml_as_json() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = r#"select * from (values (E'<a b="c">d\\e\n</a>'::xml), (null)) as t(test_xml)"#;
+    let xml = "<a b=\"c\">d\\e\n</a>";
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [PostgresTypeSystem::Xml(true)]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let rows: Vec<Option<serde_json::Value>> =
+                (0..2).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(
+                vec![Some(serde_json::Value::String(xml.to_string())), None],
+                rows
+            );
+            assert_eq!(
+                r#""<a b=\"c\">d\\e\n</a>""#,
+                serde_json::to_string(rows[0].as_ref().unwrap()).unwrap()
+            );
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_resume_after_key() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select id from generate_series(1, 100) as t(id) order by id";
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_resume_key(Some("id".to_string()));
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            {
+                // the read stops in the second batch, the first one was consumed
+                let mut parser = partition.parser().unwrap();
+                parser.fetch_next().unwrap();
+                for _ in 0..32 {
+                    let _: i32 = parser.produce().unwrap();
+                }
+                parser.fetch_next().unwrap();
+                let _: i32 = parser.produce().unwrap();
             }
-            4 => {
-                assert!(r
-                    .column(0)
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .unwrap()
-                    .eq(&Int64Array::from(vec![2, 3, 4, 1314])));
-                assert!(r
-                    .column(1)
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .unwrap()
-                    .eq(&Int64Array::from(vec![None, Some(7), Some(9), Some(2)])));
-                assert!(r
-                    .column(2)
-                    .as_any()
-                    .downcast_ref::<StringArray>()
-                    .unwrap()
-                    .eq(&StringArray::from(vec![
-                        Some("str2"),
-                        Some("b"),
-                        Some("c"),
-                        None
-                    ])));
-                assert!(r
-                    .column(3)
-                    .as_any()
-                    .downcast_ref::<Float64Array>()
-                    .unwrap()
-                    .eq(&Float64Array::from(vec![2.2, 3., 7.8, -10.])));
-                assert!(r
-                    .column(4)
-                    .as_any()
-                    .downcast_ref::<BooleanArray>()
-                    .unwrap()
-                    .eq(&BooleanArray::from(vec![
-                        Some(false),
-                        Some(false),
-                        None,
-                        Some(true)
-                    ])));
+            assert_eq!(Some(32), partition.last_key());
+
+            let mut parser = partition.parser().unwrap();
+            let mut ids = vec![];
+            loop {
+                let (n, is_last) = parser.fetch_next().unwrap();
+                for _ in 0..n {
+                    let id: i32 = parser.produce().unwrap();
+                    ids.push(id);
+                }
+                if is_last {
+                    break;
+                }
             }
-            _ => unreachable!(),
+            assert_eq!((33..=100).collect::<Vec<_>>(), ids);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_resume_key(Some("id".to_string()));
+    source.set_queries(&[CXQuery::naked(
+        "select id from generate_series(1, 100) as t(id)",
+    )]);
+    assert!(matches!(
+        source.fetch_metadata(),
+        Err(PostgresSourceError::NotOrderedByKey(..))
+    ));
+}
+
+#[test]
+fn load_with_partition_sql() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select current_setting('enable_seqscan') as seqscan";
+
+    macro_rules! check {
+        ($proto:ty) => {
+            // a single connection, so the second source checks out the one the first used
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            let mut next = source.reset();
+            source.set_partition_sql(Some("SET LOCAL enable_seqscan = off".to_string()));
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            {
+                let mut partition = source.partition().unwrap().remove(0);
+                let mut parser = partition.parser().unwrap();
+                parser.fetch_next().unwrap();
+                let seqscan: &str = parser.produce().unwrap();
+                assert_eq!("off", seqscan);
+            }
+
+            next.set_queries(&[CXQuery::naked(query)]);
+            next.fetch_metadata().unwrap();
+            let mut partition = next.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let seqscan: &str = parser.produce().unwrap();
+            assert_eq!("on", seqscan);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_at_exported_snapshot() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = config.connect(NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS test_snapshot; CREATE TABLE test_snapshot (v int); INSERT INTO test_snapshot VALUES (1)",
+        )
+        .unwrap();
+    // exported the same way `CREATE_REPLICATION_SLOT ... EXPORT_SNAPSHOT` does, and kept open
+    let mut exporter = config.connect(NoTls).unwrap();
+    let mut tx = exporter
+        .build_transaction()
+        .isolation_level(postgres::IsolationLevel::RepeatableRead)
+        .start()
+        .unwrap();
+    let snapshot: String = tx
+        .query_one("SELECT pg_export_snapshot()", &[])
+        .unwrap()
+        .get(0);
+    client
+        .batch_execute("INSERT INTO test_snapshot VALUES (2)")
+        .unwrap();
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_snapshot(Some(snapshot.clone()));
+            source.set_queries(&[CXQuery::naked("select v from test_snapshot")]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            partition.result_rows().unwrap();
+            assert_eq!(1, partition.nrows(), "{}", stringify!($proto));
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(vec![1], rows, "{}", stringify!($proto));
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+
+    tx.rollback().unwrap();
+    client.batch_execute("DROP TABLE test_snapshot").unwrap();
+}
+
+#[test]
+fn test_postgres_check_columns() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let aligned = [
+        CXQuery::naked("select test_int, test_str from test_table where test_int < 2"),
+        CXQuery::naked("select test_int, test_str from test_table where test_int >= 2"),
+    ];
+    let swapped = [
+        CXQuery::naked("select test_int, test_str from test_table where test_int < 2"),
+        CXQuery::naked("select test_str, test_int from test_table where test_int >= 2"),
+    ];
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let mut unchecked = source.reset();
+    source.set_check_columns(true);
+    let mut checked = source.reset();
+    source.set_queries(&aligned);
+    source.fetch_metadata().unwrap();
+
+    checked.set_queries(&swapped);
+    assert!(matches!(
+        checked.fetch_metadata(),
+        Err(PostgresSourceError::ColumnMismatch(_, ref columns, ref names))
+            if columns == &["test_str", "test_int"] && names == &["test_int", "test_str"]
+    ));
+
+    // only checked on demand
+    unchecked.set_queries(&swapped);
+    unchecked.fetch_metadata().unwrap();
+}
+
+#[test]
+fn load_json_rows() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = r#"select * from (values
+        (1, 1.5::float8, 12.30::numeric, 'a"b'::text, '\x68656c6c6f'::bytea, '2020-01-02 03:04:05'::timestamp, '{1,2}'::int4[], '{"k": [1]}'::jsonb),
+        (null, null, null, null, null, null, null, null)
+    ) as t(i, f, n, s, b, ts, a, j)"#;
+    let expected = vec![
+        serde_json::json!([
+            1,
+            1.5,
+            "12.30",
+            "a\"b",
+            "aGVsbG8=",
+            "2020-01-02T03:04:05",
+            [1, 2],
+            {"k": [1]}
+        ]),
+        serde_json::json!([null, null, null, null, null, null, null, null]),
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let rows: Vec<serde_json::Value> = (0..n)
+                .map(|_| serde_json::Value::Array(parser.produce_json_row().unwrap()))
+                .collect();
+            assert_eq!(expected, rows, "{}", stringify!($proto));
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_enum_order() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TYPE IF EXISTS test_enum_order;
+            CREATE TYPE test_enum_order AS ENUM ('low', 'high');",
+        )
+        .unwrap();
+    // not in the same transaction as the CREATE, which the simple query of a batch runs in
+    client
+        .batch_execute("ALTER TYPE test_enum_order ADD VALUE 'medium' BEFORE 'high'")
+        .unwrap();
+    // alphabetically high < low < medium, the enum defines low < medium < high
+    let query = "select * from (values ('high'::test_enum_order), ('low'), ('medium'), (null)) as t(test_enum)";
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_enum_order(true);
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [PostgresTypeSystem::Int4(true)]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let rows: Vec<Option<i32>> = (0..4).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(vec![Some(3), Some(1), Some(2), None], rows);
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+
+    // the labels are still read by default
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let label: Option<&str> = parser.produce().unwrap();
+    assert_eq!(Some("high"), label);
+}
+
+#[test]
+fn load_and_parse_json_path_and_enum_order() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TYPE IF EXISTS test_enum_proj;
+            CREATE TYPE test_enum_proj AS ENUM ('low', 'high');",
+        )
+        .unwrap();
+    // both options read their column through an expression of the same wrapping query
+    let query = "select * from (values ('high'::test_enum_proj, '{\"a\": \"x\"}'::jsonb), ('low', null)) as t(test_enum, test_jsonb)";
+    let mut json_paths = HashMap::new();
+    json_paths.insert("test_jsonb".to_string(), vec!["a".to_string()]);
+
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_enum_order(true);
+    source.set_json_paths(json_paths);
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[..],
+        [
+            PostgresTypeSystem::Int4(true),
+            PostgresTypeSystem::Text(true)
+        ]
+    ));
+    let mut partition = source.partition().unwrap().remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<(Option<i32>, Option<String>)> = (0..n)
+        .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+        .collect();
+    assert_eq!(
+        vec![(Some(2), Some("x".to_string())), (Some(1), None)],
+        rows
+    );
+}
+
+#[test]
+fn load_and_parse_record_array() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let query = "select (select array_agg(row(i, 'x' || i)) from generate_series(1, 2) as s(i)) as test_records union all select null";
+    let expected = vec![
+        Some(vec![
+            serde_json::json!({"f1": 1, "f2": "x1"}),
+            serde_json::json!({"f1": 2, "f2": "x2"}),
+        ]),
+        None,
+    ];
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [PostgresTypeSystem::RecordArray(true)]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            parser.fetch_next().unwrap();
+            let rows: Vec<Option<Vec<serde_json::Value>>> =
+                (0..2).map(|_| parser.produce().unwrap()).collect();
+            assert_eq!(expected, rows, "{}", stringify!($proto));
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn load_and_parse_extended_statistics() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS test_ext_stats;
+            CREATE TABLE test_ext_stats AS SELECT i % 10 AS a, i % 10 AS b FROM generate_series(1, 100) AS s(i);
+            CREATE STATISTICS test_ext_stats_ab (ndistinct, dependencies) ON a, b FROM test_ext_stats;
+            ANALYZE test_ext_stats;",
+        )
+        .unwrap();
+    let query = "select n_distinct, dependencies from pg_stats_ext where statistics_name = 'test_ext_stats_ab'";
+
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked(query)]);
+            source.fetch_metadata().unwrap();
+            assert!(matches!(
+                source.schema()[..],
+                [
+                    PostgresTypeSystem::Text(true),
+                    PostgresTypeSystem::Text(true)
+                ]
+            ));
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            assert_eq!(1, n);
+            let ndistinct: String = parser.produce().unwrap();
+            let dependencies: String = parser.produce().unwrap();
+            // a and b are the first and second columns, b depends on a and the other way around
+            assert!(ndistinct.contains(r#""1, 2": 10"#), "{}", ndistinct);
+            assert!(
+                dependencies.contains(r#""1 => 2": 1.000000"#),
+                "{}",
+                dependencies
+            );
+        };
+    }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+    check!(CursorProtocol);
+}
+
+#[test]
+fn test_plain_table_scan() {
+    let dialect = PostgreSqlDialect {};
+    assert_eq!(
+        Some(("test_table".to_string(), None)),
+        plain_table_scan("select * from test_table", &dialect)
+    );
+    assert_eq!(
+        Some((
+            "public.test_table".to_string(),
+            Some(vec!["test_int".to_string(), "\"test_str\"".to_string()])
+        )),
+        plain_table_scan(
+            "select test_int, \"test_str\" from public.test_table",
+            &dialect
+        )
+    );
+    for sql in [
+        "select * from test_table where test_int > 1",
+        "select test_int + 1 from test_table",
+        "select test_int as i from test_table",
+        "select test_int, test_int from test_table",
+        "select * from test_table t",
+        "select * from test_table limit 1",
+        "select * from test_table order by test_int",
+        "select distinct test_int from test_table",
+        "select * from generate_series(1, 2)",
+        "select * from (select * from test_table) t",
+    ] {
+        assert_eq!(None, plain_table_scan(sql, &dialect), "{}", sql);
+    }
+}
+
+#[test]
+fn load_and_parse_plain_table_scan() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute("CREATE OR REPLACE VIEW cx_plain_view AS SELECT * FROM test_table")
+        .unwrap();
+
+    // a table is copied directly, a view through the query
+    for query in [
+        "select test_str, test_int from test_table",
+        "select test_str, test_int from cx_plain_view",
+    ] {
+        let mut source =
+            PostgresSource::<BinaryProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        let mut ints = vec![];
+        for _ in 0..n {
+            let _: Option<&str> = parser.produce().unwrap();
+            let v: i32 = parser.produce().unwrap();
+            ints.push(v);
         }
+        ints.sort();
+        assert_eq!(vec![0, 1, 2, 3, 4, 1314], ints);
+
+        let mut source =
+            PostgresSource::<CSVProtocol, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+        source.set_csv_header(true);
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        assert_eq!(6, n);
+    }
+}
+
+#[test]
+fn load_and_parse_inheritance_parent() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+
+    let mut client = postgres::Client::connect(dburl.as_str(), NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS cx_inherit_parent CASCADE;
+            CREATE TABLE cx_inherit_parent(test_int INTEGER);
+            CREATE TABLE cx_inherit_child() INHERITS (cx_inherit_parent);
+            INSERT INTO cx_inherit_parent VALUES (1), (2);
+            INSERT INTO cx_inherit_child VALUES (3);",
+        )
+        .unwrap();
+
+    // `COPY cx_inherit_parent` would leave out the row of the child
+    macro_rules! check {
+        ($proto:ty) => {
+            let mut source =
+                PostgresSource::<$proto, NoTls>::new(config.clone(), NoTls, 1).unwrap();
+            source.set_queries(&[CXQuery::naked("select * from cx_inherit_parent")]);
+            source.fetch_metadata().unwrap();
+            let mut partition = source.partition().unwrap().remove(0);
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut ints: Vec<i32> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            ints.sort();
+            assert_eq!(vec![1, 2, 3], ints, "{}", stringify!($proto));
+        };
     }
+
+    check!(BinaryProtocol);
+    check!(CSVProtocol);
+
+    client
+        .batch_execute("DROP TABLE cx_inherit_parent CASCADE;")
+        .unwrap();
 }